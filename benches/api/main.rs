@@ -10,5 +10,6 @@ criterion_group!(
     render::render_sparse,
     render::render_few,
     render::render_empty,
+    render::encode_many,
 );
 criterion_main!(benches);
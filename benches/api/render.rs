@@ -1,7 +1,12 @@
 use super::utils::load_dataset;
 use criterion::{BatchSize, Criterion};
+use geo::Geometry;
+use geozero::{ToMvt, mvt::tile::Layer};
 use h3o::{CellIndex, Resolution};
-use h3o_mvt::TileID;
+use h3o_mvt::{
+    MvtEncoder, PreparedDataset, Renderer, TileEncoder, TileFeature, TileID,
+    render_geometry,
+};
 use std::hint::black_box;
 
 pub fn render_full(c: &mut Criterion) {
@@ -75,6 +80,32 @@ pub fn render_empty(c: &mut Criterion) {
     bench_render(c, "Render/Empty", cells, tile);
 }
 
+pub fn encode_many(c: &mut Criterion) {
+    let cells = load_dataset("many", Resolution::Ten);
+    let tile = TileID::new(1626, 901, 11).expect("valid tile id");
+    let geometry = render_geometry(tile, cells, false).expect("geometry");
+    let mut group = c.benchmark_group("Encode/Many");
+
+    // Commands encoded straight from the projected rings (which are also
+    // rounded and repaired).
+    let mut encoder = MvtEncoder::new();
+    group.bench_function("Direct", |b| {
+        b.iter(|| {
+            encoder.encode("empty", &[TileFeature::new(black_box(&geometry))])
+        })
+    });
+    // Former encoding, through geozero (truncated coordinates, no repair).
+    group.bench_function("Geozero", |b| {
+        b.iter_batched(
+            || Geometry::MultiPolygon(geometry.clone()),
+            |geometry| black_box(geometry).to_mvt_unscaled(),
+            BatchSize::SmallInput,
+        )
+    });
+
+    group.finish();
+}
+
 fn bench_render(
     c: &mut Criterion,
     name: &str,
@@ -112,5 +143,26 @@ fn bench_render(
         )
     });
 
+    let mut renderer = Renderer::new("empty".to_owned(), false);
+    let mut layer = Layer::default();
+    group.bench_function("Renderer", |b| {
+        b.iter_batched(
+            || cells.clone(),
+            |cells| {
+                renderer.render_into(
+                    black_box(tile),
+                    black_box(cells),
+                    &mut layer,
+                )
+            },
+            BatchSize::SmallInput,
+        )
+    });
+
+    let dataset = PreparedDataset::new(cells).expect("valid cells");
+    group.bench_function("Prepared", |b| {
+        b.iter(|| dataset.render(black_box(tile), "empty".to_owned(), false))
+    });
+
     group.finish();
 }
@@ -1,16 +1,19 @@
-use ahash::{HashMap, HashSet};
+use ahash::HashMap;
 use axum::{
     Router,
     extract::{Path, State},
-    http::Method,
+    http::{Method, StatusCode},
     response::IntoResponse,
     routing::get,
 };
 use clap::Parser;
 use geozero::mvt::Message as _;
-use h3o::{CellIndex, Resolution};
-use h3o_mvt::TileID;
-use std::sync::OnceLock;
+use h3o::Resolution;
+use h3o_mvt::{Aggregation, Limits, PreparedDataset, Pyramid, TileID};
+use std::{
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
 use tower_http::cors::{Any, CorsLayer};
 
 /// A vector tile server for H3 dataset.
@@ -31,6 +34,9 @@ struct Args {
     scratch: bool,
 }
 
+/// Maximum time spent rendering a tile.
+const RENDER_TIMEOUT: Duration = Duration::from_secs(1);
+
 #[derive(Clone, Copy)]
 struct Config {
     scratch: bool,
@@ -63,39 +69,21 @@ async fn handler(
     Path((z, x, y)): Path<(u8, u32, u32)>,
 ) -> impl IntoResponse {
     let tile_id = TileID::new(x, y, z).expect("valid tile ID");
-    let resolution = match tile_id.zoom() {
-        0..=2 => Resolution::Four,
-        3 | 4 => Resolution::Five,
-        5 => Resolution::Six,
-        6 | 7 => Resolution::Seven,
-        8..=9 => Resolution::Eight,
-        10 => Resolution::Nine,
-        _ => Resolution::Ten,
-    };
-    let data = get_data(resolution);
-
-    // At zoom level 0, the whole world is covered.
-    let content = if tile_id.zoom() == 0 {
-        data.clone()
-    } else {
-        let bbox = tile_id.cells(resolution);
-        let bbox = bbox.into_iter().collect::<HashSet<_>>();
-        data & &bbox
-    };
+    let dataset = get_dataset(resolution_for(tile_id));
+    let limits = Limits::new().deadline(Instant::now() + RENDER_TIMEOUT);
     // The name here must match the `source-layer` in `viewers.html`.
-    let layer =
-        h3o_mvt::render(tile_id, content, "h3".to_owned(), state.scratch)
-            .expect("rendered MVT layer");
+    let layer = dataset
+        .render_with_limits(tile_id, "h3".to_owned(), state.scratch, &limits)
+        .map_err(|_| StatusCode::SERVICE_UNAVAILABLE)?;
     let tile = geozero::mvt::Tile {
         layers: vec![layer],
     };
-    tile.encode_to_vec()
+    Ok::<_, StatusCode>(tile.encode_to_vec())
 }
 
 // -----------------------------------------------------------------------------
 
-static DATA: OnceLock<HashMap<Resolution, HashSet<CellIndex>>> =
-    OnceLock::new();
+static DATA: OnceLock<HashMap<Resolution, PreparedDataset>> = OnceLock::new();
 
 fn load_dataset(path: &std::path::Path) {
     let bytes = std::fs::read(path).expect("read dataset");
@@ -103,25 +91,37 @@ fn load_dataset(path: &std::path::Path) {
         .flat_map(|index| {
             index.expect("corrupted data").children(Resolution::Ten)
         })
-        .collect::<HashSet<_>>();
+        .map(|cell| (cell, 1.));
 
     // Precompute data shape for the supported resolutions.
-    let mut data = Resolution::range(Resolution::Four, Resolution::Nine)
+    let pyramid = Pyramid::new(indexes, Resolution::Four, Aggregation::Count)
+        .expect("homogeneous dataset");
+    let data = Resolution::range(pyramid.coarsest(), pyramid.finest())
         .map(|res| {
-            let cells = indexes
-                .iter()
-                .copied()
-                .map(|cell| cell.parent(res).expect("supported resolution"))
-                .collect::<HashSet<_>>();
-            (res, cells)
+            let cells = pyramid.level(res).expect("pre-computed level").keys();
+            let dataset =
+                PreparedDataset::new(cells.copied()).expect("valid dataset");
+            (res, dataset)
         })
-        .collect::<HashMap<Resolution, HashSet<CellIndex>>>();
-    data.insert(Resolution::Ten, indexes);
+        .collect::<HashMap<Resolution, PreparedDataset>>();
 
     DATA.set(data).expect("set pre-computed data");
 }
 
-// Get the dataset scaled at the requested resolution.
-fn get_data(resolution: Resolution) -> &'static HashSet<CellIndex> {
+// Get the dataset at the requested resolution.
+fn get_dataset(resolution: Resolution) -> &'static PreparedDataset {
     &DATA.get().expect("requested resolution not pre-computed")[&resolution]
 }
+
+// Resolution used to display a tile.
+const fn resolution_for(tile_id: TileID) -> Resolution {
+    match tile_id.zoom() {
+        0..=2 => Resolution::Four,
+        3 | 4 => Resolution::Five,
+        5 => Resolution::Six,
+        6 | 7 => Resolution::Seven,
+        8..=9 => Resolution::Eight,
+        10 => Resolution::Nine,
+        _ => Resolution::Ten,
+    }
+}
@@ -0,0 +1,502 @@
+use ahash::HashMap;
+use geo::{Coord, LineString, MultiPolygon, Polygon, Rect};
+use std::cmp::Ordering;
+
+/// Clips polygons against an axis-aligned rectangle.
+///
+/// This is a specialization of the Weiler–Atherton algorithm: every ring is
+/// cut into chains by the rectangle (segments being clipped with the
+/// Liang–Barsky algorithm), then the chains are stitched back together by
+/// walking along the rectangle boundary. Rings that are not cut are either
+/// kept as is (inside) or only used to know if the rectangle is filled
+/// (outside).
+///
+/// Input polygons must be valid and must not overlap. Output rings are
+/// oriented with a positive area for exteriors and a negative one for holes.
+pub fn clip(polygons: &MultiPolygon, rect: Rect) -> MultiPolygon {
+    clip_with(polygons, rect, &mut Buffers::default())
+}
+
+/// Clips polygons against an axis-aligned rectangle, like [`clip`], using
+/// the given scratch buffers.
+pub fn clip_with(
+    polygons: &MultiPolygon,
+    rect: Rect,
+    buffers: &mut Buffers,
+) -> MultiPolygon {
+    let clipper = Clipper::new(rect);
+    let Buffers {
+        coords,
+        pieces,
+        chains,
+    } = buffers;
+    let mut rings = Vec::new();
+    let mut enclosing = 0_usize;
+
+    for polygon in polygons {
+        let exterior = std::iter::once((polygon.exterior(), true));
+        let interiors = polygon.interiors().iter().map(|ring| (ring, false));
+        for (ring, is_exterior) in exterior.chain(interiors) {
+            if !normalize(ring, is_exterior, coords) {
+                continue;
+            }
+            match clipper.cut(coords, pieces, chains) {
+                Position::Inside => {
+                    let mut ring = Vec::with_capacity(coords.len() + 1);
+                    ring.extend_from_slice(coords);
+                    ring.push(coords[0]);
+                    rings.push(LineString::new(ring));
+                }
+                Position::Outside => {
+                    enclosing += usize::from(contains(coords, rect.center()));
+                }
+                Position::Crossing => (),
+            }
+        }
+    }
+
+    if chains.is_empty() {
+        // Without chains, the rectangle is either fully covered or empty.
+        if enclosing % 2 == 1 {
+            let (min, max) = (rect.min(), rect.max());
+            rings.push(LineString::from(vec![
+                (min.x, min.y),
+                (max.x, min.y),
+                (max.x, max.y),
+                (min.x, max.y),
+                (min.x, min.y),
+            ]));
+        }
+    } else {
+        clipper.stitch(chains, &mut rings);
+    }
+
+    assemble(rings)
+}
+
+// -----------------------------------------------------------------------------
+
+/// Scratch buffers, reusable across clipping operations.
+#[derive(Debug, Default)]
+pub struct Buffers {
+    /// Vertices of the current ring.
+    coords: Vec<Coord>,
+    /// Clipped segments of the current ring.
+    pieces: Vec<Option<(f64, f64)>>,
+    /// Chains cut out of the rings.
+    chains: Vec<Chain>,
+}
+
+/// Position of a ring relative to the clipping rectangle.
+enum Position {
+    /// The ring is inside the rectangle (boundary included).
+    Inside,
+    /// The ring doesn't enter the rectangle.
+    Outside,
+    /// The ring has been cut into chains.
+    Crossing,
+}
+
+/// Part of a ring inside the rectangle, entering and leaving on its boundary.
+#[derive(Debug)]
+struct Chain {
+    /// Chain vertices.
+    coords: Vec<Coord>,
+    /// Position of the first vertex along the boundary.
+    entry: f64,
+    /// Position of the last vertex along the boundary.
+    exit: f64,
+}
+
+/// Rectangle clipper.
+struct Clipper {
+    /// Lower corner.
+    min: Coord,
+    /// Upper corner.
+    max: Coord,
+}
+
+impl Clipper {
+    fn new(rect: Rect) -> Self {
+        Self {
+            min: rect.min(),
+            max: rect.max(),
+        }
+    }
+
+    /// Cuts a closed ring (without repeated closing vertex) into chains.
+    fn cut(
+        &self,
+        ring: &[Coord],
+        pieces: &mut Vec<Option<(f64, f64)>>,
+        chains: &mut Vec<Chain>,
+    ) -> Position {
+        let segments = ring.iter().zip(ring.iter().cycle().skip(1));
+        pieces.clear();
+        pieces.extend(
+            segments.map(|(&start, &end)| self.clip_segment(start, end)),
+        );
+
+        // Start right after a segment leaving the rectangle, this way chains
+        // are never split across the end of the ring.
+        let Some(first) = pieces
+            .iter()
+            .position(|piece| piece.is_none_or(|(_, end)| end < 1.))
+        else {
+            return Position::Inside;
+        };
+
+        let initial_count = chains.len();
+        let mut current: Vec<Coord> = Vec::new();
+        for offset in 1..=pieces.len() {
+            let index = (first + offset) % pieces.len();
+            let (start, end) = (ring[index], ring[(index + 1) % ring.len()]);
+            match pieces[index] {
+                Some((t0, t1)) => {
+                    if t0 > 0. {
+                        self.push_chain(&mut current, chains);
+                    }
+                    if current.is_empty() {
+                        current.push(self.interpolate(start, end, t0));
+                    }
+                    current.push(self.interpolate(start, end, t1));
+                    if t1 < 1. {
+                        self.push_chain(&mut current, chains);
+                    }
+                }
+                None => self.push_chain(&mut current, chains),
+            }
+        }
+        self.push_chain(&mut current, chains);
+
+        if chains.len() == initial_count {
+            Position::Outside
+        } else {
+            Position::Crossing
+        }
+    }
+
+    /// Stitches chains into closed rings, following the rectangle boundary.
+    ///
+    /// The chains are consumed.
+    fn stitch(&self, chains: &mut Vec<Chain>, rings: &mut Vec<LineString>) {
+        // Sort the entries along the boundary, to find the next chain.
+        let mut entries = (0..chains.len()).collect::<Vec<_>>();
+        entries.sort_unstable_by(|&lhs, &rhs| {
+            chains[lhs]
+                .entry
+                .partial_cmp(&chains[rhs].entry)
+                .unwrap_or(Ordering::Equal)
+        });
+        let next = chains
+            .iter()
+            .map(|chain| {
+                let index = entries
+                    .partition_point(|&id| chains[id].entry < chain.exit);
+                entries[index % entries.len()]
+            })
+            .collect::<Vec<_>>();
+
+        let waypoints = self.waypoints(chains);
+        let mut visited = vec![false; chains.len()];
+        for start in 0..chains.len() {
+            if visited[start] {
+                continue;
+            }
+            let mut coords = Vec::new();
+            let mut current = start;
+            loop {
+                visited[current] = true;
+                let chain = &mut chains[current];
+                coords.append(&mut chain.coords);
+                let target = next[current];
+                let from = chain.exit;
+                self.walk(from, chains[target].entry, &waypoints, &mut coords);
+                // Degenerated inputs may not form a perfect cycle.
+                if target == start || visited[target] {
+                    break;
+                }
+                current = target;
+            }
+            split_pinches(coords, rings);
+        }
+
+        chains.clear();
+    }
+
+    /// Returns the points of interest along the boundary, with their
+    /// position: the corners, and the vertices where chains touch the
+    /// boundary (which must be visited to detect pinches).
+    fn waypoints(&self, chains: &[Chain]) -> Vec<(f64, Coord)> {
+        let (width, height) =
+            (self.max.x - self.min.x, self.max.y - self.min.y);
+        let corners = [
+            (0., self.min),
+            (width, Coord::from((self.max.x, self.min.y))),
+            (width + height, self.max),
+            (
+                2_f64.mul_add(width, height),
+                Coord::from((self.min.x, self.max.y)),
+            ),
+        ];
+        let touches = chains
+            .iter()
+            .filter_map(|chain| {
+                chain.coords.get(1..chain.coords.len().saturating_sub(1))
+            })
+            .flatten()
+            .filter(|coord| self.on_boundary(**coord))
+            .map(|&coord| (self.position(coord), coord));
+
+        corners.into_iter().chain(touches).collect()
+    }
+
+    /// Walks along the boundary, from `from` to `to`, adding the waypoints.
+    fn walk(
+        &self,
+        from: f64,
+        to: f64,
+        waypoints: &[(f64, Coord)],
+        coords: &mut Vec<Coord>,
+    ) {
+        let perimeter = self.perimeter();
+        let distance = (to - from).rem_euclid(perimeter);
+        let mut visited = waypoints
+            .iter()
+            .map(|&(position, coord)| {
+                ((position - from).rem_euclid(perimeter), coord)
+            })
+            .filter(|&(offset, _)| offset > 0. && offset < distance)
+            .collect::<Vec<_>>();
+        visited.sort_unstable_by(|lhs, rhs| {
+            lhs.0.partial_cmp(&rhs.0).unwrap_or(Ordering::Equal)
+        });
+        coords.extend(visited.into_iter().map(|(_, coord)| coord));
+    }
+
+    /// Tests if the point lies on the boundary.
+    #[expect(clippy::float_cmp, reason = "exact match on purpose")]
+    fn on_boundary(&self, coord: Coord) -> bool {
+        coord.x == self.min.x
+            || coord.x == self.max.x
+            || coord.y == self.min.y
+            || coord.y == self.max.y
+    }
+
+    /// Finalizes the current chain, if any.
+    fn push_chain(&self, current: &mut Vec<Coord>, chains: &mut Vec<Chain>) {
+        if current.len() < 2 {
+            current.clear();
+            return;
+        }
+        let coords = std::mem::take(current);
+        let entry = self.position(coords[0]);
+        let exit = self.position(coords[coords.len() - 1]);
+        chains.push(Chain {
+            coords,
+            entry,
+            exit,
+        });
+    }
+
+    /// Clips a segment using the Liang–Barsky algorithm.
+    ///
+    /// Returns the parametric range of the segment inside the rectangle, if
+    /// not degenerated.
+    fn clip_segment(&self, start: Coord, end: Coord) -> Option<(f64, f64)> {
+        let delta = end - start;
+        let mut range = (0_f64, 1_f64);
+        let constraints = [
+            (-delta.x, start.x - self.min.x),
+            (delta.x, self.max.x - start.x),
+            (-delta.y, start.y - self.min.y),
+            (delta.y, self.max.y - start.y),
+        ];
+        for (p, q) in constraints {
+            if p == 0. {
+                if q < 0. {
+                    return None;
+                }
+                continue;
+            }
+            let t = q / p;
+            if p < 0. {
+                range.0 = range.0.max(t);
+            } else {
+                range.1 = range.1.min(t);
+            }
+        }
+
+        (range.0 < range.1).then_some(range)
+    }
+
+    /// Returns the point at `t` on the segment, clamped into the rectangle.
+    fn interpolate(&self, start: Coord, end: Coord, t: f64) -> Coord {
+        let coord = match t {
+            0. => start,
+            1. => end,
+            _ => start + (end - start) * t,
+        };
+        Coord {
+            x: coord.x.clamp(self.min.x, self.max.x),
+            y: coord.y.clamp(self.min.y, self.max.y),
+        }
+    }
+
+    /// Returns the position of a boundary point, as the distance walked along
+    /// the boundary from the lower corner (with the rectangle on the left).
+    fn position(&self, coord: Coord) -> f64 {
+        let (width, height) =
+            (self.max.x - self.min.x, self.max.y - self.min.y);
+        let distances = [
+            coord.y - self.min.y,
+            self.max.x - coord.x,
+            self.max.y - coord.y,
+            coord.x - self.min.x,
+        ];
+        let edge = (0..4)
+            .min_by(|&lhs, &rhs| {
+                distances[lhs]
+                    .abs()
+                    .partial_cmp(&distances[rhs].abs())
+                    .unwrap_or(Ordering::Equal)
+            })
+            .expect("four edges");
+
+        match edge {
+            0 => coord.x - self.min.x,
+            1 => width + coord.y - self.min.y,
+            2 => width + height + self.max.x - coord.x,
+            _ => 2_f64.mul_add(width, height) + self.max.y - coord.y,
+        }
+    }
+
+    /// Returns the perimeter of the rectangle.
+    fn perimeter(&self) -> f64 {
+        2. * ((self.max.x - self.min.x) + (self.max.y - self.min.y))
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+/// Writes the vertices of the ring (without the closing one) into `coords`,
+/// oriented with a positive area for an exterior and a negative one for a
+/// hole.
+///
+/// Returns false for degenerated rings.
+fn normalize(
+    ring: &LineString,
+    is_exterior: bool,
+    coords: &mut Vec<Coord>,
+) -> bool {
+    coords.clear();
+    coords.extend_from_slice(&ring.0);
+    coords.dedup();
+    if coords.len() > 1 && coords.first() == coords.last() {
+        coords.pop();
+    }
+    if coords.len() < 3 {
+        return false;
+    }
+
+    let area = signed_area(coords);
+    if area == 0. {
+        return false;
+    }
+    if (area > 0.) != is_exterior {
+        coords.reverse();
+    }
+
+    true
+}
+
+/// Splits a stitched ring (without repeated closing vertex) where it touches
+/// itself, which happens when a vertex lies on the rectangle boundary.
+fn split_pinches(coords: Vec<Coord>, rings: &mut Vec<LineString>) {
+    let mut path = Vec::<Coord>::with_capacity(coords.len() + 1);
+    let mut positions = HashMap::<(u64, u64), usize>::default();
+    let first = coords.first().copied();
+    for coord in coords.into_iter().chain(first) {
+        let key = (coord.x.to_bits(), coord.y.to_bits());
+        if let Some(&position) = positions.get(&key) {
+            // Close the loop, and resume the path from the pinch.
+            let mut ring = path.split_off(position);
+            for vertex in &ring[1..] {
+                positions.remove(&(vertex.x.to_bits(), vertex.y.to_bits()));
+            }
+            ring.push(coord);
+            if ring.len() > 3 {
+                rings.push(LineString::new(ring));
+            }
+        } else {
+            positions.insert(key, path.len());
+        }
+        path.push(coord);
+    }
+}
+
+/// Assembles oriented rings into polygons.
+///
+/// Each hole is assigned to the smallest exterior containing it.
+fn assemble(rings: Vec<LineString>) -> MultiPolygon {
+    let mut exteriors = Vec::new();
+    let mut holes = Vec::new();
+    for ring in rings {
+        let area = signed_area(&ring.0);
+        if area > 0. {
+            exteriors.push((ring, area, Vec::new()));
+        } else if area < 0. {
+            holes.push(ring);
+        }
+    }
+
+    for hole in holes {
+        // Use the middle of an edge, which is unlikely to be on another ring
+        // (unlike vertices which may be shared).
+        let point = (hole.0[0] + hole.0[1]) / 2.;
+        let parent = exteriors
+            .iter_mut()
+            .filter(|(exterior, _, _)| contains(&exterior.0, point))
+            .min_by(|lhs, rhs| {
+                lhs.1.partial_cmp(&rhs.1).unwrap_or(Ordering::Equal)
+            });
+        if let Some((_, _, interiors)) = parent {
+            interiors.push(hole);
+        }
+    }
+
+    exteriors
+        .into_iter()
+        .map(|(exterior, _, interiors)| Polygon::new(exterior, interiors))
+        .collect()
+}
+
+/// Computes the signed area of a ring (closed or not).
+fn signed_area(coords: &[Coord]) -> f64 {
+    let next = coords.iter().cycle().skip(1);
+    coords
+        .iter()
+        .zip(next)
+        .map(|(a, b)| a.x.mul_add(b.y, -(b.x * a.y)))
+        .sum::<f64>()
+        / 2.
+}
+
+/// Tests if a point is inside a ring (closed or not), using the crossing
+/// number algorithm.
+fn contains(coords: &[Coord], point: Coord) -> bool {
+    let next = coords.iter().cycle().skip(1);
+    coords.iter().zip(next).fold(false, |inside, (a, b)| {
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < (b.x - a.x) * (point.y - a.y) / (b.y - a.y) + a.x
+        {
+            !inside
+        } else {
+            inside
+        }
+    })
+}
+
+#[cfg(test)]
+#[path = "./clip_tests.rs"]
+mod tests;
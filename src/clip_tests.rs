@@ -0,0 +1,267 @@
+use super::*;
+use geo::{Area, BooleanOps, polygon};
+
+fn rect() -> Rect {
+    Rect::new((0., 0.), (10., 10.))
+}
+
+// Clips with both algorithms, and checks that the results are equivalent.
+fn check(polygons: &MultiPolygon) -> MultiPolygon {
+    let result = clip(polygons, rect());
+    let expected =
+        polygons.intersection(&MultiPolygon::new(vec![rect().to_polygon()]));
+
+    assert_eq!(result.0.len(), expected.0.len(), "polygon count");
+    let holes = |polygons: &MultiPolygon| {
+        polygons
+            .iter()
+            .map(|polygon| polygon.interiors().len())
+            .sum::<usize>()
+    };
+    assert_eq!(holes(&result), holes(&expected), "hole count");
+    assert!(
+        (result.signed_area() - expected.unsigned_area()).abs() < 1e-9,
+        "area mismatch: {} vs {}",
+        result.signed_area(),
+        expected.unsigned_area()
+    );
+
+    result
+}
+
+#[test]
+fn inside() {
+    let polygon = polygon![
+        (x: 2., y: 2.),
+        (x: 8., y: 2.),
+        (x: 8., y: 8.),
+        (x: 2., y: 8.),
+    ];
+
+    let result = check(&MultiPolygon::new(vec![polygon]));
+
+    assert_eq!(result.0[0].exterior().0.len(), 5);
+}
+
+#[test]
+fn outside() {
+    let polygon = polygon![
+        (x: 20., y: 2.),
+        (x: 28., y: 2.),
+        (x: 28., y: 8.),
+        (x: 20., y: 8.),
+    ];
+
+    let result = check(&MultiPolygon::new(vec![polygon]));
+
+    assert!(result.0.is_empty());
+}
+
+#[test]
+fn enclosing() {
+    let polygon = polygon![
+        (x: -5., y: -5.),
+        (x: 15., y: -5.),
+        (x: 15., y: 15.),
+        (x: -5., y: 15.),
+    ];
+
+    let result = check(&MultiPolygon::new(vec![polygon]));
+
+    assert_eq!(result.0[0].exterior().0.len(), 5);
+}
+
+#[test]
+fn enclosing_hole() {
+    let polygon = polygon![
+        exterior: [
+            (x: -10., y: -10.),
+            (x: 20., y: -10.),
+            (x: 20., y: 20.),
+            (x: -10., y: 20.),
+        ],
+        interiors: [[
+            (x: -5., y: -5.),
+            (x: 15., y: -5.),
+            (x: 15., y: 15.),
+            (x: -5., y: 15.),
+        ]],
+    ];
+
+    let result = check(&MultiPolygon::new(vec![polygon]));
+
+    assert!(result.0.is_empty());
+}
+
+#[test]
+fn multipart() {
+    // A U-shape, whose bottom is outside of the rectangle.
+    let polygon = polygon![
+        (x: 2., y: -5.),
+        (x: 8., y: -5.),
+        (x: 8., y: 5.),
+        (x: 6., y: 5.),
+        (x: 6., y: -2.),
+        (x: 4., y: -2.),
+        (x: 4., y: 5.),
+        (x: 2., y: 5.),
+    ];
+
+    let result = check(&MultiPolygon::new(vec![polygon]));
+
+    assert_eq!(result.0.len(), 2);
+}
+
+#[test]
+fn hole_inside() {
+    let polygon = polygon![
+        exterior: [
+            (x: -5., y: -5.),
+            (x: 15., y: -5.),
+            (x: 15., y: 5.),
+            (x: -5., y: 5.),
+        ],
+        interiors: [[
+            (x: 2., y: 2.),
+            (x: 4., y: 2.),
+            (x: 4., y: 4.),
+            (x: 2., y: 4.),
+        ]],
+    ];
+
+    let result = check(&MultiPolygon::new(vec![polygon]));
+
+    assert_eq!(result.0[0].interiors().len(), 1);
+}
+
+#[test]
+fn hole_crossing() {
+    let polygon = polygon![
+        exterior: [
+            (x: -5., y: -5.),
+            (x: 15., y: -5.),
+            (x: 15., y: 15.),
+            (x: -5., y: 15.),
+        ],
+        interiors: [[
+            (x: 2., y: 2.),
+            (x: 12., y: 2.),
+            (x: 12., y: 8.),
+            (x: 2., y: 8.),
+        ]],
+    ];
+
+    let result = check(&MultiPolygon::new(vec![polygon]));
+
+    assert!(result.0[0].interiors().is_empty());
+}
+
+#[test]
+fn island_in_hole() {
+    let polygons = vec![
+        polygon![
+            exterior: [
+                (x: -10., y: -10.),
+                (x: 20., y: -10.),
+                (x: 20., y: 20.),
+                (x: -10., y: 20.),
+            ],
+            interiors: [[
+                (x: -5., y: -5.),
+                (x: 15., y: -5.),
+                (x: 15., y: 15.),
+                (x: -5., y: 15.),
+            ]],
+        ],
+        polygon![
+            exterior: [
+                (x: 5., y: 5.),
+                (x: 12., y: 5.),
+                (x: 12., y: 8.),
+                (x: 5., y: 8.),
+            ],
+            interiors: [[
+                (x: 6., y: 6.),
+                (x: 7., y: 6.),
+                (x: 7., y: 7.),
+                (x: 6., y: 7.),
+            ]],
+        ],
+    ];
+
+    let result = check(&MultiPolygon::new(polygons));
+
+    assert_eq!(result.0.len(), 1);
+    assert_eq!(result.0[0].interiors().len(), 1);
+}
+
+#[test]
+fn along_boundary() {
+    // Shares the bottom and the left edges with the rectangle.
+    let polygon = polygon![
+        (x: 0., y: 0.),
+        (x: 12., y: 0.),
+        (x: 12., y: 5.),
+        (x: 0., y: 5.),
+    ];
+
+    let result = check(&MultiPolygon::new(vec![polygon]));
+
+    assert_eq!(result.0.len(), 1);
+}
+
+#[test]
+fn touching_outside() {
+    // Shares an edge with the rectangle, from the outside.
+    let polygon = polygon![
+        (x: 10., y: 2.),
+        (x: 15., y: 2.),
+        (x: 15., y: 8.),
+        (x: 10., y: 8.),
+    ];
+
+    let result = check(&MultiPolygon::new(vec![polygon]));
+
+    assert!(result.0.is_empty());
+}
+
+#[test]
+fn pinch() {
+    // The bottom of the V touches the top edge of the rectangle, which splits
+    // the clipped shape in two.
+    let polygon = polygon![
+        (x: -5., y: 5.),
+        (x: 5., y: 10.),
+        (x: 15., y: 5.),
+        (x: 15., y: 15.),
+        (x: -5., y: 15.),
+    ];
+
+    let result = check(&MultiPolygon::new(vec![polygon]));
+
+    assert_eq!(result.0.len(), 2);
+}
+
+#[test]
+fn orientation() {
+    // Clockwise exterior, counter-clockwise hole.
+    let polygon = polygon![
+        exterior: [
+            (x: 2., y: 2.),
+            (x: 2., y: 8.),
+            (x: 8., y: 8.),
+            (x: 8., y: 2.),
+        ],
+        interiors: [[
+            (x: 4., y: 4.),
+            (x: 6., y: 4.),
+            (x: 6., y: 6.),
+            (x: 4., y: 6.),
+        ]],
+    ];
+
+    let result = check(&MultiPolygon::new(vec![polygon]));
+
+    assert!(signed_area(&result.0[0].exterior().0) > 0.);
+    assert!(signed_area(&result.0[0].interiors()[0].0) < 0.);
+}
@@ -0,0 +1,277 @@
+use geo::{
+    BooleanOps, BoundingRect, Coord, CoordsIter, Intersects, LineString,
+    MapCoords, MapCoordsInPlace, MultiPolygon, Polygon, Rect, Relate,
+    ToRadians, coord,
+    coordinate_position::{CoordPos, coord_pos_relative_to_ring},
+    indexed::PreparedGeometry,
+};
+use h3o::{CellIndex, LatLng, Resolution, geom::ContainmentMode};
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+
+/// Tolerance, in radians, below which a cell isn't considered clear of the
+/// edges of the shape.
+const MARGIN: f64 = 1e-9;
+
+/// Polyfills a union of rectangles, in EPSG:4326 coordinates, into a
+/// compacted set of cells.
+///
+/// The refinement starts from coarse cells covering the shape, and only
+/// descends along its edges: cells (and their neighbors) lying well inside a
+/// rectangle are kept as is, and the ones lying well outside every rectangle
+/// are dropped. Only the cells at the target resolution along the edges are
+/// tested, with the same predicates as the H3 tiler.
+///
+/// The coarse cells must contain every cell of the coarse resolution
+/// intersecting the shape.
+pub fn polyfill(
+    rects: &[Rect],
+    coarse: impl IntoIterator<Item = CellIndex>,
+    resolution: Resolution,
+    mode: ContainmentMode,
+) -> Vec<CellIndex> {
+    let rects = rects.iter().map(ToRadians::to_radians).collect::<Vec<_>>();
+    let shape = rects
+        .iter()
+        .map(|rect| rect.to_polygon())
+        .collect::<MultiPolygon>();
+    let refiner = Refiner {
+        predicate: Predicate::new(&shape, mode),
+        rects,
+        resolution,
+    };
+
+    // Descendants stick out of their ancestor, but stay within its
+    // neighbors: expand the seeds to catch them all.
+    let mut seeds = coarse
+        .into_iter()
+        .flat_map(|cell| cell.grid_disk::<Vec<_>>(1))
+        .collect::<Vec<_>>();
+    seeds.sort_unstable();
+    seeds.dedup();
+
+    let mut cells = Vec::new();
+    for cell in seeds {
+        refiner.refine(cell, &mut cells);
+    }
+    cells.sort_unstable();
+    cells
+}
+
+/// Hierarchical polyfill of a shape.
+struct Refiner<'a> {
+    /// Exact containment predicate, for the cells at the target resolution.
+    predicate: Predicate<'a>,
+    /// Rectangles making the shape, in radians.
+    rects: Vec<Rect>,
+    /// Target resolution.
+    resolution: Resolution,
+}
+
+impl Refiner<'_> {
+    /// Appends the compacted coverage of the cell to `cells`.
+    ///
+    /// Returns true if every descendant of the cell is selected, in which
+    /// case the cell itself is appended.
+    fn refine(&self, cell: CellIndex, cells: &mut Vec<CellIndex>) -> bool {
+        if cell.resolution() == self.resolution {
+            let selected = self.predicate.apply(cell);
+            if selected {
+                cells.push(cell);
+            }
+            return selected;
+        }
+
+        match self.classify(cell) {
+            Some(true) => {
+                cells.push(cell);
+                return true;
+            }
+            Some(false) => return false,
+            None => {}
+        }
+
+        let start = cells.len();
+        let mut complete = true;
+        let children = cell.children(cell.resolution().succ().expect("finer"));
+        for child in children {
+            complete &= self.refine(child, cells);
+        }
+        if complete {
+            cells.truncate(start);
+            cells.push(cell);
+        }
+        complete
+    }
+
+    /// Tests if every descendant of the cell is inside the shape (true),
+    /// outside of it (false), or along its edges (`None`).
+    ///
+    /// The descendants are bounded by the cell and its neighbors.
+    fn classify(&self, cell: CellIndex) -> Option<bool> {
+        let mut bounds: Option<Rect> = None;
+        for neighbor in cell.grid_disk::<Vec<_>>(1) {
+            let ring = boundary(neighbor);
+            // Cells crossing the antimeridian cannot be bounded.
+            if is_transmeridian(&ring) {
+                return None;
+            }
+            let rect = ring.bounding_rect().expect("cell bounds");
+            bounds = Some(bounds.map_or(rect, |bounds| union(bounds, rect)));
+        }
+        let bounds = bounds.expect("non-empty disk");
+
+        if self
+            .rects
+            .iter()
+            .all(|rect| !grow(*rect, MARGIN).intersects(&bounds))
+        {
+            return Some(false);
+        }
+        self.rects
+            .iter()
+            .any(|rect| contains(grow(*rect, -MARGIN), bounds))
+            .then_some(true)
+    }
+}
+
+/// Containment predicate of a cell, as evaluated by the H3 tiler.
+enum Predicate<'a> {
+    /// The center of the cell is inside the shape.
+    ContainsCentroid(&'a MultiPolygon),
+    /// The boundary of the cell intersects (or is covered by) the shape.
+    Relate(Box<PreparedGeometry<'a, &'a MultiPolygon>>, ContainmentMode),
+}
+
+impl<'a> Predicate<'a> {
+    fn new(shape: &'a MultiPolygon, mode: ContainmentMode) -> Self {
+        if mode == ContainmentMode::ContainsCentroid {
+            Self::ContainsCentroid(shape)
+        } else {
+            Self::Relate(Box::new(PreparedGeometry::from(shape)), mode)
+        }
+    }
+
+    /// Tests if the cell is selected.
+    fn apply(&self, cell: CellIndex) -> bool {
+        match *self {
+            Self::ContainsCentroid(shape) => {
+                let center = LatLng::from(cell);
+                let coord = coord! {
+                    x: center.lng_radians(),
+                    y: center.lat_radians(),
+                };
+                shape
+                    .iter()
+                    .any(|polygon| contains_centroid(polygon.exterior(), coord))
+            }
+            Self::Relate(ref shape, mode) => {
+                let relation = shape.relate(&cell_shape(cell));
+                if mode == ContainmentMode::ContainsBoundary {
+                    relation.is_covers()
+                } else {
+                    relation.is_intersects()
+                }
+            }
+        }
+    }
+}
+
+/// Tests if a point is inside a ring, points on the edges being nudged
+/// northward (like the H3 tiler does).
+fn contains_centroid(ring: &LineString, mut coord: Coord) -> bool {
+    match coord_pos_relative_to_ring(coord, ring) {
+        CoordPos::Inside => true,
+        CoordPos::Outside => false,
+        CoordPos::OnBoundary => {
+            coord.y += f64::EPSILON;
+            coord_pos_relative_to_ring(coord, ring) == CoordPos::Inside
+        }
+    }
+}
+
+/// Returns the boundary of the cell, in radians.
+fn boundary(cell: CellIndex) -> LineString {
+    let mut ring = cell
+        .boundary()
+        .iter()
+        .map(|ll| coord! { x: ll.lng_radians(), y: ll.lat_radians() })
+        .collect::<LineString>();
+    ring.close();
+    ring
+}
+
+/// Returns the shape of the cell, in radians, split along the antimeridian
+/// if necessary (like the H3 tiler does).
+fn cell_shape(cell: CellIndex) -> MultiPolygon {
+    let mut ring = boundary(cell);
+    if !is_transmeridian(&ring) {
+        return MultiPolygon::new(vec![Polygon::new(ring, vec![])]);
+    }
+
+    // Shift the cell east of the antimeridian, then split it.
+    for coord in ring.coords_mut() {
+        coord.x += f64::from(coord.x < 0.) * TAU;
+    }
+    let polygon = Polygon::new(ring, vec![]);
+    let west = Rect::new((PI, -FRAC_PI_2), (TAU, FRAC_PI_2)).to_polygon();
+    let east = Rect::new((0., -FRAC_PI_2), (PI, FRAC_PI_2)).to_polygon();
+    let mut shape = polygon
+        .intersection(&west)
+        .map_coords(|coord| coord! { x: coord.x - TAU, y: coord.y });
+    snap_x(&mut shape, f64::min, -PI);
+    let mut east = polygon.intersection(&east);
+    snap_x(&mut east, f64::max, PI);
+    shape.0.extend(east);
+    shape
+}
+
+/// Snaps the extreme X coordinates of a split cell onto the antimeridian, to
+/// absorb the rounding errors of the split.
+fn snap_x(shape: &mut MultiPolygon, pick: fn(f64, f64) -> f64, value: f64) {
+    let Some(extreme) = shape.coords_iter().map(|coord| coord.x).reduce(pick)
+    else {
+        return;
+    };
+    #[expect(clippy::float_cmp, reason = "exact match on purpose")]
+    shape.map_coords_in_place(|coord| {
+        if coord.x == extreme {
+            coord! { x: value, y: coord.y }
+        } else {
+            coord
+        }
+    });
+}
+
+/// Tests if a ring crosses the antimeridian.
+fn is_transmeridian(ring: &LineString) -> bool {
+    ring.lines()
+        .any(|line| (line.start.x - line.end.x).abs() > PI)
+}
+
+/// Grows (or shrinks, if negative) a rectangle by the given margin.
+fn grow(rect: Rect, margin: f64) -> Rect {
+    let offset = coord! { x: margin, y: margin };
+    Rect::new(rect.min() - offset, rect.max() + offset)
+}
+
+/// Returns the bounding box of two rectangles.
+fn union(lhs: Rect, rhs: Rect) -> Rect {
+    Rect::new(
+        coord! {
+            x: lhs.min().x.min(rhs.min().x),
+            y: lhs.min().y.min(rhs.min().y),
+        },
+        coord! {
+            x: lhs.max().x.max(rhs.max().x),
+            y: lhs.max().y.max(rhs.max().y),
+        },
+    )
+}
+
+/// Tests if a rectangle contains another one.
+fn contains(outer: Rect, inner: Rect) -> bool {
+    outer.min().x <= inner.min().x
+        && outer.min().y <= inner.min().y
+        && inner.max().x <= outer.max().x
+        && inner.max().y <= outer.max().y
+}
@@ -0,0 +1,117 @@
+use crate::{DecodingError, TileID, clip};
+use ahash::HashSet;
+use geo::{
+    BooleanOps, Coord, Geometry, LineString, MapCoords, MultiPolygon, Polygon,
+    Rect,
+};
+use geozero::mvt::tile::{GeomType, Layer};
+use h3o::{
+    CellIndex, Resolution,
+    geom::{ContainmentMode, TilerBuilder},
+};
+
+/// Recovers the cells, at the given resolution, represented by a MVT layer
+/// rendered in the specified tile.
+///
+/// This is the inverse of [`render`](crate::render): the polygons of every
+/// feature are merged, the shape is turned inside out if the layer was
+/// rendered in scratch mode, then polyfilled. Only the cells whose center
+/// lies in the tile (buffer excluded) are returned.
+///
+/// # Errors
+///
+/// `DecodingError::InvalidGeometry` is returned if a geometry cannot be
+/// decoded, and `DecodingError::UnsupportedGeometry` if a feature isn't a
+/// polygon.
+///
+/// # Example
+///
+/// ```
+/// use h3o::{CellIndex, Resolution};
+/// use h3o_mvt::{decode_layer, render, tiles_for_cell};
+///
+/// let cell = CellIndex::try_from(0x891fb46622fffff)?;
+/// let cells = cell.children(Resolution::Ten).collect::<Vec<_>>();
+/// let tile_id = tiles_for_cell(cell, 14..=14).into_iter().next().expect("tile");
+///
+/// let layer = render(tile_id, cells.iter().copied(), "h3".to_owned(), false)?;
+/// let decoded = decode_layer(tile_id, &layer, Resolution::Ten, false)?;
+/// assert!(decoded.iter().all(|cell| cells.contains(cell)));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn decode_layer(
+    tile_id: TileID,
+    layer: &Layer,
+    resolution: Resolution,
+    scratch: bool,
+) -> Result<HashSet<CellIndex>, DecodingError> {
+    let extent = f64::from(TileID::extent());
+    let scale = extent / f64::from(layer.extent.unwrap_or(TileID::extent()));
+    let mut shape = MultiPolygon::new(Vec::new());
+    for (index, feature) in layer.features.iter().enumerate() {
+        let kind = feature
+            .r#type
+            .and_then(GeomType::from_i32)
+            .unwrap_or(GeomType::Unknown);
+        if kind != GeomType::Polygon {
+            return Err(DecodingError::UnsupportedGeometry(index));
+        }
+
+        let mut writer = geozero::geo_types::GeoWriter::new();
+        geozero::mvt::process_geom(feature, &mut writer)
+            .map_err(DecodingError::InvalidGeometry)?;
+        let polygons = match writer.take_geometry() {
+            Some(Geometry::Polygon(polygon)) => {
+                MultiPolygon::new(vec![polygon])
+            }
+            Some(Geometry::MultiPolygon(polygons)) => polygons,
+            None => continue,
+            Some(_) => return Err(DecodingError::UnsupportedGeometry(index)),
+        };
+        let polygons = polygons.map_coords(|coord| coord * scale);
+        shape = if shape.0.is_empty() {
+            polygons
+        } else {
+            shape.union(&polygons)
+        };
+    }
+
+    // Only keep the part inside the tile, turned inside out in scratch mode.
+    let tile = Rect::new((0., 0.), (extent, extent));
+    let shape = if scratch {
+        MultiPolygon::from(tile.to_polygon()).difference(&shape)
+    } else {
+        clip::clip(&shape, tile)
+    };
+
+    let mut tiler = TilerBuilder::new(resolution)
+        .containment_mode(ContainmentMode::ContainsCentroid)
+        .build();
+    for polygon in shape {
+        let (exterior, interiors) = polygon.into_inner();
+        let unproject = |ring: LineString| {
+            ring.0
+                .into_iter()
+                .map(|coord| Coord::from(tile_id.unproject(coord)))
+                .collect::<LineString>()
+        };
+        // Slivers may collapse when rounded into the tile grid.
+        if exterior.0.len() < 4 {
+            continue;
+        }
+        let interiors = interiors
+            .into_iter()
+            .filter(|ring| ring.0.len() >= 4)
+            .map(unproject)
+            .collect();
+        tiler
+            .add(Polygon::new(unproject(exterior), interiors))
+            .expect("valid coordinates");
+    }
+
+    Ok(tiler.into_coverage().collect())
+}
+
+#[cfg(test)]
+#[path = "./decode_tests.rs"]
+mod tests;
@@ -0,0 +1,111 @@
+use super::*;
+use crate::{render, tile::TileCoord, tiles_for_cell};
+use geozero::mvt::tile::Feature;
+use h3o::LatLng;
+
+/// Returns a dataset, the tile at its center, and the cells of the dataset
+/// centered in the tile.
+fn dataset() -> (Vec<CellIndex>, TileID, HashSet<CellIndex>) {
+    let center = LatLng::new(48.85, 2.35)
+        .expect("coordinate")
+        .to_cell(Resolution::Ten);
+    let mut cells = center.grid_disk::<Vec<_>>(12);
+    // Punch a hole in the dataset.
+    cells.retain(|&cell| center.grid_distance(cell).expect("distance") != 2);
+    let tile_id = tiles_for_cell(center, 16..=16)
+        .into_iter()
+        .find(|tile| tile.zoom() == 16)
+        .expect("tile");
+    let extent = f64::from(TileID::extent());
+    let expected = cells
+        .iter()
+        .copied()
+        .filter(|&cell| {
+            let coord = TileCoord::from_ll(LatLng::from(cell).into(), 16)
+                .project(tile_id);
+            (0. ..extent).contains(&coord.x) && (0. ..extent).contains(&coord.y)
+        })
+        .collect();
+
+    (cells, tile_id, expected)
+}
+
+#[test]
+fn roundtrip() {
+    let (cells, tile_id, expected) = dataset();
+    assert!(!expected.is_empty());
+
+    for scratch in [false, true] {
+        let layer = render(tile_id, cells.clone(), "h3".to_owned(), scratch)
+            .expect("render");
+
+        let decoded = decode_layer(tile_id, &layer, Resolution::Ten, scratch)
+            .expect("decode");
+
+        assert_eq!(decoded, expected, "scratch: {scratch}");
+    }
+}
+
+#[test]
+fn finer_resolution() {
+    let (cells, tile_id, _) = dataset();
+    let layer =
+        render(tile_id, cells.clone(), "h3".to_owned(), false).expect("render");
+
+    let decoded = decode_layer(tile_id, &layer, Resolution::Eleven, false)
+        .expect("decode");
+
+    assert!(!decoded.is_empty());
+    for cell in decoded {
+        let center = LatLng::from(cell).to_cell(Resolution::Ten);
+        assert!(cells.contains(&center), "{cell}");
+    }
+}
+
+#[test]
+fn empty() {
+    let (_, tile_id, _) = dataset();
+
+    for scratch in [false, true] {
+        let layer =
+            render(tile_id, [], "h3".to_owned(), scratch).expect("render");
+
+        let decoded =
+            decode_layer(tile_id, &layer, Resolution::Twelve, scratch)
+                .expect("decode");
+
+        assert!(decoded.is_empty(), "scratch: {scratch}");
+    }
+}
+
+#[test]
+fn full() {
+    let (_, tile_id, _) = dataset();
+    let expected = tile_id
+        .coverage(Resolution::Twelve, ContainmentMode::ContainsCentroid, 0)
+        .collect::<HashSet<_>>();
+    assert!(!expected.is_empty());
+
+    // A tile fully covered is empty in scratch mode.
+    let decoded =
+        decode_layer(tile_id, &Layer::default(), Resolution::Twelve, true)
+            .expect("decode");
+
+    assert_eq!(decoded, expected);
+}
+
+#[test]
+fn unsupported_geometry() {
+    let (cells, tile_id, _) = dataset();
+    let mut layer =
+        render(tile_id, cells, "h3".to_owned(), false).expect("render");
+    layer.features.push(Feature {
+        r#type: Some(GeomType::Point.into()),
+        geometry: vec![9, 2, 2],
+        ..Feature::default()
+    });
+
+    let result = decode_layer(tile_id, &layer, Resolution::Ten, false);
+
+    assert!(matches!(result, Err(DecodingError::UnsupportedGeometry(1))));
+}
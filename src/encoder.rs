@@ -0,0 +1,377 @@
+use crate::{TileID, mvt};
+use ahash::HashMap;
+use geo::{LineString, MultiPolygon};
+use geozero::mvt::tile::{Feature, Layer, Value};
+use std::fmt::Write as _;
+
+/// An encoder turning the rendered features of a tile into an output format.
+///
+/// Features are given in tile coordinates: the extent spans from 0 to
+/// [`TileID::extent`] (excluded), with Y pointing down, and the geometry may
+/// overflow into the tile buffer. Coordinates are not rounded.
+///
+/// # Example
+///
+/// ```
+/// use h3o::CellIndex;
+/// use h3o_mvt::{Renderer, TileEncoder, TileFeature, tiles_for_cell};
+///
+/// /// Counts the polygons of a tile.
+/// struct Counter;
+///
+/// impl TileEncoder for Counter {
+///     type Output = usize;
+///
+///     fn encode(&mut self, _name: &str, features: &[TileFeature<'_>]) -> usize {
+///         features.iter().map(|feature| feature.geometry().0.len()).sum()
+///     }
+/// }
+///
+/// let cell = CellIndex::try_from(0x891fb46622fffff)?;
+/// let tile_id = tiles_for_cell(cell, 14..=14).into_iter().next().expect("tile");
+/// let mut renderer = Renderer::new("h3".to_owned(), false);
+///
+/// let count = renderer.render_with(tile_id, [cell], &mut Counter)?;
+/// assert_eq!(count, 1);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub trait TileEncoder {
+    /// Encoded layer.
+    type Output;
+
+    /// Encodes the features of a layer.
+    fn encode(
+        &mut self,
+        name: &str,
+        features: &[TileFeature<'_>],
+    ) -> Self::Output;
+}
+
+// -----------------------------------------------------------------------------
+
+/// A feature to encode or export.
+#[derive(Debug, Clone, Copy)]
+pub struct TileFeature<'a> {
+    /// Feature identifier.
+    id: Option<u64>,
+    /// Feature geometry.
+    geometry: &'a MultiPolygon,
+    /// Feature properties.
+    properties: &'a [(&'a str, PropertyValue<'a>)],
+}
+
+impl<'a> TileFeature<'a> {
+    /// Initializes a feature, without identifier nor properties.
+    #[must_use]
+    pub const fn new(geometry: &'a MultiPolygon) -> Self {
+        Self {
+            id: None,
+            geometry,
+            properties: &[],
+        }
+    }
+
+    /// Sets the feature identifier.
+    #[must_use]
+    pub const fn with_id(mut self, id: u64) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Sets the feature properties.
+    #[must_use]
+    pub const fn with_properties(
+        mut self,
+        properties: &'a [(&'a str, PropertyValue<'a>)],
+    ) -> Self {
+        self.properties = properties;
+        self
+    }
+
+    /// Returns the feature identifier, if any.
+    #[must_use]
+    pub const fn id(&self) -> Option<u64> {
+        self.id
+    }
+
+    /// Returns the feature geometry.
+    #[must_use]
+    pub const fn geometry(&self) -> &'a MultiPolygon {
+        self.geometry
+    }
+
+    /// Returns the feature properties.
+    #[must_use]
+    pub const fn properties(&self) -> &'a [(&'a str, PropertyValue<'a>)] {
+        self.properties
+    }
+}
+
+/// Value of a feature property.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum PropertyValue<'a> {
+    /// A string.
+    String(&'a str),
+    /// A floating-point number.
+    Double(f64),
+    /// A signed integer.
+    Int(i64),
+    /// An unsigned integer.
+    UInt(u64),
+    /// A boolean.
+    Bool(bool),
+}
+
+// -----------------------------------------------------------------------------
+
+/// MVT encoder, the default encoder of the rendering pipeline.
+///
+/// Coordinates are rounded, and the rings repaired and oriented as required by
+/// the MVT specification. Features without geometry left are skipped.
+#[derive(Debug, Default)]
+pub struct MvtEncoder {
+    /// Geometry encoder.
+    encoder: mvt::Encoder,
+    /// Index of the keys in the layer.
+    keys: HashMap<String, u32>,
+    /// Index of the values in the layer.
+    values: HashMap<ValueKey, u32>,
+}
+
+impl MvtEncoder {
+    /// Initializes a new MVT encoder.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the index of the key, adding it to the layer if needed.
+    fn key(&mut self, layer: &mut Layer, key: &str) -> u32 {
+        if let Some(&index) = self.keys.get(key) {
+            return index;
+        }
+        let index = u32::try_from(layer.keys.len()).expect("too many keys");
+        layer.keys.push(key.to_owned());
+        self.keys.insert(key.to_owned(), index);
+        index
+    }
+
+    /// Returns the index of the value, adding it to the layer if needed.
+    fn value(&mut self, layer: &mut Layer, value: PropertyValue<'_>) -> u32 {
+        let key = ValueKey::from(value);
+        if let Some(&index) = self.values.get(&key) {
+            return index;
+        }
+        let index = u32::try_from(layer.values.len()).expect("too many values");
+        layer.values.push(match value {
+            PropertyValue::String(value) => Value {
+                string_value: Some(value.to_owned()),
+                ..Value::default()
+            },
+            PropertyValue::Double(value) => Value {
+                double_value: Some(value),
+                ..Value::default()
+            },
+            PropertyValue::Int(value) => Value {
+                int_value: Some(value),
+                ..Value::default()
+            },
+            PropertyValue::UInt(value) => Value {
+                uint_value: Some(value),
+                ..Value::default()
+            },
+            PropertyValue::Bool(value) => Value {
+                bool_value: Some(value),
+                ..Value::default()
+            },
+        });
+        self.values.insert(key, index);
+        index
+    }
+}
+
+impl TileEncoder for MvtEncoder {
+    type Output = Layer;
+
+    fn encode(&mut self, name: &str, features: &[TileFeature<'_>]) -> Layer {
+        self.keys.clear();
+        self.values.clear();
+        let mut layer = Layer {
+            version: 2,
+            name: name.to_owned(),
+            extent: Some(TileID::extent()),
+            ..Layer::default()
+        };
+
+        for feature in features {
+            let mut encoded = Feature {
+                id: feature.id,
+                ..Feature::default()
+            };
+            if !self.encoder.encode(feature.geometry, &mut encoded) {
+                continue;
+            }
+            for &(key, value) in feature.properties {
+                let key = self.key(&mut layer, key);
+                let value = self.value(&mut layer, value);
+                encoded.tags.extend([key, value]);
+            }
+            layer.features.push(encoded);
+        }
+
+        layer
+    }
+}
+
+/// Hashable representation of a property value.
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum ValueKey {
+    String(String),
+    Double(u64),
+    Int(i64),
+    UInt(u64),
+    Bool(bool),
+}
+
+impl From<PropertyValue<'_>> for ValueKey {
+    fn from(value: PropertyValue<'_>) -> Self {
+        match value {
+            PropertyValue::String(value) => Self::String(value.to_owned()),
+            PropertyValue::Double(value) => Self::Double(value.to_bits()),
+            PropertyValue::Int(value) => Self::Int(value),
+            PropertyValue::UInt(value) => Self::UInt(value),
+            PropertyValue::Bool(value) => Self::Bool(value),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+/// GeoJSON encoder, outputting a `FeatureCollection` in tile coordinates.
+///
+/// Meant for debugging: coordinates are neither rounded nor reprojected, and
+/// the layer name is stored as a foreign member of the collection.
+#[derive(Debug, Default)]
+pub struct GeoJsonEncoder;
+
+impl GeoJsonEncoder {
+    /// Initializes a new GeoJSON encoder.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self
+    }
+}
+
+impl TileEncoder for GeoJsonEncoder {
+    type Output = String;
+
+    fn encode(&mut self, name: &str, features: &[TileFeature<'_>]) -> String {
+        let mut json = String::from(r#"{"type":"FeatureCollection","name":"#);
+        write_string(&mut json, name);
+        json.push_str(r#","features":["#);
+        for (index, feature) in features.iter().enumerate() {
+            if index != 0 {
+                json.push(',');
+            }
+            write_feature(&mut json, feature);
+        }
+        json.push_str("]}");
+        json
+    }
+}
+
+/// Writes a GeoJSON feature.
+pub fn write_feature(json: &mut String, feature: &TileFeature<'_>) {
+    json.push_str(r#"{"type":"Feature","#);
+    if let Some(id) = feature.id {
+        write!(json, r#""id":{id},"#).expect("write to string");
+    }
+    json.push_str(r#""geometry":{"type":"MultiPolygon","coordinates":["#);
+    for (index, polygon) in feature.geometry.iter().enumerate() {
+        if index != 0 {
+            json.push(',');
+        }
+        json.push('[');
+        let rings =
+            std::iter::once(polygon.exterior()).chain(polygon.interiors());
+        for (index, ring) in rings.enumerate() {
+            if index != 0 {
+                json.push(',');
+            }
+            write_ring(json, ring);
+        }
+        json.push(']');
+    }
+    json.push_str(r#"]},"properties":"#);
+    write_properties(json, feature.properties);
+    json.push('}');
+}
+
+/// Writes properties as a JSON object.
+pub fn write_properties(
+    json: &mut String,
+    properties: &[(&str, PropertyValue<'_>)],
+) {
+    json.push('{');
+    for (index, &(key, value)) in properties.iter().enumerate() {
+        if index != 0 {
+            json.push(',');
+        }
+        write_string(json, key);
+        json.push(':');
+        match value {
+            PropertyValue::String(value) => write_string(json, value),
+            PropertyValue::Double(value) if value.is_finite() => {
+                write!(json, "{value}").expect("write to string");
+            }
+            PropertyValue::Double(_) => json.push_str("null"),
+            PropertyValue::Int(value) => {
+                write!(json, "{value}").expect("write to string");
+            }
+            PropertyValue::UInt(value) => {
+                write!(json, "{value}").expect("write to string");
+            }
+            PropertyValue::Bool(value) => {
+                write!(json, "{value}").expect("write to string");
+            }
+        }
+    }
+    json.push('}');
+}
+
+/// Writes the coordinates of a ring.
+fn write_ring(json: &mut String, ring: &LineString) {
+    json.push('[');
+    for (index, coord) in ring.coords().enumerate() {
+        if index != 0 {
+            json.push(',');
+        }
+        write!(json, "[{},{}]", coord.x, coord.y).expect("write to string");
+    }
+    json.push(']');
+}
+
+/// Writes an escaped JSON string.
+pub fn write_string(json: &mut String, value: &str) {
+    json.push('"');
+    for char in value.chars() {
+        match char {
+            '"' => json.push_str(r#"\""#),
+            '\\' => json.push_str(r"\\"),
+            '\n' => json.push_str(r"\n"),
+            '\r' => json.push_str(r"\r"),
+            '\t' => json.push_str(r"\t"),
+            char if char.is_control() => {
+                write!(json, r"\u{:04x}", u32::from(char))
+                    .expect("write to string");
+            }
+            char => json.push(char),
+        }
+    }
+    json.push('"');
+}
+
+#[cfg(test)]
+#[path = "./encoder_tests.rs"]
+mod tests;
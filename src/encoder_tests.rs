@@ -0,0 +1,140 @@
+use super::*;
+use crate::{render, render_with, tiles_for_cell, validate_layer};
+use geo::polygon;
+use h3o::CellIndex;
+
+fn square(x: f64, y: f64) -> MultiPolygon {
+    MultiPolygon::new(vec![polygon![
+        (x: x, y: y),
+        (x: x + 10., y: y),
+        (x: x + 10., y: y + 10.),
+        (x: x, y: y + 10.),
+    ]])
+}
+
+#[test]
+fn mvt_is_default() {
+    let center = CellIndex::try_from(0x891fb46622fffff).expect("cell");
+    let cells = center.grid_disk::<Vec<_>>(4);
+
+    for scratch in [false, true] {
+        for tile_id in tiles_for_cell(center, 12..=15)
+            .into_iter()
+            .chain([TileID::new(0, 0, 15).expect("tile")])
+        {
+            let expected = render(
+                tile_id,
+                cells.iter().copied(),
+                "test".to_owned(),
+                scratch,
+            )
+            .expect("render");
+
+            let result = render_with(
+                tile_id,
+                cells.iter().copied(),
+                "test".to_owned(),
+                scratch,
+                &mut MvtEncoder::new(),
+            )
+            .expect("render with");
+
+            assert_eq!(result, expected, "{tile_id:?}/{scratch}");
+        }
+    }
+}
+
+#[test]
+fn mvt_properties() {
+    let (first, second, empty) =
+        (square(0., 0.), square(20., 20.), MultiPolygon::new(vec![]));
+    let properties = [
+        ("name", PropertyValue::String("first")),
+        ("count", PropertyValue::UInt(3)),
+    ];
+    let others = [
+        ("count", PropertyValue::UInt(3)),
+        ("ratio", PropertyValue::Double(0.5)),
+    ];
+    let features = [
+        TileFeature::new(&first)
+            .with_id(1)
+            .with_properties(&properties),
+        TileFeature::new(&empty).with_id(2),
+        TileFeature::new(&second)
+            .with_id(3)
+            .with_properties(&others),
+    ];
+    let mut encoder = MvtEncoder::new();
+
+    let layer = encoder.encode("test", &features);
+
+    assert_eq!(validate_layer(&layer, 80), []);
+    assert_eq!(layer.name, "test");
+    assert_eq!(layer.keys, ["name", "count", "ratio"]);
+    assert_eq!(layer.values.len(), 3);
+    // The empty feature is skipped.
+    let ids = layer.features.iter().map(|feature| feature.id);
+    assert_eq!(ids.collect::<Vec<_>>(), [Some(1), Some(3)]);
+    assert_eq!(layer.features[0].tags, [0, 0, 1, 1]);
+    assert_eq!(layer.features[1].tags, [1, 1, 2, 2]);
+    // The encoder state doesn't leak between layers.
+    assert_eq!(encoder.encode("test", &features), layer);
+}
+
+#[test]
+fn geojson() {
+    let geometry = square(0.5, 1.);
+    let properties = [
+        ("name", PropertyValue::String("a \"quoted\"\nname")),
+        ("count", PropertyValue::Int(-3)),
+        ("valid", PropertyValue::Bool(true)),
+        ("nan", PropertyValue::Double(f64::NAN)),
+    ];
+    let features = [TileFeature::new(&geometry)
+        .with_id(7)
+        .with_properties(&properties)];
+
+    let json = GeoJsonEncoder::new().encode("test", &features);
+
+    let collection =
+        json.parse::<geojson::FeatureCollection>().expect("GeoJSON");
+    let feature = &collection.features[0];
+    assert_eq!(feature.id, Some(geojson::feature::Id::Number(7.into())));
+    assert_eq!(
+        feature.property("name").and_then(|value| value.as_str()),
+        Some("a \"quoted\"\nname")
+    );
+    assert_eq!(
+        feature
+            .property("count")
+            .and_then(geojson::JsonValue::as_i64),
+        Some(-3)
+    );
+    assert_eq!(
+        feature
+            .property("valid")
+            .and_then(geojson::JsonValue::as_bool),
+        Some(true)
+    );
+    assert!(
+        feature
+            .property("nan")
+            .is_some_and(geojson::JsonValue::is_null)
+    );
+    let result = MultiPolygon::<f64>::try_from(
+        feature.geometry.clone().expect("geometry").value,
+    )
+    .expect("multipolygon");
+    assert_eq!(result, geometry);
+}
+
+#[test]
+fn geojson_empty() {
+    let json = GeoJsonEncoder::new().encode("test", &[]);
+
+    assert_eq!(
+        json,
+        r#"{"type":"FeatureCollection","name":"test","features":[]}"#
+    );
+}
@@ -1,6 +1,6 @@
 use geozero::error::GeozeroError;
-use h3o::error::DissolutionError;
-use std::{error::Error, fmt};
+use h3o::{CellIndex, error::DissolutionError};
+use std::{error::Error, fmt, io};
 
 /// Error occurring while rendering a set of cell indices to MVT.
 #[derive(Debug)]
@@ -12,6 +12,8 @@ pub enum InvalidTileID {
     InvalidY(u32),
     /// Invalid Z coordinate.
     InvalidZ(u32),
+    /// Malformed tile identifier (`z/x/y` string or quadkey).
+    InvalidFormat(String),
 }
 
 impl fmt::Display for InvalidTileID {
@@ -26,6 +28,9 @@ impl fmt::Display for InvalidTileID {
             Self::InvalidZ(value) => {
                 write!(f, "invalid z coordinate: {value}")
             }
+            Self::InvalidFormat(ref value) => {
+                write!(f, "invalid tile identifier: {value}")
+            }
         }
     }
 }
@@ -33,7 +38,10 @@ impl fmt::Display for InvalidTileID {
 impl Error for InvalidTileID {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match *self {
-            Self::InvalidX(_) | Self::InvalidY(_) | Self::InvalidZ(_) => None,
+            Self::InvalidX(_)
+            | Self::InvalidY(_)
+            | Self::InvalidZ(_)
+            | Self::InvalidFormat(_) => None,
         }
     }
 }
@@ -45,7 +53,10 @@ pub enum RenderingError {
     /// Invalid input.
     InvalidInput(DissolutionError),
     /// MVT encoding failed.
+    #[deprecated(note = "MVT encoding cannot fail anymore")]
     Encoding(GeozeroError),
+    /// Rendering aborted before completion.
+    Aborted(AbortReason),
 }
 
 impl fmt::Display for RenderingError {
@@ -54,9 +65,13 @@ impl fmt::Display for RenderingError {
             Self::InvalidInput(ref source) => {
                 write!(f, "invalid input: {source}")
             }
+            #[expect(deprecated, reason = "still part of the API")]
             Self::Encoding(ref source) => {
                 write!(f, "MVT encoding failed: {source}")
             }
+            Self::Aborted(ref source) => {
+                write!(f, "rendering aborted: {source}")
+            }
         }
     }
 }
@@ -65,7 +80,228 @@ impl Error for RenderingError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         match *self {
             Self::InvalidInput(ref source) => Some(source),
+            #[expect(deprecated, reason = "still part of the API")]
             Self::Encoding(ref source) => Some(source),
+            Self::Aborted(ref source) => Some(source),
+        }
+    }
+}
+
+/// Reason why a rendering was aborted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AbortReason {
+    /// The input has more cells than allowed.
+    TooManyCells,
+    /// The geometry has more vertices than allowed.
+    TooManyVertices,
+    /// The deadline has passed.
+    DeadlineExceeded,
+    /// The rendering has been cancelled.
+    Cancelled,
+}
+
+impl fmt::Display for AbortReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::TooManyCells => write!(f, "too many cells"),
+            Self::TooManyVertices => write!(f, "too many vertices"),
+            Self::DeadlineExceeded => write!(f, "deadline exceeded"),
+            Self::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+impl Error for AbortReason {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            Self::TooManyCells
+            | Self::TooManyVertices
+            | Self::DeadlineExceeded
+            | Self::Cancelled => None,
+        }
+    }
+}
+
+/// Errors occurring while exporting the geometry of a dataset.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ExportError {
+    /// Invalid input.
+    InvalidInput(DissolutionError),
+    /// Property missing from the schema, or of the wrong type (carries the
+    /// property name).
+    InvalidProperty(String),
+    /// I/O error.
+    Io(io::Error),
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::InvalidInput(ref source) => {
+                write!(f, "invalid input: {source}")
+            }
+            Self::InvalidProperty(ref name) => {
+                write!(f, "invalid property: {name}")
+            }
+            Self::Io(ref source) => write!(f, "I/O error: {source}"),
+        }
+    }
+}
+
+impl Error for ExportError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            Self::InvalidInput(ref source) => Some(source),
+            Self::InvalidProperty(_) => None,
+            Self::Io(ref source) => Some(source),
+        }
+    }
+}
+
+/// Errors occurring while decoding a rendered tile back into cells.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DecodingError {
+    /// Invalid MVT geometry.
+    InvalidGeometry(GeozeroError),
+    /// Feature that isn't a polygon (carries the feature index).
+    UnsupportedGeometry(usize),
+}
+
+impl fmt::Display for DecodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::InvalidGeometry(ref source) => {
+                write!(f, "invalid MVT geometry: {source}")
+            }
+            Self::UnsupportedGeometry(index) => {
+                write!(f, "unsupported geometry for feature {index}")
+            }
+        }
+    }
+}
+
+impl Error for DecodingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            Self::InvalidGeometry(ref source) => Some(source),
+            Self::UnsupportedGeometry(_) => None,
+        }
+    }
+}
+
+/// Errors occurring while merging layers into an encoded tile.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum MergeError {
+    /// Compressed tile that cannot be decompressed.
+    InvalidCompression,
+    /// Compressed tile exceeding the decompressed size limit.
+    TooLarge,
+    /// Tile that cannot be decoded.
+    InvalidTile(Box<dyn Error + Send + Sync>),
+    /// Invalid MVT geometry.
+    InvalidGeometry(GeozeroError),
+    /// Feature that isn't a polygon (carries the feature index).
+    UnsupportedGeometry(usize),
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::InvalidCompression => write!(f, "invalid tile compression"),
+            Self::TooLarge => write!(f, "decompressed tile too large"),
+            Self::InvalidTile(ref source) => {
+                write!(f, "invalid MVT tile: {source}")
+            }
+            Self::InvalidGeometry(ref source) => {
+                write!(f, "invalid MVT geometry: {source}")
+            }
+            Self::UnsupportedGeometry(index) => {
+                write!(f, "unsupported geometry for feature {index}")
+            }
+        }
+    }
+}
+
+impl Error for MergeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            Self::InvalidTile(ref source) => Some(&**source),
+            Self::InvalidGeometry(ref source) => Some(source),
+            Self::InvalidCompression
+            | Self::TooLarge
+            | Self::UnsupportedGeometry(_) => None,
+        }
+    }
+}
+
+/// Errors occurring while building a dataset pyramid.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub enum PyramidError {
+    /// Cells have different resolutions (carries the first mismatching cell).
+    HeterogeneousResolution(CellIndex),
+    /// Cells are coarser than the coarsest resolution of the pyramid (carries
+    /// the first offending cell).
+    UnsupportedResolution(CellIndex),
+}
+
+impl fmt::Display for PyramidError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::HeterogeneousResolution(cell) => {
+                write!(f, "heterogeneous resolution: {cell}")
+            }
+            Self::UnsupportedResolution(cell) => {
+                write!(f, "unsupported resolution: {cell}")
+            }
+        }
+    }
+}
+
+impl Error for PyramidError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            Self::HeterogeneousResolution(_)
+            | Self::UnsupportedResolution(_) => None,
+        }
+    }
+}
+
+/// Errors occurring while building or loading a cell store.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum StoreError {
+    /// Cells have different resolutions (carries the first mismatching cell).
+    HeterogeneousResolution(CellIndex),
+    /// Invalid data (unsorted or invalid cell) at the given byte offset.
+    InvalidData(usize),
+    /// I/O error.
+    Io(io::Error),
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::HeterogeneousResolution(cell) => {
+                write!(f, "heterogeneous resolution: {cell}")
+            }
+            Self::InvalidData(offset) => {
+                write!(f, "invalid data at offset {offset}")
+            }
+            Self::Io(ref source) => write!(f, "I/O error: {source}"),
+        }
+    }
+}
+
+impl Error for StoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            Self::HeterogeneousResolution(_) | Self::InvalidData(_) => None,
+            Self::Io(ref source) => Some(source),
         }
     }
 }
@@ -73,26 +309,112 @@ impl Error for RenderingError {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use geozero::mvt::{Message, Tile};
+
+    fn decode_error() -> Box<dyn Error + Send + Sync> {
+        Box::new(Tile::decode(&b"\xff"[..]).expect_err("invalid tile"))
+    }
 
     // All error must have a non-empty display.
     #[test]
+    #[expect(
+        clippy::cognitive_complexity,
+        reason = "one assertion per variant"
+    )]
     fn display() {
         assert!(
             !RenderingError::InvalidInput(DissolutionError::DuplicateInput)
                 .to_string()
                 .is_empty()
         );
+        #[expect(deprecated, reason = "still part of the API")]
+        let encoding = RenderingError::Encoding(GeozeroError::GeometryFormat);
+        assert!(!encoding.to_string().is_empty());
+        assert!(
+            !RenderingError::Aborted(AbortReason::Cancelled)
+                .to_string()
+                .is_empty()
+        );
+        assert!(
+            !ExportError::InvalidInput(DissolutionError::DuplicateInput)
+                .to_string()
+                .is_empty()
+        );
         assert!(
-            !RenderingError::Encoding(GeozeroError::GeometryFormat)
+            !ExportError::InvalidProperty("name".to_owned())
                 .to_string()
                 .is_empty()
         );
+        assert!(
+            !ExportError::Io(io::Error::other("oops"))
+                .to_string()
+                .is_empty()
+        );
+        assert!(
+            !DecodingError::InvalidGeometry(GeozeroError::GeometryFormat)
+                .to_string()
+                .is_empty()
+        );
+        assert!(!DecodingError::UnsupportedGeometry(3).to_string().is_empty());
+        assert!(!MergeError::InvalidCompression.to_string().is_empty());
+        assert!(!MergeError::TooLarge.to_string().is_empty());
+        assert!(
+            !MergeError::InvalidTile(decode_error())
+                .to_string()
+                .is_empty()
+        );
+        assert!(
+            !MergeError::InvalidGeometry(GeozeroError::GeometryFormat)
+                .to_string()
+                .is_empty()
+        );
+        assert!(!MergeError::UnsupportedGeometry(3).to_string().is_empty());
+        assert!(!AbortReason::TooManyCells.to_string().is_empty());
+        assert!(!AbortReason::TooManyVertices.to_string().is_empty());
+        assert!(!AbortReason::DeadlineExceeded.to_string().is_empty());
+        assert!(!AbortReason::Cancelled.to_string().is_empty());
         assert!(!InvalidTileID::InvalidX(42).to_string().is_empty());
         assert!(!InvalidTileID::InvalidY(42).to_string().is_empty());
         assert!(!InvalidTileID::InvalidZ(42).to_string().is_empty());
+        assert!(
+            !InvalidTileID::InvalidFormat("1/2".to_owned())
+                .to_string()
+                .is_empty()
+        );
+        assert!(
+            !StoreError::HeterogeneousResolution(
+                CellIndex::try_from(0x8a1fb46622dffff).expect("valid cell")
+            )
+            .to_string()
+            .is_empty()
+        );
+        assert!(!StoreError::InvalidData(42).to_string().is_empty());
+        assert!(
+            !PyramidError::HeterogeneousResolution(
+                CellIndex::try_from(0x8a1fb46622dffff).expect("valid cell")
+            )
+            .to_string()
+            .is_empty()
+        );
+        assert!(
+            !PyramidError::UnsupportedResolution(
+                CellIndex::try_from(0x8a1fb46622dffff).expect("valid cell")
+            )
+            .to_string()
+            .is_empty()
+        );
+        assert!(
+            !StoreError::Io(io::Error::other("oops"))
+                .to_string()
+                .is_empty()
+        );
     }
 
     #[test]
+    #[expect(
+        clippy::cognitive_complexity,
+        reason = "one assertion per variant"
+    )]
     fn source() {
         assert!(
             RenderingError::InvalidInput(
@@ -101,13 +423,74 @@ mod tests {
             .source()
             .is_some()
         );
+        #[expect(deprecated, reason = "still part of the API")]
+        let encoding = RenderingError::Encoding(GeozeroError::GeometryFormat);
+        assert!(encoding.source().is_some());
+        assert!(
+            RenderingError::Aborted(AbortReason::TooManyCells)
+                .source()
+                .is_some()
+        );
+        assert!(
+            ExportError::InvalidInput(DissolutionError::DuplicateInput)
+                .source()
+                .is_some()
+        );
+        assert!(
+            ExportError::InvalidProperty("name".to_owned())
+                .source()
+                .is_none()
+        );
+        assert!(ExportError::Io(io::Error::other("oops")).source().is_some());
+        assert!(
+            DecodingError::InvalidGeometry(GeozeroError::GeometryFormat)
+                .source()
+                .is_some()
+        );
+        assert!(DecodingError::UnsupportedGeometry(3).source().is_none());
+        assert!(MergeError::InvalidCompression.source().is_none());
+        assert!(MergeError::TooLarge.source().is_none());
+        assert!(MergeError::InvalidTile(decode_error()).source().is_some());
         assert!(
-            RenderingError::Encoding(GeozeroError::GeometryFormat)
+            MergeError::InvalidGeometry(GeozeroError::GeometryFormat)
                 .source()
                 .is_some()
         );
+        assert!(MergeError::UnsupportedGeometry(3).source().is_none());
+        assert!(AbortReason::TooManyCells.source().is_none());
+        assert!(AbortReason::TooManyVertices.source().is_none());
+        assert!(AbortReason::DeadlineExceeded.source().is_none());
+        assert!(AbortReason::Cancelled.source().is_none());
         assert!(InvalidTileID::InvalidX(42).source().is_none());
         assert!(InvalidTileID::InvalidY(42).source().is_none());
         assert!(InvalidTileID::InvalidZ(42).source().is_none());
+        assert!(
+            InvalidTileID::InvalidFormat("1/2".to_owned())
+                .source()
+                .is_none()
+        );
+        assert!(
+            StoreError::HeterogeneousResolution(
+                CellIndex::try_from(0x8a1fb46622dffff).expect("valid cell")
+            )
+            .source()
+            .is_none()
+        );
+        assert!(StoreError::InvalidData(42).source().is_none());
+        assert!(
+            PyramidError::HeterogeneousResolution(
+                CellIndex::try_from(0x8a1fb46622dffff).expect("valid cell")
+            )
+            .source()
+            .is_none()
+        );
+        assert!(
+            PyramidError::UnsupportedResolution(
+                CellIndex::try_from(0x8a1fb46622dffff).expect("valid cell")
+            )
+            .source()
+            .is_none()
+        );
+        assert!(StoreError::Io(io::Error::other("oops")).source().is_some());
     }
 }
@@ -0,0 +1,307 @@
+use crate::{
+    ExportError, PropertyValue, RingHierarchy, TileFeature, clip,
+    encoder::write_feature,
+    render::{canonicalize, is_transmeridian},
+};
+use geo::{
+    BoundingRect, Coord, LineString, MapCoordsInPlace, MultiPolygon, Orient,
+    Rect, orient::Direction,
+};
+use h3o::{CellIndex, geom::SolventBuilder};
+use std::io;
+
+/// A reusable exporter, writing the dissolved geometry of datasets into GIS
+/// files.
+///
+/// The cells are dissolved like when rendering tiles, but the resulting shape
+/// stays in EPSG:4326 coordinates: polygons crossing the antimeridian are
+/// split in two (as required by RFC 7946), with counter-clockwise exteriors
+/// and clockwise holes. Shapes going around the world (e.g. enclosing a pole)
+/// are not supported.
+///
+/// Each feature is dissolved in memory, then written right away to the given
+/// [`FeatureWriter`]: memory use is bounded by the largest feature, not by the
+/// whole dataset.
+///
+/// # Example
+///
+/// ```
+/// use h3o::{CellIndex, LatLng, Resolution};
+/// use h3o_mvt::{Exporter, GeoJsonWriter};
+///
+/// let cell = LatLng::new(48.85, 2.35)?.to_cell(Resolution::Seven);
+/// let mut writer = GeoJsonWriter::new(Vec::new())?;
+///
+/// Exporter::new().export(cell.grid_disk::<Vec<_>>(2), &mut writer)?;
+///
+/// let json = String::from_utf8(writer.finish()?)?;
+/// assert!(json.starts_with(r#"{"type":"FeatureCollection""#));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Default)]
+pub struct Exporter {
+    /// Clipping buffers, used to split the shape at the antimeridian.
+    clip: clip::Buffers,
+}
+
+impl Exporter {
+    /// Initializes a new exporter.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Dissolves the given cells into polygons, in EPSG:4326 coordinates,
+    /// split at the antimeridian.
+    ///
+    /// The shape is in canonical form, regardless of the input order.
+    ///
+    /// # Errors
+    ///
+    /// All cell indexes must be unique and have the same resolution, otherwise
+    /// an `ExportError::InvalidInput` is returned.
+    pub fn dissolve(
+        &mut self,
+        cells: impl IntoIterator<Item = CellIndex>,
+    ) -> Result<MultiPolygon, ExportError> {
+        let shape = SolventBuilder::new()
+            .build()
+            .dissolve(cells)
+            .map_err(ExportError::InvalidInput)?;
+
+        if !shape
+            .iter()
+            .flat_map(|polygon| {
+                std::iter::once(polygon.exterior()).chain(polygon.interiors())
+            })
+            .any(is_transmeridian)
+        {
+            let mut shape = shape.orient(Direction::Default);
+            canonicalize(&mut shape);
+            return Ok(shape);
+        }
+
+        // The nesting computed in EPSG:4326 is wrong for rings crossing the
+        // antimeridian, so every ring is unwrapped around a meridian crossed
+        // by none of them before being nested again.
+        let mut rings = shape
+            .into_iter()
+            .flat_map(|polygon| {
+                let (exterior, interiors) = polygon.into_inner();
+                std::iter::once(exterior).chain(interiors)
+            })
+            .collect::<Vec<_>>();
+        let mut bounds = Vec::with_capacity(rings.len());
+        for ring in &mut rings {
+            if is_transmeridian(ring) {
+                shift_eastward(ring);
+            }
+            let bbox = ring.bounding_rect().expect("non-empty ring");
+            bounds.push((bbox.min().x, bbox.max().x));
+        }
+        let cut = cut_meridian(bounds.clone()).unwrap_or(-180.);
+        for (ring, (min, _)) in rings.iter_mut().zip(bounds) {
+            let offset = ((cut - min) / 360.).ceil() * 360.;
+            ring.map_coords_in_place(|coord| Coord {
+                x: coord.x + offset,
+                y: coord.y,
+            });
+        }
+        let shape = MultiPolygon::from(RingHierarchy::new(rings));
+
+        // Then the shape is cut back into the [-180, 180] range.
+        let mut polygons = Vec::new();
+        let first = ((cut + 180.) / 360.).floor() * 360.;
+        for offset in [first, first + 360.] {
+            let mut part = clip::clip_with(
+                &shape,
+                Rect::new((offset - 180., -90.), (offset + 180., 90.)),
+                &mut self.clip,
+            );
+            part.map_coords_in_place(|coord| Coord {
+                x: coord.x - offset,
+                y: coord.y,
+            });
+            polygons.extend(part);
+        }
+
+        let mut shape = MultiPolygon::new(polygons).orient(Direction::Default);
+        canonicalize(&mut shape);
+        Ok(shape)
+    }
+
+    /// Dissolves the given cells, and writes the resulting shape as a single
+    /// feature, without properties.
+    ///
+    /// The whole dataset is dissolved in memory: use [`Self::export_classes`]
+    /// to split very large datasets into several features.
+    ///
+    /// Nothing is written if there are no cells.
+    ///
+    /// # Errors
+    ///
+    /// All cell indexes must be unique and have the same resolution, otherwise
+    /// an `ExportError::InvalidInput` is returned.
+    ///
+    /// Errors from the writer are forwarded.
+    pub fn export(
+        &mut self,
+        cells: impl IntoIterator<Item = CellIndex>,
+        writer: &mut impl FeatureWriter,
+    ) -> Result<(), ExportError> {
+        let shape = self.dissolve(cells)?;
+        if shape.0.is_empty() {
+            return Ok(());
+        }
+
+        writer.write_feature(&TileFeature::new(&shape))
+    }
+
+    /// Dissolves the given cells per class, and writes one feature per run of
+    /// consecutive cells of the same class, as soon as the run ends.
+    ///
+    /// The input is expected to be grouped by class (e.g. sorted by class):
+    /// only the cells of the current run are held in memory, so memory use is
+    /// bounded by the largest class rather than by the whole dataset. A class
+    /// spread over several runs is written as several features.
+    ///
+    /// Each feature has a single property, named `property`, holding the value
+    /// of its class.
+    ///
+    /// # Errors
+    ///
+    /// Cell indexes must be unique and have the same resolution within a
+    /// class, otherwise an `ExportError::InvalidInput` is returned.
+    ///
+    /// Errors from the writer are forwarded.
+    pub fn export_classes<C: PartialEq>(
+        &mut self,
+        cells: impl IntoIterator<Item = (CellIndex, C)>,
+        property: &str,
+        value: impl Fn(&C) -> PropertyValue<'_>,
+        writer: &mut impl FeatureWriter,
+    ) -> Result<(), ExportError> {
+        let mut cells = cells.into_iter().peekable();
+        let mut run = Vec::new();
+        while let Some((cell, class)) = cells.next() {
+            run.push(cell);
+            while let Some((cell, _)) =
+                cells.next_if(|(_, next)| *next == class)
+            {
+                run.push(cell);
+            }
+
+            let shape = self.dissolve(run.iter().copied())?;
+            run.clear();
+            let properties = [(property, value(&class))];
+            writer.write_feature(
+                &TileFeature::new(&shape).with_properties(&properties),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Moves the western part of a ring crossing the antimeridian to the east.
+fn shift_eastward(ring: &mut LineString) {
+    ring.map_coords_in_place(|coord| Coord {
+        x: coord.x + f64::from(u8::from(coord.x < 0.)) * 360.,
+        y: coord.y,
+    });
+}
+
+/// Finds a meridian crossed by none of the given longitude ranges, if any.
+fn cut_meridian(mut ranges: Vec<(f64, f64)>) -> Option<f64> {
+    ranges.sort_unstable_by(|lhs, rhs| lhs.0.total_cmp(&rhs.0));
+    let (first, mut reach) = *ranges.first()?;
+    for &(min, max) in &ranges[1..] {
+        if min > reach {
+            return Some(f64::midpoint(reach, min));
+        }
+        reach = reach.max(max);
+    }
+
+    // Otherwise, look for a gap across the wrapping point.
+    (reach < first + 360.).then(|| f64::midpoint(reach, first + 360.))
+}
+
+// -----------------------------------------------------------------------------
+
+/// A writer of features, in EPSG:4326 coordinates, into a GIS file.
+pub trait FeatureWriter {
+    /// Writes a feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error that occurred while writing, if any, or an
+    /// `ExportError::InvalidProperty` if a property cannot be stored.
+    fn write_feature(
+        &mut self,
+        feature: &TileFeature<'_>,
+    ) -> Result<(), ExportError>;
+}
+
+/// A streaming writer of RFC 7946 GeoJSON `FeatureCollection`.
+#[derive(Debug)]
+pub struct GeoJsonWriter<W> {
+    /// Output.
+    writer: W,
+    /// Scratch buffer for the current feature.
+    buffer: String,
+    /// Number of features written so far.
+    count: usize,
+}
+
+impl<W: io::Write> GeoJsonWriter<W> {
+    /// Initializes a new writer, and starts the collection.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error that occurred while writing, if any.
+    pub fn new(mut writer: W) -> Result<Self, ExportError> {
+        writer
+            .write_all(br#"{"type":"FeatureCollection","features":["#)
+            .map_err(ExportError::Io)?;
+
+        Ok(Self {
+            writer,
+            buffer: String::new(),
+            count: 0,
+        })
+    }
+
+    /// Ends the collection, and returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error that occurred while writing, if any.
+    pub fn finish(mut self) -> Result<W, ExportError> {
+        self.writer.write_all(b"]}").map_err(ExportError::Io)?;
+        self.writer.flush().map_err(ExportError::Io)?;
+
+        Ok(self.writer)
+    }
+}
+
+impl<W: io::Write> FeatureWriter for GeoJsonWriter<W> {
+    fn write_feature(
+        &mut self,
+        feature: &TileFeature<'_>,
+    ) -> Result<(), ExportError> {
+        self.buffer.clear();
+        if self.count != 0 {
+            self.buffer.push(',');
+        }
+        write_feature(&mut self.buffer, feature);
+        self.count += 1;
+
+        self.writer
+            .write_all(self.buffer.as_bytes())
+            .map_err(ExportError::Io)
+    }
+}
+
+#[cfg(test)]
+#[path = "./export_tests.rs"]
+mod tests;
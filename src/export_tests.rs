@@ -0,0 +1,200 @@
+use super::*;
+use geo::{Contains, CoordsIter, Polygon, Validation, Winding};
+use h3o::{LatLng, Resolution};
+use std::{cell::RefCell, rc::Rc};
+
+fn export_geojson(cells: Vec<CellIndex>) -> geojson::FeatureCollection {
+    let mut writer = GeoJsonWriter::new(Vec::new()).expect("writer");
+    Exporter::new().export(cells, &mut writer).expect("export");
+    let json =
+        String::from_utf8(writer.finish().expect("finish")).expect("UTF-8");
+
+    json.parse().expect("GeoJSON")
+}
+
+fn disk(lat: f64, lng: f64, k: u32) -> Vec<CellIndex> {
+    LatLng::new(lat, lng)
+        .expect("coordinate")
+        .to_cell(Resolution::Five)
+        .grid_disk(k)
+}
+
+#[test]
+fn geojson() {
+    let cells = disk(48.85, 2.35, 3);
+
+    let collection = export_geojson(cells.clone());
+
+    assert_eq!(collection.features.len(), 1);
+    let feature = &collection.features[0];
+    assert!(feature.id.is_none());
+    let shape = MultiPolygon::<f64>::try_from(
+        feature.geometry.clone().expect("geometry").value,
+    )
+    .expect("multipolygon");
+    let expected = Exporter::new().dissolve(cells).expect("dissolve");
+    assert_eq!(shape.0.len(), expected.0.len());
+    for (coord, expected) in shape.coords_iter().zip(expected.coords_iter()) {
+        // Parsing is not exact to the last bit.
+        assert!((coord.x - expected.x).abs() < 1e-12, "{coord:?}");
+        assert!((coord.y - expected.y).abs() < 1e-12, "{coord:?}");
+    }
+    assert_eq!(shape.0.len(), 1);
+    assert!(shape.0[0].exterior().is_ccw());
+}
+
+#[test]
+fn antimeridian() {
+    let cells = disk(10., 179.9, 4);
+
+    let shape = Exporter::new().dissolve(cells.clone()).expect("dissolve");
+
+    assert!(shape.is_valid());
+    // Split in two, on each side of the antimeridian.
+    assert_eq!(shape.0.len(), 2);
+    for polygon in &shape {
+        assert!(polygon.exterior().is_ccw());
+        assert!(
+            polygon
+                .exterior()
+                .coords()
+                .all(|coord| (-180. ..=180.).contains(&coord.x))
+        );
+        assert!(!is_transmeridian(polygon.exterior()));
+    }
+    for cell in cells {
+        let center = geo::Point::from(Coord::from(LatLng::from(cell)));
+        assert!(shape.contains(&center), "{cell}");
+    }
+}
+
+#[test]
+fn holes() {
+    let mut cells = disk(10., 179.9, 4);
+    let center = cells.remove(0);
+
+    let shape = Exporter::new().dissolve(cells).expect("dissolve");
+
+    assert!(shape.is_valid(), "{:?}", shape.validation_errors());
+    let holes = shape
+        .iter()
+        .flat_map(Polygon::interiors)
+        .collect::<Vec<_>>();
+    assert!(!holes.is_empty());
+    assert!(holes.iter().all(|ring| ring.is_cw()));
+    let center = geo::Point::from(Coord::from(LatLng::from(center)));
+    assert!(!shape.contains(&center));
+}
+
+#[test]
+fn classes() {
+    let cells = disk(48.85, 2.35, 2);
+    let classes = cells
+        .into_iter()
+        .enumerate()
+        .map(|(index, cell)| (cell, if index < 7 { "center" } else { "ring" }));
+    let mut writer = GeoJsonWriter::new(Vec::new()).expect("writer");
+
+    Exporter::new()
+        .export_classes(
+            classes,
+            "class",
+            |class| PropertyValue::String(class),
+            &mut writer,
+        )
+        .expect("export");
+
+    let json =
+        String::from_utf8(writer.finish().expect("finish")).expect("UTF-8");
+    let collection =
+        json.parse::<geojson::FeatureCollection>().expect("GeoJSON");
+    let classes = collection
+        .features
+        .iter()
+        .map(|feature| {
+            feature
+                .property("class")
+                .and_then(geojson::JsonValue::as_str)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(classes, [Some("center"), Some("ring")]);
+}
+
+/// Records the class of the written features.
+struct Recorder(Rc<RefCell<Vec<String>>>);
+
+impl FeatureWriter for Recorder {
+    fn write_feature(
+        &mut self,
+        feature: &TileFeature<'_>,
+    ) -> Result<(), ExportError> {
+        let [("class", PropertyValue::String(class))] = feature.properties()
+        else {
+            return Err(ExportError::InvalidProperty("class".to_owned()));
+        };
+        self.0.borrow_mut().push((*class).to_owned());
+        Ok(())
+    }
+}
+
+#[test]
+fn classes_streamed() {
+    let written = Rc::new(RefCell::new(Vec::new()));
+    let mut writer = Recorder(Rc::clone(&written));
+    let runs = [
+        ("a", disk(48.85, 2.35, 1)),
+        ("b", disk(40.71, -74.01, 1)),
+        ("a", disk(35.68, 139.69, 1)),
+    ];
+    let cells = runs.iter().enumerate().flat_map(|(run, (class, cells))| {
+        let written = Rc::clone(&written);
+        cells.iter().enumerate().map(move |(index, &cell)| {
+            // Previous runs are written by the time the second cell of a run
+            // is read (the first one ends the previous run).
+            if index > 0 {
+                assert_eq!(written.borrow().len(), run, "run {run}");
+            }
+            (cell, *class)
+        })
+    });
+
+    Exporter::new()
+        .export_classes(
+            cells,
+            "class",
+            |class| PropertyValue::String(class),
+            &mut writer,
+        )
+        .expect("export");
+
+    assert_eq!(*written.borrow(), ["a", "b", "a"]);
+}
+
+#[test]
+fn empty() {
+    let collection = export_geojson(Vec::new());
+
+    assert!(collection.features.is_empty());
+}
+
+#[test]
+fn invalid_input() {
+    let mut cells = disk(48.85, 2.35, 1);
+    cells.push(cells[0]);
+
+    let result = Exporter::new().dissolve(cells);
+
+    assert!(matches!(result, Err(ExportError::InvalidInput(_))));
+}
+
+#[test]
+fn cut() {
+    assert_eq!(
+        cut_meridian(vec![(170., 190.), (-10., 40.), (20., 30.)]),
+        Some(105.)
+    );
+    // Gap across the wrapping point.
+    assert_eq!(cut_meridian(vec![(-170., 170.)]), Some(180.));
+    assert_eq!(cut_meridian(vec![(-180., 0.), (0., 180.)]), None);
+    assert_eq!(cut_meridian(Vec::new()), None);
+}
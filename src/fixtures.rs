@@ -0,0 +1,229 @@
+//! Fixtures shared by the unit tests.
+#![expect(clippy::panic, reason = "unit tests, this is fine")]
+
+use crate::{
+    TileID,
+    render::{canonicalize, layer_to_geometry},
+    tiles_for_cell,
+};
+use ahash::{HashMap, HashSet};
+use float_eq::{assert_float_eq, float_eq};
+use geo::{Geometry, LineString, MultiPolygon, Polygon};
+use geojson::{Feature, FeatureCollection, feature::Id as FeatureId};
+use geozero::mvt::tile::Layer;
+use h3o::{CellIndex, LatLng, Resolution};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Returns a small dataset: a disk of 61 cells, in Paris.
+pub fn disk() -> Vec<CellIndex> {
+    let center = CellIndex::try_from(0x891fb46622fffff).expect("cell");
+    center.grid_disk::<Vec<_>>(4)
+}
+
+/// Returns a coarse dataset: a disk of 19 resolution 3 cells, in Norway.
+///
+/// Cells edges are long, and far from the equator: in tile coordinates they
+/// depart a lot from the straight lines they are in EPSG:4326 coordinates.
+pub fn coarse_disk() -> Vec<CellIndex> {
+    let center = LatLng::new(60., 5.6)
+        .expect("coordinate")
+        .to_cell(Resolution::Three);
+    center.grid_disk::<Vec<_>>(2)
+}
+
+/// Returns the tiles, at the given zoom level, touched by the cells.
+pub fn tiles(cells: &[CellIndex], zoom: u8) -> HashSet<TileID> {
+    cells
+        .iter()
+        .flat_map(|&cell| tiles_for_cell(cell, zoom..=zoom))
+        .collect()
+}
+
+/// Asserts that two rendered layers have the same rings, in tile coordinates,
+/// regardless of their starting vertex.
+pub fn assert_same_rings(tile_id: TileID, result: &Layer, expected: &Layer) {
+    let rings = |layer: &Layer| {
+        let mut shape = layer
+            .features
+            .iter()
+            .flat_map(|feature| {
+                let mut writer = geozero::geo_types::GeoWriter::new();
+                geozero::mvt::process_geom(feature, &mut writer)
+                    .expect("MVT geometry");
+                match writer.take_geometry() {
+                    Some(Geometry::Polygon(polygon)) => vec![polygon],
+                    Some(Geometry::MultiPolygon(shape)) => shape.0,
+                    _ => panic!("unexpected geometry in {tile_id:?}"),
+                }
+            })
+            .collect::<MultiPolygon>();
+        // Coordinates are integers: the canonical form is exact.
+        canonicalize(&mut shape);
+        shape
+    };
+
+    assert_eq!(rings(result), rings(expected), "{tile_id:?}");
+}
+
+/// Checks rendered tiles, of a given zoom level, against the golden files.
+///
+/// If `UPDATE_GOLDEN_FILE` is set, the golden files are updated instead.
+pub fn assert_golden_tiles(
+    testcase: &str,
+    zoom: u8,
+    scratch: bool,
+    layers: impl IntoIterator<Item = (TileID, Layer)>,
+) {
+    // Skip the empty tiles.
+    let geometries = layers
+        .into_iter()
+        .filter_map(|(tile_id, layer)| {
+            layer_to_geometry(tile_id, &layer).map(|geometry| {
+                let ((x, y), z) = (tile_id.xy(), tile_id.zoom());
+                let tile_name = format!("{z}/{x}/{y}");
+
+                (tile_name, geometry)
+            })
+        })
+        .collect::<HashMap<_, _>>();
+
+    // Build the corresponding filename.
+    let suffix = if scratch { "hollow" } else { "filled" };
+    let filepath: PathBuf = [
+        env!("CARGO_MANIFEST_DIR"),
+        "testdata",
+        testcase,
+        &format!("{zoom}-{suffix}.json"),
+    ]
+    .iter()
+    .collect();
+
+    // In update mode, save the result as a GeoJSON FeatureCollection.
+    if std::env::var_os("UPDATE_GOLDEN_FILE").is_some() {
+        return save_golden_file(&filepath, geometries);
+    }
+
+    // Otherwise, check the geometries against the corresponding golden file.
+    let references = load_golden_file(&filepath);
+    assert_eq!(geometries.len(), references.len(), "tile count");
+    for (tile_name, result) in geometries {
+        let expected = references[&tile_name].clone();
+        assert_geometry_equals(&result, &expected);
+    }
+}
+
+// Persists the specified geometries on disk.
+fn save_golden_file(filepath: &Path, geometries: HashMap<String, Geometry>) {
+    let feature_collection = FeatureCollection {
+        bbox: None,
+        features: geometries
+            .into_iter()
+            .map(|(tile_name, geometry)| Feature {
+                bbox: None,
+                geometry: Some((&geometry).into()),
+                id: Some(FeatureId::String(tile_name)),
+                properties: None,
+                foreign_members: None,
+            })
+            .collect(),
+        foreign_members: None,
+    };
+
+    let payload = feature_collection.to_string();
+    fs::write(filepath, payload)
+        .unwrap_or_else(|err| panic!("writing {}: {err}", filepath.display()));
+}
+
+// Load geometries from a golden file.
+fn load_golden_file(filepath: &Path) -> HashMap<String, Geometry> {
+    let payload = fs::read_to_string(filepath)
+        .unwrap_or_else(|err| panic!("reading {}: {err}", filepath.display()));
+    let feature_collection = payload
+        .parse::<FeatureCollection>()
+        .unwrap_or_else(|err| panic!("parsing {}: {err}", filepath.display()));
+    feature_collection
+        .features
+        .into_iter()
+        .map(|feature| {
+            let Some(FeatureId::String(tile_name)) = feature.id else {
+                panic!(
+                    "invalid or missing feature ID in {}",
+                    filepath.display()
+                )
+            };
+
+            let geometry: Geometry = feature
+                .geometry
+                .unwrap_or_else(|| panic!("missing geometry in {tile_name}"))
+                .try_into()
+                .unwrap_or_else(|err| {
+                    panic!("invalid geometry in {tile_name}: {err}")
+                });
+
+            (tile_name, geometry)
+        })
+        .collect()
+}
+
+fn assert_geometry_equals(lhs: &Geometry, rhs: &Geometry) {
+    match (lhs, rhs) {
+        (Geometry::MultiPolygon(lhs), Geometry::MultiPolygon(rhs)) => {
+            assert_eq!(lhs.0.len(), rhs.0.len(), "polygon count");
+            for (lhs, rhs) in lhs.0.iter().zip(rhs.0.iter()) {
+                assert_polygon_equals(lhs, rhs);
+            }
+        }
+        (Geometry::Polygon(lhs), Geometry::Polygon(rhs)) => {
+            assert_polygon_equals(lhs, rhs);
+        }
+        _ => panic!("unexpected geometry types"),
+    }
+}
+
+fn assert_polygon_equals(lhs: &Polygon, rhs: &Polygon) {
+    assert_linestring_equals(lhs.exterior(), rhs.exterior());
+    assert_eq!(lhs.interiors().len(), rhs.interiors().len(), "hole count");
+    // TODO: be order-agnostic?
+    for (lhs, rhs) in lhs.interiors().iter().zip(rhs.interiors().iter()) {
+        assert_linestring_equals(lhs, rhs);
+    }
+}
+
+/// Assert that two `LineString` are equivalent.
+///
+/// `LineString` are equivalent if they contains the same point in the same order
+/// (but they don't necessarily start at the same point).
+fn assert_linestring_equals(lhs: &LineString, rhs: &LineString) {
+    // Based on https://gis.stackexchange.com/a/8674
+    const EPSILON: f64 = 1e-5;
+
+    let lhs_is_closed = lhs.is_closed();
+    let mut lhs = lhs.coords().collect::<Vec<_>>();
+    if lhs_is_closed {
+        lhs.pop(); // Remove the duplicated coord that close the ring.
+    }
+
+    let rhs_is_closed = rhs.is_closed();
+    let mut rhs = rhs.coords().collect::<Vec<_>>();
+    if rhs_is_closed {
+        rhs.pop(); // Remove the duplicated coord that close the ring
+    }
+
+    assert_eq!(lhs.len(), rhs.len(), "linestring size mismatch");
+    let offset = rhs
+        .iter()
+        .position(|&coord| {
+            float_eq!(coord.x, lhs[0].x, abs <= EPSILON)
+                && float_eq!(coord.y, lhs[0].y, abs <= EPSILON)
+        })
+        .expect("linestring are different");
+    for (i, lhs) in lhs.iter().enumerate() {
+        let j = (i + offset) % rhs.len();
+
+        assert_float_eq!(lhs.x, rhs[j].x, abs <= EPSILON);
+        assert_float_eq!(lhs.y, rhs[j].y, abs <= EPSILON);
+    }
+}
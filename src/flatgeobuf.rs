@@ -0,0 +1,426 @@
+use crate::{ExportError, FeatureWriter, PropertyValue, TileFeature};
+use geo::{CoordsIter, Polygon};
+use std::io;
+
+/// Magic bytes of a FlatGeobuf file (version 3).
+const MAGIC: [u8; 8] = *b"fgb\x03fgb\x00";
+
+// Geometry types.
+/// Polygon.
+const POLYGON: u8 = 3;
+/// Multi-polygon.
+const MULTI_POLYGON: u8 = 6;
+
+/// Type of a FlatGeobuf property column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ColumnType {
+    /// Booleans, from [`PropertyValue::Bool`].
+    Bool,
+    /// Signed 64-bit integers, from [`PropertyValue::Int`].
+    Long,
+    /// Unsigned 64-bit integers, from [`PropertyValue::UInt`].
+    ULong,
+    /// Double-precision floats, from [`PropertyValue::Double`].
+    Double,
+    /// UTF-8 strings, from [`PropertyValue::String`].
+    String,
+}
+
+impl ColumnType {
+    /// Returns the code of the type, in the FlatGeobuf schema.
+    const fn code(self) -> u8 {
+        match self {
+            Self::Bool => 2,
+            Self::Long => 7,
+            Self::ULong => 8,
+            Self::Double => 10,
+            Self::String => 11,
+        }
+    }
+}
+
+/// A streaming writer of FlatGeobuf files.
+///
+/// Features are multi-polygons, in EPSG:4326 coordinates. As the file is
+/// written in a single pass, it has neither spatial index nor feature count,
+/// and the property columns must be declared upfront. Feature IDs are not
+/// stored.
+///
+/// See <https://flatgeobuf.org>
+#[derive(Debug)]
+pub struct FlatGeobufWriter<W> {
+    /// Output.
+    writer: W,
+    /// Property columns.
+    columns: Vec<(String, ColumnType)>,
+    /// Scratch buffer for the current feature.
+    buffer: Vec<u8>,
+    /// Scratch buffer for the encoded properties.
+    properties: Vec<u8>,
+}
+
+impl<W: io::Write> FlatGeobufWriter<W> {
+    /// Initializes a new writer, and writes the header of the dataset.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error that occurred while writing, if any.
+    pub fn new(
+        mut writer: W,
+        name: &str,
+        columns: &[(&str, ColumnType)],
+    ) -> Result<Self, ExportError> {
+        let header = vec![
+            (0, Field::String(name)),
+            (2, Field::UByte(MULTI_POLYGON)),
+            (
+                7,
+                Field::Tables(
+                    columns
+                        .iter()
+                        .map(|&(name, kind)| {
+                            vec![
+                                (0, Field::String(name)),
+                                (1, Field::UByte(kind.code())),
+                            ]
+                        })
+                        .collect(),
+                ),
+            ),
+            (8, Field::ULong(0)),
+            (9, Field::UShort(0)),
+            (
+                10,
+                Field::Table(vec![
+                    (0, Field::String("EPSG")),
+                    (1, Field::Int(4326)),
+                ]),
+            ),
+        ];
+        let mut buffer = MAGIC.to_vec();
+        serialize(&mut buffer, &header);
+        writer.write_all(&buffer).map_err(ExportError::Io)?;
+
+        Ok(Self {
+            writer,
+            columns: columns
+                .iter()
+                .map(|&(name, kind)| (name.to_owned(), kind))
+                .collect(),
+            buffer,
+            properties: Vec::new(),
+        })
+    }
+
+    /// Flushes the dataset, and returns the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error that occurred while writing, if any.
+    pub fn finish(mut self) -> Result<W, ExportError> {
+        self.writer.flush().map_err(ExportError::Io)?;
+
+        Ok(self.writer)
+    }
+
+    /// Encodes the properties of a feature.
+    fn encode_properties(
+        &mut self,
+        feature: &TileFeature<'_>,
+    ) -> Result<(), ExportError> {
+        self.properties.clear();
+        for &(name, value) in feature.properties() {
+            let index = self
+                .columns
+                .iter()
+                .position(|column| column.0 == name)
+                .filter(|&index| {
+                    matches!(
+                        (self.columns[index].1, value),
+                        (ColumnType::Bool, PropertyValue::Bool(_))
+                            | (ColumnType::Long, PropertyValue::Int(_))
+                            | (ColumnType::ULong, PropertyValue::UInt(_))
+                            | (ColumnType::Double, PropertyValue::Double(_))
+                            | (ColumnType::String, PropertyValue::String(_))
+                    )
+                })
+                .and_then(|index| u16::try_from(index).ok())
+                .ok_or_else(|| ExportError::InvalidProperty(name.to_owned()))?;
+
+            self.properties.extend(index.to_le_bytes());
+            match value {
+                PropertyValue::Bool(value) => {
+                    self.properties.push(value.into());
+                }
+                PropertyValue::Int(value) => {
+                    self.properties.extend(value.to_le_bytes());
+                }
+                PropertyValue::UInt(value) => {
+                    self.properties.extend(value.to_le_bytes());
+                }
+                PropertyValue::Double(value) => {
+                    self.properties.extend(value.to_le_bytes());
+                }
+                PropertyValue::String(value) => {
+                    let len = u32::try_from(value.len()).map_err(|_| {
+                        ExportError::InvalidProperty(name.to_owned())
+                    })?;
+                    self.properties.extend(len.to_le_bytes());
+                    self.properties.extend_from_slice(value.as_bytes());
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: io::Write> FeatureWriter for FlatGeobufWriter<W> {
+    fn write_feature(
+        &mut self,
+        feature: &TileFeature<'_>,
+    ) -> Result<(), ExportError> {
+        self.encode_properties(feature)?;
+
+        let polygons = feature
+            .geometry()
+            .iter()
+            .map(PolygonBuffers::from)
+            .collect::<Vec<_>>();
+        let parts = polygons
+            .iter()
+            .map(|polygon| {
+                let mut part = vec![(1, Field::Doubles(&polygon.xy))];
+                // Ends are only needed with several rings.
+                if polygon.ends.len() > 1 {
+                    part.push((0, Field::UInts(&polygon.ends)));
+                }
+                part.push((6, Field::UByte(POLYGON)));
+                part
+            })
+            .collect();
+        let mut root = vec![(
+            0,
+            Field::Table(vec![
+                (6, Field::UByte(MULTI_POLYGON)),
+                (7, Field::Tables(parts)),
+            ]),
+        )];
+        if !self.properties.is_empty() {
+            root.push((1, Field::Bytes(&self.properties)));
+        }
+
+        self.buffer.clear();
+        serialize(&mut self.buffer, &root);
+        self.writer.write_all(&self.buffer).map_err(ExportError::Io)
+    }
+}
+
+/// Flattened coordinates of a polygon.
+struct PolygonBuffers {
+    /// Interleaved coordinates, ring after ring.
+    xy: Vec<f64>,
+    /// End of each ring, in vertices.
+    ends: Vec<u32>,
+}
+
+impl From<&Polygon> for PolygonBuffers {
+    fn from(value: &Polygon) -> Self {
+        let mut xy = Vec::with_capacity(value.coords_count() * 2);
+        let mut ends = Vec::with_capacity(value.interiors().len() + 1);
+        for ring in std::iter::once(value.exterior()).chain(value.interiors()) {
+            xy.extend(ring.coords().flat_map(|coord| [coord.x, coord.y]));
+            ends.push(u32::try_from(xy.len() / 2).expect("too many vertices"));
+        }
+        Self { xy, ends }
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+/// A FlatBuffers table, as a list of fields with their slot.
+type Table<'a> = Vec<(u16, Field<'a>)>;
+
+/// Value of a FlatBuffers table field.
+enum Field<'a> {
+    UByte(u8),
+    UShort(u16),
+    Int(i32),
+    ULong(u64),
+    String(&'a str),
+    Bytes(&'a [u8]),
+    UInts(&'a [u32]),
+    Doubles(&'a [f64]),
+    Table(Table<'a>),
+    Tables(Vec<Table<'a>>),
+}
+
+impl Field<'_> {
+    /// Returns the size of the field inside its table.
+    const fn inline_size(&self) -> usize {
+        match *self {
+            Self::UByte(_) => 1,
+            Self::UShort(_) => 2,
+            Self::ULong(_) => 8,
+            Self::Int(_)
+            | Self::String(_)
+            | Self::Bytes(_)
+            | Self::UInts(_)
+            | Self::Doubles(_)
+            | Self::Table(_)
+            | Self::Tables(_) => 4,
+        }
+    }
+}
+
+/// Appends a size-prefixed FlatBuffers buffer, whose root is the given table.
+///
+/// Unlike the reference builders, objects are laid out front to back: every
+/// table is preceded by its vtable and followed by its children. Alignment is
+/// relative to the start of the buffer (i.e. its size prefix).
+fn serialize(buffer: &mut Vec<u8>, root: &Table<'_>) {
+    let base = buffer.len();
+    // Size prefix and root offset, patched at the end.
+    buffer.extend([0; 8]);
+    let table = write_table(buffer, base, root);
+    patch_offset(buffer, base + 4, table);
+    let size = u32::try_from(buffer.len() - base - 4).expect("buffer size");
+    buffer[base..base + 4].copy_from_slice(&size.to_le_bytes());
+}
+
+/// Writes a table, preceded by its vtable, then its children.
+///
+/// Returns the position of the table.
+fn write_table(buffer: &mut Vec<u8>, base: usize, fields: &Table<'_>) -> usize {
+    // Largest fields first, to keep them aligned without too much padding.
+    let mut order = (0..fields.len()).collect::<Vec<_>>();
+    order
+        .sort_by_key(|&index| std::cmp::Reverse(fields[index].1.inline_size()));
+    let mut offsets = vec![0; fields.len()];
+    // Fields start after the vtable offset.
+    let mut size = 4_usize;
+    for index in order {
+        let field_size = fields[index].1.inline_size();
+        size = size.next_multiple_of(field_size);
+        offsets[index] = size;
+        size += field_size;
+    }
+
+    let slots = fields.iter().map(|&(slot, _)| slot + 1).max().unwrap_or(0);
+    let mut vtable = vec![0_u16; usize::from(slots) + 2];
+    vtable[0] = 2 * (slots + 2);
+    vtable[1] = u16::try_from(size).expect("table size");
+    for (&(slot, _), &offset) in fields.iter().zip(&offsets) {
+        vtable[usize::from(slot) + 2] =
+            u16::try_from(offset).expect("field offset");
+    }
+    align(buffer, base, 2);
+    let vtable_position = buffer.len();
+    buffer.extend(vtable.iter().flat_map(|value| value.to_le_bytes()));
+
+    align(buffer, base, 8);
+    let table = buffer.len();
+    buffer.resize(table + size, 0);
+    let vtable_offset =
+        i32::try_from(table - vtable_position).expect("vtable offset");
+    buffer[table..table + 4].copy_from_slice(&vtable_offset.to_le_bytes());
+
+    for ((_, field), offset) in fields.iter().zip(offsets) {
+        let position = table + offset;
+        let child = match *field {
+            Field::UByte(value) => {
+                buffer[position] = value;
+                continue;
+            }
+            Field::UShort(value) => {
+                buffer[position..position + 2]
+                    .copy_from_slice(&value.to_le_bytes());
+                continue;
+            }
+            Field::Int(value) => {
+                buffer[position..position + 4]
+                    .copy_from_slice(&value.to_le_bytes());
+                continue;
+            }
+            Field::ULong(value) => {
+                buffer[position..position + 8]
+                    .copy_from_slice(&value.to_le_bytes());
+                continue;
+            }
+            Field::String(value) => {
+                let child = write_vector(buffer, base, 1, value.as_bytes());
+                // Strings are null-terminated.
+                buffer.push(0);
+                child
+            }
+            Field::Bytes(value) => write_vector(buffer, base, 1, value),
+            Field::UInts(values) => {
+                let bytes = values
+                    .iter()
+                    .flat_map(|value| value.to_le_bytes())
+                    .collect::<Vec<_>>();
+                write_vector(buffer, base, 4, &bytes)
+            }
+            Field::Doubles(values) => {
+                let bytes = values
+                    .iter()
+                    .flat_map(|value| value.to_le_bytes())
+                    .collect::<Vec<_>>();
+                write_vector(buffer, base, 8, &bytes)
+            }
+            Field::Table(ref table) => write_table(buffer, base, table),
+            Field::Tables(ref tables) => {
+                align(buffer, base, 4);
+                let vector = buffer.len();
+                let len = u32::try_from(tables.len()).expect("vector size");
+                buffer.extend(len.to_le_bytes());
+                buffer.resize(vector + 4 + 4 * tables.len(), 0);
+                for (index, table) in tables.iter().enumerate() {
+                    let child = write_table(buffer, base, table);
+                    patch_offset(buffer, vector + 4 + 4 * index, child);
+                }
+                vector
+            }
+        };
+        patch_offset(buffer, position, child);
+    }
+
+    table
+}
+
+/// Writes a vector of scalars (given as little-endian bytes).
+///
+/// Returns the position of the vector.
+fn write_vector(
+    buffer: &mut Vec<u8>,
+    base: usize,
+    element_size: usize,
+    bytes: &[u8],
+) -> usize {
+    // The elements, right after the length, must be aligned too.
+    while !(buffer.len() - base + 4).is_multiple_of(element_size.max(4)) {
+        buffer.push(0);
+    }
+    let vector = buffer.len();
+    let len = u32::try_from(bytes.len() / element_size).expect("vector size");
+    buffer.extend(len.to_le_bytes());
+    buffer.extend_from_slice(bytes);
+    vector
+}
+
+/// Pads the buffer up to the given alignment.
+fn align(buffer: &mut Vec<u8>, base: usize, alignment: usize) {
+    let len = (buffer.len() - base).next_multiple_of(alignment);
+    buffer.resize(base + len, 0);
+}
+
+/// Writes, at `position`, the offset toward `target`.
+fn patch_offset(buffer: &mut [u8], position: usize, target: usize) {
+    let offset = u32::try_from(target - position).expect("forward offset");
+    buffer[position..position + 4].copy_from_slice(&offset.to_le_bytes());
+}
+
+#[cfg(test)]
+#[path = "./flatgeobuf_tests.rs"]
+mod tests;
@@ -0,0 +1,192 @@
+use super::*;
+use crate::Exporter;
+use flatgeobuf::{
+    FallibleStreamingIterator, FeatureProperties, FgbReader, GeometryType,
+};
+use geo::{MultiPolygon, polygon};
+use geozero::{ColumnValue, PropertyProcessor, ToGeo, error::GeozeroError};
+use h3o::{CellIndex, LatLng, Resolution};
+use std::io::Cursor;
+
+/// A property value, as read by the reference reader.
+#[derive(Debug, PartialEq)]
+enum Value {
+    Bool(bool),
+    Long(i64),
+    ULong(u64),
+    Double(f64),
+    String(String),
+}
+
+/// Collects the properties of a feature.
+#[derive(Default)]
+struct Properties(Vec<(String, Value)>);
+
+impl PropertyProcessor for Properties {
+    fn property(
+        &mut self,
+        _index: usize,
+        name: &str,
+        value: &ColumnValue<'_>,
+    ) -> Result<bool, GeozeroError> {
+        let value = match *value {
+            ColumnValue::Bool(value) => Value::Bool(value),
+            ColumnValue::Long(value) => Value::Long(value),
+            ColumnValue::ULong(value) => Value::ULong(value),
+            ColumnValue::Double(value) => Value::Double(value),
+            ColumnValue::String(value) => Value::String(value.to_owned()),
+            _ => return Err(GeozeroError::Property(name.to_owned())),
+        };
+        self.0.push((name.to_owned(), value));
+        Ok(false)
+    }
+}
+
+/// Reads the features of a file with the reference reader (which verifies
+/// every buffer).
+fn features(file: &[u8]) -> Vec<(MultiPolygon, Vec<(String, Value)>)> {
+    let mut features = FgbReader::open(Cursor::new(file))
+        .expect("header")
+        .select_all_seq()
+        .expect("features");
+    let mut result = Vec::new();
+    while let Some(feature) = features.next().expect("feature") {
+        let geometry = MultiPolygon::try_from(feature.to_geo().expect("geo"))
+            .expect("multi-polygon");
+        let mut properties = Properties::default();
+        feature
+            .process_properties(&mut properties)
+            .expect("properties");
+        result.push((geometry, properties.0));
+    }
+    result
+}
+
+#[test]
+fn header() {
+    let columns = [
+        ("name", ColumnType::String),
+        ("count", ColumnType::ULong),
+        ("delta", ColumnType::Long),
+        ("ratio", ColumnType::Double),
+        ("valid", ColumnType::Bool),
+    ];
+
+    let file = FlatGeobufWriter::new(Vec::new(), "h3", &columns)
+        .expect("writer")
+        .finish()
+        .expect("finish");
+
+    let reader = FgbReader::open(Cursor::new(&file)).expect("header");
+    let header = reader.header();
+    assert_eq!(header.name(), Some("h3"));
+    assert_eq!(header.geometry_type(), GeometryType::MultiPolygon);
+    assert_eq!(header.features_count(), 0);
+    assert_eq!(header.index_node_size(), 0);
+    let columns = header
+        .columns()
+        .expect("columns")
+        .iter()
+        .map(|column| (column.name(), column.type_()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        columns,
+        [
+            ("name", flatgeobuf::ColumnType::String),
+            ("count", flatgeobuf::ColumnType::ULong),
+            ("delta", flatgeobuf::ColumnType::Long),
+            ("ratio", flatgeobuf::ColumnType::Double),
+            ("valid", flatgeobuf::ColumnType::Bool),
+        ]
+    );
+    let crs = header.crs().expect("CRS");
+    assert_eq!(crs.org(), Some("EPSG"));
+    assert_eq!(crs.code(), 4326);
+    assert!(features(&file).is_empty());
+}
+
+#[test]
+fn features_and_properties() {
+    let first = MultiPolygon::new(vec![polygon!(
+        exterior: [
+            (x: 0., y: 0.),
+            (x: 10., y: 0.),
+            (x: 10., y: 10.),
+            (x: 0., y: 10.),
+        ],
+        interiors: [[
+            (x: 2., y: 2.),
+            (x: 2., y: 8.),
+            (x: 8., y: 8.),
+            (x: 8., y: 2.),
+        ]],
+    )]);
+    let cell = LatLng::new(48.85, 2.35)
+        .expect("coordinate")
+        .to_cell(Resolution::Six);
+    let second = Exporter::new()
+        .dissolve(cell.grid_disk::<Vec<CellIndex>>(1))
+        .expect("dissolve");
+    let properties = [
+        ("name", PropertyValue::String("first")),
+        ("valid", PropertyValue::Bool(true)),
+        ("delta", PropertyValue::Int(-3)),
+        ("ratio", PropertyValue::Double(0.5)),
+        ("count", PropertyValue::UInt(u64::MAX)),
+    ];
+    let columns = [
+        ("ratio", ColumnType::Double),
+        ("valid", ColumnType::Bool),
+        ("delta", ColumnType::Long),
+        ("name", ColumnType::String),
+        ("count", ColumnType::ULong),
+    ];
+    let mut writer =
+        FlatGeobufWriter::new(Vec::new(), "test", &columns).expect("writer");
+
+    writer
+        .write_feature(&TileFeature::new(&first).with_properties(&properties))
+        .expect("first");
+    writer
+        .write_feature(&TileFeature::new(&second).with_id(3))
+        .expect("second");
+    let file = writer.finish().expect("finish");
+
+    let features = features(&file);
+    assert_eq!(features.len(), 2);
+    assert_eq!(features[0].0, first);
+    // Properties come in the order of the feature.
+    assert_eq!(
+        features[0].1,
+        [
+            ("name".to_owned(), Value::String("first".to_owned())),
+            ("valid".to_owned(), Value::Bool(true)),
+            ("delta".to_owned(), Value::Long(-3)),
+            ("ratio".to_owned(), Value::Double(0.5)),
+            ("count".to_owned(), Value::ULong(u64::MAX)),
+        ]
+    );
+    assert_eq!(features[1].0, second);
+    assert!(features[1].1.is_empty());
+}
+
+#[test]
+fn invalid_property() {
+    let geometry = MultiPolygon::new(vec![]);
+    let columns = [("count", ColumnType::ULong)];
+    let mut writer =
+        FlatGeobufWriter::new(Vec::new(), "test", &columns).expect("writer");
+
+    for properties in [
+        [("count", PropertyValue::Int(3))],
+        [("other", PropertyValue::UInt(3))],
+    ] {
+        let result = writer.write_feature(
+            &TileFeature::new(&geometry).with_properties(&properties),
+        );
+        assert!(
+            matches!(result, Err(ExportError::InvalidProperty(_))),
+            "{properties:?}"
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use crate::{
+    Limits, RenderingError, TileID,
+    render::{
+        Buffers, clip_around, project_polygon_into_world, render_world_into,
+        tiles_for_cell,
+    },
+};
+use ahash::HashSet;
+use geo::MultiPolygon;
+use geozero::mvt::{Message as _, Tile, tile::Layer};
+use h3o::{CellIndex, geom::SolventBuilder};
+use std::{ops::RangeInclusive, sync::Arc};
+
+/// A generator streaming every non-empty tile of a dataset, over a range of
+/// zoom levels.
+///
+/// The dataset is dissolved once, then the tile pyramid is walked top-down:
+/// the geometry of a tile is clipped from the geometry of its parent, instead
+/// of being computed from scratch, which keeps the cost of each tile
+/// proportional to its content. Geometries are clipped in world coordinates,
+/// where the edges are the ones rendered: tiles are the same as the ones
+/// rendered by [`render`](crate::render).
+///
+/// Tiles are yielded in depth-first order, encoded as MVT tiles with a single
+/// layer. The remaining work can be shared between several consumers (e.g.
+/// threads) using [`TileGenerator::split`].
+#[derive(Debug, Clone)]
+pub struct TileGenerator {
+    /// Zoom levels of the generated tiles.
+    zoom: RangeInclusive<u8>,
+    /// Name of the generated layers.
+    name: String,
+    /// Whether the shape is scratched off the tiles.
+    scratch: bool,
+    /// Non-empty tiles, from zoom 0 to the maximum zoom.
+    tiles: Arc<HashSet<TileID>>,
+    /// Dissolved shape, in EPSG:4326 coordinates.
+    ///
+    /// The tiles of zoom 1 are projected from it, as they are the first ones
+    /// to pick a side of the antimeridian.
+    shape: Arc<MultiPolygon>,
+    /// Pending tiles, with their geometry in world coordinates.
+    pending: Vec<(TileID, MultiPolygon)>,
+}
+
+impl TileGenerator {
+    /// Initializes a new generator for the given cells, over the given zoom
+    /// levels.
+    ///
+    /// # Errors
+    ///
+    /// All cell indexes must be unique and have the same resolution, otherwise
+    /// a `RenderingError::InvalidInput` is returned.
+    ///
+    /// # Panics
+    ///
+    /// If the zoom range extends beyond the maximum zoom level.
+    pub fn new(
+        cells: impl IntoIterator<Item = CellIndex>,
+        zoom: RangeInclusive<u8>,
+        name: String,
+        scratch: bool,
+    ) -> Result<Self, RenderingError> {
+        assert!(TileID::new(0, 0, *zoom.end()).is_ok(), "invalid zoom range");
+
+        let cells = cells.into_iter().collect::<Vec<_>>();
+        let tiles = cells
+            .iter()
+            .flat_map(|&cell| tiles_for_cell(cell, 0..=*zoom.end()))
+            .collect::<HashSet<_>>();
+        let geometry = SolventBuilder::new()
+            .build()
+            .dissolve(cells)
+            .map_err(RenderingError::InvalidInput)?;
+
+        let root = TileID::new_unchecked(0, 0, 0);
+        let pending = if zoom.is_empty() || geometry.0.is_empty() {
+            Vec::new()
+        } else {
+            vec![(root, project(&geometry, root))]
+        };
+
+        Ok(Self {
+            zoom,
+            name,
+            scratch,
+            tiles: Arc::new(tiles),
+            shape: Arc::new(geometry),
+            pending,
+        })
+    }
+
+    /// Splits off part of the remaining work into a new generator.
+    ///
+    /// Both generators can then be consumed independently, and they yield
+    /// disjoint sets of tiles.
+    ///
+    /// Returns `None` if there isn't enough pending work to be shared, which
+    /// is always the case before the first tile has been generated.
+    pub fn split(&mut self) -> Option<Self> {
+        (self.pending.len() > 1).then(|| {
+            let pending = self.pending.split_off(self.pending.len() / 2);
+            Self {
+                zoom: self.zoom.clone(),
+                name: self.name.clone(),
+                scratch: self.scratch,
+                tiles: Arc::clone(&self.tiles),
+                shape: Arc::clone(&self.shape),
+                pending,
+            }
+        })
+    }
+
+    /// Queues the non-empty children of the tile, clipped from its geometry.
+    fn expand(&mut self, tile_id: TileID, geometry: &MultiPolygon) {
+        if tile_id.zoom() >= *self.zoom.end() {
+            return;
+        }
+        let children = tile_id
+            .children()
+            .filter(|child| self.tiles.contains(child))
+            .map(|child| {
+                let geometry = if tile_id.zoom() == 0 {
+                    clip_around(&project(&self.shape, child), child)
+                } else {
+                    clip_around(geometry, child)
+                };
+                (child, geometry)
+            })
+            .filter(|(_, geometry)| !geometry.0.is_empty());
+
+        // Reversed, to pop them in order.
+        self.pending.extend(children.rev());
+    }
+}
+
+impl Iterator for TileGenerator {
+    type Item = (TileID, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((tile_id, geometry)) = self.pending.pop() {
+            self.expand(tile_id, &geometry);
+            if tile_id.zoom() < *self.zoom.start() {
+                continue;
+            }
+
+            let mut layer = Layer {
+                name: self.name.clone(),
+                ..Layer::default()
+            };
+            render_world_into(
+                tile_id,
+                Some(geometry),
+                self.scratch,
+                &Limits::new(),
+                &mut Buffers::default(),
+                &mut layer,
+            )
+            .expect("unlimited rendering");
+            if layer.features.is_empty() {
+                continue;
+            }
+            let tile = Tile {
+                layers: vec![layer],
+            };
+
+            return Some((tile_id, tile.encode_to_vec()));
+        }
+
+        None
+    }
+}
+
+/// Projects the shape, in EPSG:4326 coordinates, into world coordinates for
+/// the given tile.
+fn project(shape: &MultiPolygon, tile_id: TileID) -> MultiPolygon {
+    shape
+        .iter()
+        .cloned()
+        .map(|mut polygon| {
+            project_polygon_into_world(&mut polygon, tile_id);
+            polygon
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "./generator_tests.rs"]
+mod tests;
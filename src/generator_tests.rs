@@ -0,0 +1,118 @@
+use super::*;
+use crate::{
+    fixtures::{assert_same_rings, coarse_disk, disk},
+    render,
+};
+use ahash::HashMap;
+use h3o::Resolution;
+
+fn generate(generator: TileGenerator) -> HashMap<TileID, Vec<u8>> {
+    generator.collect()
+}
+
+#[test]
+fn every_tile() {
+    let cells = disk();
+    let generator = TileGenerator::new(
+        cells.iter().copied(),
+        12..=15,
+        "test".to_owned(),
+        false,
+    )
+    .expect("generator");
+    let expected = cells
+        .iter()
+        .flat_map(|&cell| tiles_for_cell(cell, 12..=15))
+        .collect::<HashSet<_>>();
+
+    let result = generate(generator);
+
+    assert_eq!(result.keys().copied().collect::<HashSet<_>>(), expected);
+}
+
+#[test]
+fn same_as_render() {
+    // A cell crossing the antimeridian.
+    let transmeridian = CellIndex::try_from(0x8a9b4361e0effff)
+        .expect("cell")
+        .parent(Resolution::Zero)
+        .expect("parent");
+
+    // Fine cells at high zoom, coarse cells (long edges, bent by the
+    // projection) spanning many tiles, and both sides of the antimeridian.
+    for (cells, zoom) in [
+        (disk(), 12..=16),
+        (coarse_disk(), 8..=11),
+        (vec![transmeridian], 0..=5),
+    ] {
+        for scratch in [false, true] {
+            assert_same_as_render(&cells, zoom.clone(), scratch);
+        }
+    }
+}
+
+fn assert_same_as_render(
+    cells: &[CellIndex],
+    zoom: RangeInclusive<u8>,
+    scratch: bool,
+) {
+    let generator = TileGenerator::new(
+        cells.iter().copied(),
+        zoom,
+        "test".to_owned(),
+        scratch,
+    )
+    .expect("generator");
+
+    for (tile_id, bytes) in generate(generator) {
+        let mut tile = Tile::decode(bytes.as_slice()).expect("MVT tile");
+        assert_eq!(tile.layers.len(), 1);
+        let result = tile.layers.pop().expect("layer");
+        let expected =
+            render(tile_id, cells.iter().copied(), "test".to_owned(), scratch)
+                .expect("render");
+
+        // The clipping box is larger than the tile buffer, so the final
+        // clipping yields the same rings (but rounding errors may change
+        // their starting vertex).
+        assert_same_rings(tile_id, &result, &expected);
+    }
+}
+
+#[test]
+fn split() {
+    let cells = disk();
+    let mut generator = TileGenerator::new(
+        cells.iter().copied(),
+        0..=15,
+        "test".to_owned(),
+        true,
+    )
+    .expect("generator");
+    let expected = generate(generator.clone());
+
+    assert!(generator.split().is_none());
+    let mut result = HashMap::default();
+    let other = loop {
+        if let Some(other) = generator.split() {
+            break other;
+        }
+        let (tile_id, bytes) = generator.next().expect("pending tile");
+        result.insert(tile_id, bytes);
+    };
+
+    result.extend(generate(generator));
+    let other = generate(other);
+    assert!(!other.is_empty());
+    assert!(other.keys().all(|tile_id| !result.contains_key(tile_id)));
+    result.extend(other);
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn empty() {
+    let generator = TileGenerator::new([], 0..=10, "test".to_owned(), true)
+        .expect("generator");
+
+    assert_eq!(generator.count(), 0);
+}
@@ -0,0 +1,215 @@
+use crate::{TileID, render::tiles_for_cell};
+use ahash::HashSet;
+use h3o::CellIndex;
+use std::ops::Range;
+
+/// Maximum number of zoom levels between a tile and the sub-tiles used to
+/// cover its buffer.
+///
+/// With the default buffer (80/4096 ≈ 1/51 of a tile) a single row of 32
+/// sub-tiles per side is enough to cover the buffer, keeping the number of
+/// lookups per query bounded (at most 132).
+const BUFFER_DEPTH: u8 = 5;
+
+/// A spatial index bucketing a set of cells by tiles.
+///
+/// Every cell is assigned to the tiles it touches at the index zoom level, and
+/// buckets are stored in Z-order (Morton order): this way, the bucket of a
+/// coarser tile is a contiguous range of the index, no matter the zoom level.
+///
+/// Memory usage is roughly 16 bytes per (cell, tile) pair at the index zoom
+/// level, plus a set of the non-empty tiles. Choose the index zoom level such
+/// that tiles are bigger than the cells, otherwise the number of pairs grows
+/// quickly.
+#[derive(Debug, Clone)]
+pub struct TileIndex {
+    /// Zoom level of the buckets.
+    zoom: u8,
+    /// Morton codes of the buckets, sorted.
+    keys: Vec<u64>,
+    /// Cells, bucketed by `keys`.
+    cells: Vec<CellIndex>,
+    /// Non-empty tiles, from zoom 0 to `zoom`.
+    tiles: HashSet<TileID>,
+}
+
+impl TileIndex {
+    /// Builds a new index over the given cells, bucketed at `zoom`.
+    ///
+    /// Queries for tiles at finer zoom levels are still supported, but the
+    /// returned cells are those of the ancestor tile at `zoom`.
+    #[must_use]
+    pub fn new(cells: impl IntoIterator<Item = CellIndex>, zoom: u8) -> Self {
+        let mut entries = cells
+            .into_iter()
+            .flat_map(|cell| {
+                tiles_for_cell(cell, zoom..=zoom)
+                    .into_iter()
+                    .map(move |tile_id| (morton_code(tile_id), cell))
+            })
+            .collect::<Vec<_>>();
+        entries.sort_unstable();
+        entries.dedup();
+        let (keys, cells) = entries.into_iter().unzip::<_, _, Vec<_>, Vec<_>>();
+
+        // Keep track of every non-empty tile, up to zoom 0.
+        let mut tiles = HashSet::default();
+        for key in keys.iter().copied() {
+            let mut tile_id = from_morton_code(key, zoom);
+            while tiles.insert(tile_id) {
+                let Some(parent) = tile_id
+                    .zoom()
+                    .checked_sub(1)
+                    .and_then(|zoom| tile_id.parent(zoom))
+                else {
+                    break;
+                };
+                tile_id = parent;
+            }
+        }
+
+        Self {
+            zoom,
+            keys,
+            cells,
+            tiles,
+        }
+    }
+
+    /// Returns the zoom level at which cells are bucketed.
+    #[must_use]
+    pub const fn zoom(&self) -> u8 {
+        self.zoom
+    }
+
+    /// Returns true if at least one cell touches the given tile.
+    ///
+    /// The tile buffer is not taken into account here.
+    #[must_use]
+    pub fn contains(&self, tile_id: TileID) -> bool {
+        let tile_id = tile_id.parent(self.zoom).unwrap_or(tile_id);
+        self.tiles.contains(&tile_id)
+    }
+
+    /// Returns the cells touching the given tile, buffer included.
+    ///
+    /// The result is sorted and may contain cells slightly outside of the
+    /// buffered tile (but they are clipped out at rendering time anyway).
+    #[must_use]
+    pub fn cells(&self, tile_id: TileID) -> Vec<CellIndex> {
+        let tile_id = tile_id.parent(self.zoom).unwrap_or(tile_id);
+        // The buffer is made of sub-tiles of the neighbors: if they are all
+        // empty, so is the buffered tile.
+        if !self.tiles.contains(&tile_id)
+            && !tile_id
+                .neighbors()
+                .any(|neighbor| self.tiles.contains(&neighbor))
+        {
+            return Vec::new();
+        }
+
+        let mut cells = self.bucket(tile_id).to_vec();
+        for neighbor in buffer_tiles(tile_id, self.zoom) {
+            if self.tiles.contains(&neighbor) {
+                cells.extend_from_slice(self.bucket(neighbor));
+            }
+        }
+        // A cell can overlap several buckets.
+        cells.sort_unstable();
+        cells.dedup();
+
+        cells
+    }
+
+    /// Returns the cells bucketed under the given tile.
+    fn bucket(&self, tile_id: TileID) -> &[CellIndex] {
+        let Range { start, end } = self.key_range(tile_id);
+        let start = self.keys.partition_point(|&key| key < start);
+        let end = self.keys.partition_point(|&key| key < end);
+
+        &self.cells[start..end]
+    }
+
+    /// Returns the range of Morton codes covered by the given tile.
+    fn key_range(&self, tile_id: TileID) -> Range<u64> {
+        debug_assert!(tile_id.zoom() <= self.zoom);
+        let shift = 2 * u32::from(self.zoom - tile_id.zoom());
+        let start = morton_code(tile_id) << shift;
+
+        start..start + (1 << shift)
+    }
+}
+
+/// Returns the tiles covering the buffer of the given tile.
+///
+/// Tiles are chosen as fine as possible (up to `BUFFER_DEPTH` zoom levels
+/// below the tile, and not finer than `max_zoom`).
+fn buffer_tiles(tile_id: TileID, max_zoom: u8) -> impl Iterator<Item = TileID> {
+    let delta = (max_zoom - tile_id.zoom()).min(BUFFER_DEPTH);
+    let zoom = tile_id.zoom() + delta;
+    let (x, y) = tile_id.xy();
+    let (x, y) = (i64::from(x) << delta, i64::from(y) << delta);
+    let size = 1_i64 << delta;
+    let bound = 1_i64 << zoom;
+    // How many sub-tiles are needed to cover the buffer.
+    let depth =
+        i64::from((TileID::buffer() << delta).div_ceil(TileID::extent()))
+            .min(bound);
+
+    ((y - depth)..(y + size + depth))
+        // Wrap around the antimeridian, but not around the poles.
+        .filter(move |&j| (0..bound).contains(&j))
+        .flat_map(move |j| {
+            let is_inner_row = (y..y + size).contains(&j);
+            ((x - depth)..(x + size + depth))
+                // Skip the tile itself.
+                .filter(move |&i| !is_inner_row || !(x..x + size).contains(&i))
+                .map(move |i| {
+                    let (i, j) = (i.rem_euclid(bound), j);
+                    #[expect(
+                        clippy::cast_possible_truncation,
+                        clippy::cast_sign_loss,
+                        reason = "coordinates are in [0; 2^zoom)"
+                    )]
+                    TileID::new_unchecked(i as u32, j as u32, zoom)
+                })
+        })
+}
+
+/// Computes the Morton code (a.k.a. Z-order) of the tile.
+const fn morton_code(tile_id: TileID) -> u64 {
+    let (x, y) = tile_id.xy();
+    spread_bits(x) | (spread_bits(y) << 1)
+}
+
+/// Rebuilds a tile ID from its Morton code and zoom level.
+fn from_morton_code(code: u64, zoom: u8) -> TileID {
+    TileID::new_unchecked(compact_bits(code), compact_bits(code >> 1), zoom)
+}
+
+/// Interleaves the bits of `value` with zeros.
+const fn spread_bits(value: u32) -> u64 {
+    let mut value = value as u64;
+    value = (value | (value << 16)) & 0x0000_ffff_0000_ffff;
+    value = (value | (value << 8)) & 0x00ff_00ff_00ff_00ff;
+    value = (value | (value << 4)) & 0x0f0f_0f0f_0f0f_0f0f;
+    value = (value | (value << 2)) & 0x3333_3333_3333_3333;
+    value = (value | (value << 1)) & 0x5555_5555_5555_5555;
+    value
+}
+
+/// Inverse of `spread_bits`.
+#[expect(clippy::cast_possible_truncation, reason = "value fits on 32 bits")]
+const fn compact_bits(value: u64) -> u32 {
+    let mut value = value & 0x5555_5555_5555_5555;
+    value = (value | (value >> 1)) & 0x3333_3333_3333_3333;
+    value = (value | (value >> 2)) & 0x0f0f_0f0f_0f0f_0f0f;
+    value = (value | (value >> 4)) & 0x00ff_00ff_00ff_00ff;
+    value = (value | (value >> 8)) & 0x0000_ffff_0000_ffff;
+    value = (value | (value >> 16)) & 0x0000_0000_ffff_ffff;
+    value as u32
+}
+
+#[cfg(test)]
+#[path = "./index_tests.rs"]
+mod tests;
@@ -0,0 +1,152 @@
+use super::*;
+use h3o::{Resolution, geom::ContainmentMode};
+
+fn dataset() -> Vec<CellIndex> {
+    [
+        0x8a65b508518ffff,
+        0x8a65b5085c57fff,
+        0x8a65b50858dffff,
+        0x8a65b519b66ffff,
+        0x8a65b5083457fff,
+        0x8a65b50855a7fff,
+        0x8a65b50b434ffff,
+        0x8a65b50b4757fff,
+        0x8a65b5085ce7fff,
+        0x8a65b50b08c7fff,
+        0x8a65b50b598ffff,
+        0x8a65b50b4267fff,
+        0x8a65b50834e7fff,
+        0x8a65b50851affff,
+        0x8a65b50b08f7fff,
+        0x8a65b50b5cb7fff,
+    ]
+    .into_iter()
+    .map(|value| CellIndex::try_from(value).expect("valid cell"))
+    .collect()
+}
+
+#[test]
+fn morton_roundtrip() {
+    let tile = TileID::new_unchecked(265544, 180338, 19);
+    let code = morton_code(tile);
+
+    assert_eq!(from_morton_code(code, 19), tile);
+    assert_eq!(morton_code(TileID::new_unchecked(1, 0, 1)), 0b01);
+    assert_eq!(morton_code(TileID::new_unchecked(0, 1, 1)), 0b10);
+    assert_eq!(morton_code(TileID::new_unchecked(3, 2, 2)), 0b1101);
+}
+
+#[test]
+fn empty_tile() {
+    let index = TileIndex::new(dataset(), 14);
+    let tile = TileID::new_unchecked(518, 352, 10);
+
+    assert!(!index.contains(tile));
+    assert!(index.cells(tile).is_empty());
+}
+
+#[test]
+fn every_zoom_level() {
+    let cells = dataset();
+    let index = TileIndex::new(cells.iter().copied(), 14);
+
+    for zoom in 0..=14 {
+        let tiles = cells
+            .iter()
+            .flat_map(|&cell| tiles_for_cell(cell, zoom..=zoom))
+            .collect::<HashSet<_>>();
+
+        for tile_id in tiles {
+            assert!(index.contains(tile_id), "{tile_id:?}");
+            let result = index.cells(tile_id);
+            let expected = cells
+                .iter()
+                .copied()
+                .filter(|&cell| {
+                    tiles_for_cell(cell, zoom..=zoom).contains(&tile_id)
+                })
+                .collect::<Vec<_>>();
+
+            assert!(result.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(
+                expected.iter().all(|cell| result.contains(cell)),
+                "{tile_id:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn whole_world() {
+    let cells = dataset();
+    let index = TileIndex::new(cells.iter().copied(), 12);
+    let mut expected = cells;
+    expected.sort_unstable();
+
+    let result = index.cells(TileID::new_unchecked(0, 0, 0));
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn includes_buffer() {
+    let tile = TileID::new_unchecked(3266, 1926, 12);
+    let inside = tile
+        .coverage(Resolution::Ten, ContainmentMode::ContainsCentroid, 0)
+        .collect::<HashSet<_>>();
+    let buffered = tile.coverage(
+        Resolution::Ten,
+        ContainmentMode::ContainsCentroid,
+        TileID::buffer(),
+    );
+    // Cell in the buffer, not touching the tile itself.
+    let cell = buffered
+        .filter(|cell| !inside.contains(cell))
+        .find(|&cell| !tiles_for_cell(cell, 12..=12).contains(&tile))
+        .expect("cell in the buffer");
+    let anchor = *inside.iter().next().expect("cell inside");
+    let index = TileIndex::new([anchor, cell], 12);
+
+    let result = index.cells(tile);
+
+    assert!(result.contains(&anchor));
+    assert!(result.contains(&cell));
+}
+
+#[test]
+fn only_buffer() {
+    let tile = TileID::new_unchecked(3266, 1926, 12);
+    let inside = tile
+        .coverage(Resolution::Ten, ContainmentMode::ContainsCentroid, 0)
+        .collect::<HashSet<_>>();
+    let cell = tile
+        .coverage(
+            Resolution::Ten,
+            ContainmentMode::ContainsCentroid,
+            TileID::buffer(),
+        )
+        .filter(|cell| !inside.contains(cell))
+        .find(|&cell| !tiles_for_cell(cell, 12..=12).contains(&tile))
+        .expect("cell in the buffer");
+    let index = TileIndex::new([cell], 12);
+    // The tile has no data of its own, but renders its buffer.
+    let layer =
+        crate::render(tile, [cell], "test".to_owned(), false).expect("render");
+    assert!(!layer.features.is_empty());
+
+    let result = index.cells(tile);
+
+    assert!(!index.contains(tile));
+    assert_eq!(result, [cell]);
+}
+
+#[test]
+fn finer_zoom() {
+    let cells = dataset();
+    let index = TileIndex::new(cells.iter().copied(), 10);
+    let cell = cells[0];
+    let tile_id = *tiles_for_cell(cell, 16..=16).iter().next().expect("tile");
+
+    assert!(index.contains(tile_id));
+    assert!(index.cells(tile_id).contains(&cell));
+}
@@ -115,12 +115,60 @@
 
 // }}}
 
+mod clip;
+mod coverage;
+mod decode;
+mod encoder;
 mod error;
+mod export;
+#[cfg(test)]
+mod fixtures;
+mod flatgeobuf;
+mod generator;
+mod index;
+mod limits;
+mod merge;
+#[cfg(feature = "mlt")]
+mod mlt;
+mod mvt;
+mod prepared;
+mod pyramid;
+#[cfg(feature = "raster")]
+mod raster;
 mod render;
-mod tile;
-// TODO: if possible, try to reuse the implementation from h3o instead.
+mod renderer;
 mod ring_hierarchy;
+mod store;
+mod tile;
+mod utfgrid;
+mod validate;
 
-pub use error::{InvalidTileID, RenderingError};
-pub use render::{render, tiles_for_cell};
-pub use tile::TileID;
+pub use decode::decode_layer;
+pub use encoder::{
+    GeoJsonEncoder, MvtEncoder, PropertyValue, TileEncoder, TileFeature,
+};
+pub use error::{
+    AbortReason, DecodingError, ExportError, InvalidTileID, MergeError,
+    PyramidError, RenderingError, StoreError,
+};
+pub use export::{Exporter, FeatureWriter, GeoJsonWriter};
+pub use flatgeobuf::{ColumnType, FlatGeobufWriter};
+pub use generator::TileGenerator;
+pub use index::TileIndex;
+pub use limits::{CancellationToken, Limits};
+pub use merge::{MAX_TILE_SIZE, MergeMode, merge_layers};
+#[cfg(feature = "mlt")]
+pub use mlt::MltEncoder;
+pub use prepared::PreparedDataset;
+pub use pyramid::{Aggregation, Pyramid, ResolutionPolicy};
+#[cfg(feature = "raster")]
+pub use raster::{Fill, RasterEncoder, RasterSize};
+pub use render::{
+    encode_layer, render, render_geometry, render_with, tiles_for_cell,
+};
+pub use renderer::Renderer;
+pub use ring_hierarchy::{Nesting, RingHierarchy};
+pub use store::CellStore;
+pub use tile::{TileCoverage, TileID};
+pub use utfgrid::UtfGridEncoder;
+pub use validate::{Finding, Issue, validate_layer};
@@ -0,0 +1,155 @@
+use crate::{AbortReason, RenderingError};
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Instant,
+};
+
+/// Limits bounding the work done to render a tile.
+///
+/// Limits are checked between the rendering stages (dissolution, projection,
+/// clipping and encoding): exceeding one aborts the rendering with a
+/// `RenderingError::Aborted`. By default, there is no limit.
+///
+/// # Example
+///
+/// ```
+/// use h3o_mvt::{CancellationToken, Limits};
+/// use std::time::{Duration, Instant};
+///
+/// let token = CancellationToken::new();
+/// let limits = Limits::new()
+///     .max_cells(100_000)
+///     .deadline(Instant::now() + Duration::from_millis(200))
+///     .cancellation(token.clone());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    /// Maximum number of input cells.
+    max_cells: Option<usize>,
+    /// Maximum number of vertices of the geometry to render.
+    max_vertices: Option<usize>,
+    /// Point in time after which the rendering is aborted.
+    deadline: Option<Instant>,
+    /// Token used to cancel the rendering.
+    cancellation: Option<CancellationToken>,
+}
+
+impl Limits {
+    /// Initializes limits without any restriction.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            max_cells: None,
+            max_vertices: None,
+            deadline: None,
+            cancellation: None,
+        }
+    }
+
+    /// Sets the maximum number of input cells.
+    #[must_use]
+    pub const fn max_cells(mut self, count: usize) -> Self {
+        self.max_cells = Some(count);
+        self
+    }
+
+    /// Sets the maximum number of vertices of the geometry to render.
+    #[must_use]
+    pub const fn max_vertices(mut self, count: usize) -> Self {
+        self.max_vertices = Some(count);
+        self
+    }
+
+    /// Sets a deadline.
+    #[must_use]
+    pub const fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Sets a cancellation token.
+    #[must_use]
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Returns the maximum number of input cells, if any.
+    pub(crate) const fn cell_limit(&self) -> Option<usize> {
+        self.max_cells
+    }
+
+    /// Checks the deadline and the cancellation token.
+    pub(crate) fn check(&self) -> Result<(), RenderingError> {
+        if self
+            .cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(RenderingError::Aborted(AbortReason::Cancelled));
+        }
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(RenderingError::Aborted(AbortReason::DeadlineExceeded));
+        }
+        Ok(())
+    }
+
+    /// Checks the number of input cells.
+    pub(crate) fn check_cells(
+        &self,
+        count: usize,
+    ) -> Result<(), RenderingError> {
+        if self.max_cells.is_some_and(|max| count > max) {
+            return Err(RenderingError::Aborted(AbortReason::TooManyCells));
+        }
+        Ok(())
+    }
+
+    /// Checks the number of vertices of the geometry.
+    pub(crate) fn check_vertices(
+        &self,
+        count: usize,
+    ) -> Result<(), RenderingError> {
+        if self.max_vertices.is_some_and(|max| count > max) {
+            return Err(RenderingError::Aborted(AbortReason::TooManyVertices));
+        }
+        Ok(())
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+/// A token to cancel renderings from another thread or task.
+///
+/// Clones share the same state: cancelling one cancels them all.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Initializes a new, uncancelled, token.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels the renderings using this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns true if the token has been cancelled.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+#[path = "./limits_tests.rs"]
+mod tests;
@@ -0,0 +1,161 @@
+use super::*;
+use crate::{
+    PreparedDataset, Renderer, TileID, fixtures::disk, render, tiles_for_cell,
+};
+use geozero::mvt::tile::Layer;
+use h3o::{CellIndex, Resolution};
+use std::time::Duration;
+
+fn tile() -> TileID {
+    let center = CellIndex::try_from(0x891fb46622fffff).expect("cell");
+    tiles_for_cell(center, 14..=14)
+        .into_iter()
+        .min()
+        .expect("tile")
+}
+
+fn aborted(result: &Result<(), RenderingError>) -> Option<AbortReason> {
+    match *result {
+        Err(RenderingError::Aborted(reason)) => Some(reason),
+        _ => None,
+    }
+}
+
+fn render_with(limits: Limits) -> Result<(), RenderingError> {
+    let mut renderer = Renderer::new("test".to_owned(), false);
+    renderer.set_limits(limits);
+    renderer.render_into(tile(), disk(), &mut Layer::default())
+}
+
+#[test]
+fn unlimited() {
+    let expected =
+        render(tile(), disk(), "test".to_owned(), false).expect("render");
+    let mut renderer = Renderer::new("test".to_owned(), false);
+    renderer.set_limits(
+        Limits::new()
+            .max_cells(61)
+            .max_vertices(1000)
+            .deadline(Instant::now() + Duration::from_hours(1))
+            .cancellation(CancellationToken::new()),
+    );
+    let mut layer = Layer::default();
+
+    renderer
+        .render_into(tile(), disk(), &mut layer)
+        .expect("render into");
+
+    assert!(!layer.features.is_empty());
+    assert_eq!(layer, expected);
+}
+
+#[test]
+fn max_cells() {
+    let result = render_with(Limits::new().max_cells(60));
+
+    assert_eq!(aborted(&result), Some(AbortReason::TooManyCells));
+}
+
+#[test]
+fn max_cells_before_dissolve() {
+    // Too many cells is reported before the duplicates.
+    let cells = disk().into_iter().chain(disk());
+    let mut renderer = Renderer::new("test".to_owned(), false);
+    renderer.set_limits(Limits::new().max_cells(100));
+
+    let result = renderer.render_into(tile(), cells, &mut Layer::default());
+
+    assert_eq!(aborted(&result), Some(AbortReason::TooManyCells));
+}
+
+#[test]
+fn geometry_stage() {
+    let mut renderer = Renderer::new("test".to_owned(), false);
+    renderer.set_limits(Limits::new().max_cells(60));
+
+    let result = renderer.render_geometry(tile(), disk()).map(|_| ());
+
+    assert_eq!(aborted(&result), Some(AbortReason::TooManyCells));
+}
+
+#[test]
+fn max_vertices() {
+    let result = render_with(Limits::new().max_vertices(10));
+
+    assert_eq!(aborted(&result), Some(AbortReason::TooManyVertices));
+}
+
+#[test]
+fn deadline() {
+    let result = render_with(Limits::new().deadline(Instant::now()));
+
+    assert_eq!(aborted(&result), Some(AbortReason::DeadlineExceeded));
+}
+
+#[test]
+fn cancellation() {
+    let token = CancellationToken::new();
+    let limits = Limits::new().cancellation(token.clone());
+    assert!(render_with(limits.clone()).is_ok());
+
+    token.cancel();
+
+    assert!(token.is_cancelled());
+    assert_eq!(aborted(&render_with(limits)), Some(AbortReason::Cancelled));
+}
+
+#[test]
+fn layer_untouched() {
+    let mut renderer = Renderer::new("test".to_owned(), false);
+    let mut layer = Layer::default();
+    renderer
+        .render_into(tile(), disk(), &mut layer)
+        .expect("render into");
+    let expected = layer.clone();
+    renderer.set_limits(Limits::new().max_vertices(10));
+
+    let result = renderer.render_into(tile(), disk(), &mut layer);
+
+    assert_eq!(aborted(&result), Some(AbortReason::TooManyVertices));
+    assert_eq!(layer, expected);
+}
+
+#[test]
+fn prepared() {
+    let dataset = PreparedDataset::new(disk()).expect("dataset");
+    let limits = Limits::new().max_cells(0);
+    let layer = dataset
+        .render_with_limits(tile(), "test".to_owned(), false, &limits)
+        .expect("cell limit doesn't apply");
+    assert_eq!(layer, dataset.render(tile(), "test".to_owned(), false));
+
+    let limits = Limits::new().max_vertices(10);
+    let result = dataset
+        .render_with_limits(tile(), "test".to_owned(), false, &limits)
+        .map(drop);
+
+    assert_eq!(aborted(&result), Some(AbortReason::TooManyVertices));
+}
+
+#[test]
+fn cells() {
+    let tile_id = TileID::new(0, 0, 0).expect("tile");
+    let limits = Limits::new().max_cells(1_000_000);
+
+    // Rejected before any expansion (the world has 10^10 cells at res 10).
+    let result = tile_id
+        .cells_with_limits(Resolution::Ten, &limits)
+        .map(drop);
+    assert_eq!(aborted(&result), Some(AbortReason::TooManyCells));
+
+    let result = tile_id
+        .cells_with_limits(Resolution::Two, &limits)
+        .expect("cells");
+    assert_eq!(result, tile_id.cells(Resolution::Two));
+
+    let limits = Limits::new().deadline(Instant::now());
+    let result = tile_id
+        .cells_with_limits(Resolution::Two, &limits)
+        .map(drop);
+    assert_eq!(aborted(&result), Some(AbortReason::DeadlineExceeded));
+}
@@ -0,0 +1,218 @@
+use crate::{MergeError, TileID, mvt};
+use flate2::{
+    read::{GzDecoder, ZlibDecoder},
+    write::{GzEncoder, ZlibEncoder},
+};
+use geo::{Geometry, MapCoords, MultiPolygon};
+use geozero::mvt::{
+    Message, Tile,
+    tile::{GeomType, Layer},
+};
+use std::io::{Read, Write};
+
+/// Maximum size of a decompressed tile, in bytes.
+///
+/// Tiles are usually well under a megabyte: this only protects against
+/// decompression bombs.
+pub const MAX_TILE_SIZE: usize = 64 << 20;
+
+/// How to handle a layer whose name is already used in the tile.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MergeMode {
+    /// The existing layer is replaced, in place.
+    #[default]
+    Replace,
+    /// The new layer is added, renamed with a numeric suffix (e.g. `h3_1`).
+    Append,
+}
+
+/// Compression of an encoded tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compression {
+    /// Raw protobuf.
+    None,
+    /// Gzip member.
+    Gzip,
+    /// Zlib stream.
+    Zlib,
+}
+
+/// Merges layers into an existing encoded MVT tile.
+///
+/// The tile can be raw, gzip or zlib compressed, and the result is
+/// compressed the same way. An empty input is an empty tile.
+///
+/// Compressed tiles are decompressed up to [`MAX_TILE_SIZE`] bytes.
+///
+/// Layers using a different extent than the tile (the extent of its first
+/// layer) are rescaled to it. Features whose geometry collapses once rescaled
+/// are dropped.
+///
+/// # Errors
+///
+/// `MergeError::InvalidCompression` is returned if the tile cannot be
+/// decompressed, `MergeError::TooLarge` if it decompresses to more than
+/// [`MAX_TILE_SIZE`] bytes, `MergeError::InvalidTile` if it cannot be decoded,
+/// and
+/// `MergeError::InvalidGeometry` or `MergeError::UnsupportedGeometry` if a
+/// layer to rescale holds an invalid or non-polygonal feature.
+///
+/// # Example
+///
+/// ```
+/// use geozero::mvt::{Message, Tile, tile::Layer};
+/// use h3o::CellIndex;
+/// use h3o_mvt::{MergeMode, merge_layers, render, tiles_for_cell};
+///
+/// let basemap = Tile {
+///     layers: vec![Layer {
+///         name: "water".to_owned(),
+///         extent: Some(4096),
+///         version: 2,
+///         ..Layer::default()
+///     }],
+/// };
+/// let cell = CellIndex::try_from(0x891fb46622fffff)?;
+/// let tile_id = tiles_for_cell(cell, 14..=14).into_iter().next().expect("tile");
+/// let layer = render(tile_id, [cell], "h3".to_owned(), false)?;
+///
+/// let bytes = merge_layers(&basemap.encode_to_vec(), [layer], MergeMode::Replace)?;
+/// let tile = Tile::decode(bytes.as_slice())?;
+/// assert_eq!(tile.layers.len(), 2);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub fn merge_layers(
+    tile: &[u8],
+    layers: impl IntoIterator<Item = Layer>,
+    mode: MergeMode,
+) -> Result<Vec<u8>, MergeError> {
+    let compression = Compression::detect(tile);
+    let bytes = match compression {
+        Compression::None => None,
+        Compression::Gzip => Some(decompress(GzDecoder::new(tile))?),
+        Compression::Zlib => Some(decompress(ZlibDecoder::new(tile))?),
+    };
+    let mut merged = Tile::decode(bytes.as_deref().unwrap_or(tile))
+        .map_err(|err| MergeError::InvalidTile(Box::new(err)))?;
+
+    let extent = merged.layers.first().map_or(TileID::extent(), |layer| {
+        layer.extent.unwrap_or(TileID::extent())
+    });
+    for mut layer in layers {
+        rescale(&mut layer, extent)?;
+        let existing = merged
+            .layers
+            .iter()
+            .position(|existing| existing.name == layer.name);
+        match (existing, mode) {
+            (Some(index), MergeMode::Replace) => merged.layers[index] = layer,
+            (Some(_), MergeMode::Append) => {
+                // At most one name per layer is taken.
+                layer.name = (1..=merged.layers.len())
+                    .map(|suffix| format!("{}_{suffix}", layer.name))
+                    .find(|name| {
+                        merged.layers.iter().all(|layer| layer.name != *name)
+                    })
+                    .expect("unused name");
+                merged.layers.push(layer);
+            }
+            (None, _) => merged.layers.push(layer),
+        }
+    }
+
+    let bytes = merged.encode_to_vec();
+    let level = flate2::Compression::default();
+    Ok(match compression {
+        Compression::None => bytes,
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), level);
+            encoder
+                .write_all(&bytes)
+                .and_then(|()| encoder.finish())
+                .expect("in-memory compression")
+        }
+        Compression::Zlib => {
+            let mut encoder = ZlibEncoder::new(Vec::new(), level);
+            encoder
+                .write_all(&bytes)
+                .and_then(|()| encoder.finish())
+                .expect("in-memory compression")
+        }
+    })
+}
+
+impl Compression {
+    /// Detects the compression of an encoded tile from its header.
+    fn detect(tile: &[u8]) -> Self {
+        match *tile {
+            [0x1f, 0x8b, ..] => Self::Gzip,
+            // Deflate method, and header checksum.
+            [cmf, flg, ..]
+                if cmf & 0x0f == 8
+                    && (u16::from(cmf) << 8 | u16::from(flg))
+                        .is_multiple_of(31) =>
+            {
+                Self::Zlib
+            }
+            _ => Self::None,
+        }
+    }
+}
+
+/// Reads a decompressed tile, up to [`MAX_TILE_SIZE`] bytes.
+fn decompress(decoder: impl Read) -> Result<Vec<u8>, MergeError> {
+    let mut bytes = Vec::new();
+    decoder
+        .take(MAX_TILE_SIZE as u64 + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|_| MergeError::InvalidCompression)?;
+    if bytes.len() > MAX_TILE_SIZE {
+        return Err(MergeError::TooLarge);
+    }
+    Ok(bytes)
+}
+
+/// Rescales the features of a layer to the given extent.
+fn rescale(layer: &mut Layer, extent: u32) -> Result<(), MergeError> {
+    let current = layer.extent.unwrap_or(TileID::extent());
+    layer.extent = Some(extent);
+    if current == extent {
+        return Ok(());
+    }
+
+    let scale = f64::from(extent) / f64::from(current);
+    let mut encoder = mvt::Encoder::default();
+    let mut features = std::mem::take(&mut layer.features);
+    for (index, feature) in features.iter_mut().enumerate() {
+        let kind = feature
+            .r#type
+            .and_then(GeomType::from_i32)
+            .unwrap_or(GeomType::Unknown);
+        if kind != GeomType::Polygon {
+            return Err(MergeError::UnsupportedGeometry(index));
+        }
+
+        let mut writer = geozero::geo_types::GeoWriter::new();
+        geozero::mvt::process_geom(feature, &mut writer)
+            .map_err(MergeError::InvalidGeometry)?;
+        let polygons = match writer.take_geometry() {
+            Some(Geometry::Polygon(polygon)) => {
+                MultiPolygon::new(vec![polygon])
+            }
+            Some(Geometry::MultiPolygon(polygons)) => polygons,
+            None => continue,
+            Some(_) => return Err(MergeError::UnsupportedGeometry(index)),
+        };
+        let polygons = polygons.map_coords(|coord| coord * scale);
+        if encoder.encode(&polygons, feature) {
+            layer.features.push(std::mem::take(feature));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "./merge_tests.rs"]
+mod tests;
@@ -0,0 +1,184 @@
+use super::*;
+use crate::{render, tiles_for_cell};
+use geo::{BoundingRect, polygon};
+use geozero::mvt::tile::Feature;
+use h3o::{CellIndex, Resolution};
+
+/// Returns a basemap tile, with a water and a `h3` layer.
+fn basemap(extent: u32) -> Tile {
+    let water = polygon![
+        (x: 0., y: 0.),
+        (x: f64::from(extent), y: 0.),
+        (x: f64::from(extent), y: f64::from(extent) / 2.),
+    ];
+    let mut feature = Feature::default();
+    assert!(mvt::Encoder::default().encode([&water], &mut feature));
+    let layer = |name: &str, features| Layer {
+        name: name.to_owned(),
+        extent: Some(extent),
+        version: 2,
+        features,
+        ..Layer::default()
+    };
+
+    Tile {
+        layers: vec![layer("water", vec![feature]), layer("h3", vec![])],
+    }
+}
+
+/// Returns a rendered layer.
+fn rendered() -> Layer {
+    let cell = CellIndex::try_from(0x891fb46622fffff).expect("cell");
+    let cells = cell.children(Resolution::Eleven);
+    let tile_id = tiles_for_cell(cell, 15..=15)
+        .into_iter()
+        .find(|tile| tile.zoom() == 15)
+        .expect("tile");
+
+    render(tile_id, cells, "h3".to_owned(), false).expect("rendered layer")
+}
+
+fn names(tile: &Tile) -> Vec<&str> {
+    tile.layers
+        .iter()
+        .map(|layer| layer.name.as_str())
+        .collect()
+}
+
+fn decode(bytes: &[u8]) -> Tile {
+    Tile::decode(bytes).expect("valid tile")
+}
+
+fn gzip(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(bytes).expect("compressed");
+    encoder.finish().expect("compressed")
+}
+
+fn zlib(bytes: &[u8]) -> Vec<u8> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder.write_all(bytes).expect("compressed");
+    encoder.finish().expect("compressed")
+}
+
+#[test]
+fn replace() {
+    let layer = rendered();
+
+    let bytes = merge_layers(
+        &basemap(4096).encode_to_vec(),
+        [layer.clone()],
+        MergeMode::Replace,
+    )
+    .expect("merged");
+
+    let tile = decode(&bytes);
+    assert_eq!(names(&tile), ["water", "h3"]);
+    assert_eq!(tile.layers[1], layer);
+}
+
+#[test]
+fn append() {
+    let mut other = rendered();
+    other.name = "cells".to_owned();
+    let layers = [rendered(), rendered(), other];
+
+    let bytes =
+        merge_layers(&basemap(4096).encode_to_vec(), layers, MergeMode::Append)
+            .expect("merged");
+
+    let tile = decode(&bytes);
+    assert_eq!(names(&tile), ["water", "h3", "h3_1", "h3_2", "cells"]);
+    assert!(tile.layers[1].features.is_empty());
+}
+
+#[test]
+fn extent() {
+    let layer = rendered();
+    let bytes = merge_layers(
+        &basemap(512).encode_to_vec(),
+        [layer.clone()],
+        MergeMode::Replace,
+    )
+    .expect("merged");
+
+    let tile = decode(&bytes);
+    let merged = &tile.layers[1];
+    assert_eq!(merged.extent, Some(512));
+    assert_eq!(merged.features.len(), layer.features.len());
+    for (merged, original) in merged.features.iter().zip(&layer.features) {
+        assert_eq!(merged.tags, original.tags);
+        let bbox = |feature| {
+            let mut writer = geozero::geo_types::GeoWriter::new();
+            geozero::mvt::process_geom(feature, &mut writer).expect("geometry");
+            writer
+                .take_geometry()
+                .and_then(|geometry| geometry.bounding_rect())
+                .expect("bbox")
+        };
+        let (merged, original) = (bbox(merged), bbox(original));
+        assert!((merged.min().x - original.min().x / 8.).abs() <= 1.);
+        assert!((merged.max().y - original.max().y / 8.).abs() <= 1.);
+    }
+}
+
+#[test]
+fn compression() {
+    let bytes = basemap(4096).encode_to_vec();
+
+    for compression in [Compression::Gzip, Compression::Zlib] {
+        let compressed = match compression {
+            Compression::Gzip => gzip(&bytes),
+            Compression::Zlib => zlib(&bytes),
+            Compression::None => unreachable!("compressed tile"),
+        };
+
+        let merged = merge_layers(&compressed, [rendered()], MergeMode::Append)
+            .expect("merged");
+
+        assert_eq!(Compression::detect(&merged), compression);
+        let bytes = match compression {
+            Compression::Gzip => decompress(GzDecoder::new(&merged[..])),
+            _ => decompress(ZlibDecoder::new(&merged[..])),
+        };
+        let tile = decode(&bytes.expect("same compression"));
+        assert_eq!(names(&tile), ["water", "h3", "h3_1"]);
+    }
+}
+
+#[test]
+fn empty() {
+    let bytes =
+        merge_layers(&[], [rendered()], MergeMode::Append).expect("merged");
+
+    let tile = decode(&bytes);
+    assert_eq!(names(&tile), ["h3"]);
+    assert_eq!(tile.layers[0].extent, Some(TileID::extent()));
+}
+
+#[test]
+fn invalid() {
+    let mut bytes = gzip(&basemap(4096).encode_to_vec());
+    let last = bytes.len() - 1;
+    bytes[last] ^= 1;
+
+    assert!(matches!(
+        merge_layers(&bytes, [], MergeMode::Append),
+        Err(MergeError::InvalidCompression)
+    ));
+    assert!(matches!(
+        merge_layers(b"\x1a\xff", [], MergeMode::Append),
+        Err(MergeError::InvalidTile(_))
+    ));
+}
+
+#[test]
+fn too_large() {
+    let bomb = gzip(&vec![0; MAX_TILE_SIZE + 1]);
+
+    assert!(bomb.len() < MAX_TILE_SIZE / 100);
+    assert!(matches!(
+        merge_layers(&bomb, [], MergeMode::Append),
+        Err(MergeError::TooLarge)
+    ));
+}
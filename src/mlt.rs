@@ -0,0 +1,496 @@
+use crate::{PropertyValue, TileEncoder, TileFeature, TileID, mvt};
+
+/// Tag of a layer, in an encoded MLT tile.
+const LAYER_TAG: u64 = 1;
+
+// Column types.
+/// Non-nullable 64-bit identifiers.
+const ID: u64 = 2;
+/// Nullable 64-bit identifiers.
+const OPTIONAL_ID: u64 = 3;
+/// Geometry.
+const GEOMETRY: u64 = 4;
+/// Booleans (add 1 for the nullable variant).
+const BOOLEAN: u64 = 10;
+/// Signed 64-bit integers (add 1 for the nullable variant).
+const INT_64: u64 = 20;
+/// Unsigned 64-bit integers (add 1 for the nullable variant).
+const UINT_64: u64 = 22;
+/// Double-precision floats (add 1 for the nullable variant).
+const DOUBLE: u64 = 26;
+/// UTF-8 strings (add 1 for the nullable variant).
+const STRING: u64 = 28;
+
+// Physical stream types, with their logical sub-type.
+/// Nullability bitmap.
+const PRESENT: u8 = 0;
+/// Plain data.
+const DATA: u8 = 1 << 4;
+/// Vertex buffer.
+const DATA_VERTEX: u8 = (1 << 4) | 3;
+/// Lengths of variable-size values.
+const LENGTH_VAR_BINARY: u8 = 3 << 4;
+/// Number of polygons of each multi-polygon.
+const LENGTH_GEOMETRIES: u8 = (3 << 4) | 1;
+/// Number of rings of each polygon.
+const LENGTH_PARTS: u8 = (3 << 4) | 2;
+/// Number of vertices of each ring.
+const LENGTH_RINGS: u8 = (3 << 4) | 3;
+
+// Logical and physical level techniques.
+/// No logical encoding.
+const LOGICAL_NONE: u8 = 0;
+/// Delta encoding of the X and Y components, separately.
+const COMPONENTWISE_DELTA: u8 = 2;
+/// Raw bytes.
+const PHYSICAL_NONE: u8 = 0;
+/// Variable-length integers.
+const VARINT: u8 = 2;
+
+// Geometry types.
+/// Single polygon.
+const POLYGON: u64 = 2;
+/// Multiple polygons.
+const MULTI_POLYGON: u64 = 5;
+
+/// `MapLibre` Tile (MLT) encoder.
+///
+/// The features are encoded into a columnar layer: an optional ID column, the
+/// geometry column and one column per property, typed from its values (mixed
+/// numeric values are stored as doubles, other mixes as strings). Geometries
+/// are rounded and repaired like with the MVT encoder, and stored without
+/// closing vertex, the vertex buffer being delta encoded.
+///
+/// The output is a single-layer tile: concatenating the output of several
+/// encodings (with distinct names) yields a multi-layer tile.
+///
+/// Requires the `mlt` feature.
+///
+/// See <https://github.com/maplibre/maplibre-tile-spec>
+#[derive(Debug, Default)]
+pub struct MltEncoder {
+    /// Geometry encoder (rounding and repair).
+    encoder: mvt::Encoder,
+    /// Geometry type of each feature.
+    types: Vec<u64>,
+    /// Number of polygons of each multi-polygon.
+    geometries: Vec<u64>,
+    /// Number of rings of each polygon.
+    parts: Vec<u64>,
+    /// Number of vertices of each ring.
+    rings: Vec<u64>,
+    /// Vertices, as interleaved X and Y deltas.
+    vertices: Vec<u64>,
+    /// Index of the encoded features.
+    encoded: Vec<usize>,
+}
+
+impl MltEncoder {
+    /// Initializes a new MLT encoder.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Encodes the geometry column, keeping track of the non-empty features.
+    fn geometries(&mut self, features: &[TileFeature<'_>]) {
+        self.types.clear();
+        self.geometries.clear();
+        self.parts.clear();
+        self.rings.clear();
+        self.vertices.clear();
+        self.encoded.clear();
+
+        let mut cursor = (0, 0);
+        for (index, feature) in features.iter().enumerate() {
+            let shape = self.encoder.repair(feature.geometry());
+            let count = shape.polygons().count();
+            if count == 0 {
+                continue;
+            }
+            self.encoded.push(index);
+            if count == 1 {
+                self.types.push(POLYGON);
+            } else {
+                self.types.push(MULTI_POLYGON);
+                self.geometries.push(to_u64(count));
+            }
+            for polygon in shape.polygons() {
+                let start = self.rings.len();
+                for ring in polygon {
+                    self.rings.push(to_u64(ring.len()));
+                    for &(x, y) in ring {
+                        self.vertices.extend([
+                            zigzag(i64::from(x) - i64::from(cursor.0)),
+                            zigzag(i64::from(y) - i64::from(cursor.1)),
+                        ]);
+                        cursor = (x, y);
+                    }
+                }
+                self.parts.push(to_u64(self.rings.len() - start));
+            }
+        }
+    }
+
+    /// Writes the geometry column.
+    fn geometry_column(&self, layer: &mut Vec<u8>) {
+        let has_multi = !self.geometries.is_empty();
+        write_varint(layer, 4 + u64::from(has_multi));
+        write_varints(layer, DATA, LOGICAL_NONE, &self.types);
+        if has_multi {
+            write_varints(
+                layer,
+                LENGTH_GEOMETRIES,
+                LOGICAL_NONE,
+                &self.geometries,
+            );
+        }
+        write_varints(layer, LENGTH_PARTS, LOGICAL_NONE, &self.parts);
+        write_varints(layer, LENGTH_RINGS, LOGICAL_NONE, &self.rings);
+        write_varints(layer, DATA_VERTEX, COMPONENTWISE_DELTA, &self.vertices);
+    }
+}
+
+impl TileEncoder for MltEncoder {
+    type Output = Vec<u8>;
+
+    fn encode(&mut self, name: &str, features: &[TileFeature<'_>]) -> Vec<u8> {
+        self.geometries(features);
+        // MLT has no empty layers.
+        if self.encoded.is_empty() {
+            return Vec::new();
+        }
+        let features = self
+            .encoded
+            .iter()
+            .map(|&index| features[index])
+            .collect::<Vec<_>>();
+        let columns = property_columns(&features);
+        let has_id = features.iter().any(|feature| feature.id().is_some());
+        let all_ids = features.iter().all(|feature| feature.id().is_some());
+
+        // Metadata.
+        let mut layer = Vec::new();
+        write_string(&mut layer, name);
+        write_varint(&mut layer, u64::from(TileID::extent()));
+        write_varint(
+            &mut layer,
+            to_u64(usize::from(has_id) + 1 + columns.len()),
+        );
+        if has_id {
+            write_varint(&mut layer, if all_ids { ID } else { OPTIONAL_ID });
+        }
+        write_varint(&mut layer, GEOMETRY);
+        for column in &columns {
+            let nullable = column.values.iter().any(Option::is_none);
+            write_varint(&mut layer, column.kind.code() + u64::from(nullable));
+            write_string(&mut layer, column.name);
+        }
+
+        // Columns.
+        if has_id {
+            let ids = features.iter().map(TileFeature::id);
+            if !all_ids {
+                write_bitmap(
+                    &mut layer,
+                    PRESENT,
+                    ids.clone().map(|id| id.is_some()),
+                );
+            }
+            let ids = ids.flatten().collect::<Vec<_>>();
+            write_varints(&mut layer, DATA, LOGICAL_NONE, &ids);
+        }
+        self.geometry_column(&mut layer);
+        for column in &columns {
+            write_column(&mut layer, column);
+        }
+
+        let mut tile = Vec::with_capacity(layer.len() + 12);
+        write_varint(&mut tile, to_u64(varint_size(LAYER_TAG) + layer.len()));
+        write_varint(&mut tile, LAYER_TAG);
+        tile.extend_from_slice(&layer);
+        tile
+    }
+}
+
+/// Writes a property column.
+fn write_column(layer: &mut Vec<u8>, column: &Column<'_>) {
+    let nullable = column.values.iter().any(Option::is_none);
+    let values = column.values.iter().flatten().copied();
+    if column.kind == Kind::String {
+        write_varint(layer, 2 + u64::from(nullable));
+    }
+    if nullable {
+        write_bitmap(layer, PRESENT, column.values.iter().map(Option::is_some));
+    }
+
+    match column.kind {
+        Kind::Boolean => write_bitmap(layer, DATA, values.map(as_bool)),
+        Kind::Int => {
+            let values = values.map(as_int).collect::<Vec<_>>();
+            write_varints(layer, DATA, LOGICAL_NONE, &values);
+        }
+        Kind::UInt => {
+            let values = values.map(as_uint).collect::<Vec<_>>();
+            write_varints(layer, DATA, LOGICAL_NONE, &values);
+        }
+        Kind::Double => {
+            let values = values
+                .flat_map(|value| as_double(value).to_le_bytes())
+                .collect::<Vec<_>>();
+            write_stream(
+                layer,
+                DATA,
+                LOGICAL_NONE,
+                PHYSICAL_NONE,
+                values.len() / 8,
+                &values,
+            );
+        }
+        Kind::String => {
+            let values = values.map(as_string).collect::<Vec<_>>();
+            let lengths = values
+                .iter()
+                .map(|value| to_u64(value.len()))
+                .collect::<Vec<_>>();
+            write_varints(layer, LENGTH_VAR_BINARY, LOGICAL_NONE, &lengths);
+            let bytes = values.concat();
+            write_stream(
+                layer,
+                DATA,
+                LOGICAL_NONE,
+                PHYSICAL_NONE,
+                bytes.len(),
+                bytes.as_bytes(),
+            );
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+/// Type of a property column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Boolean,
+    Int,
+    UInt,
+    Double,
+    String,
+}
+
+impl Kind {
+    /// Returns the type of a value.
+    const fn of(value: PropertyValue<'_>) -> Self {
+        match value {
+            PropertyValue::String(_) => Self::String,
+            PropertyValue::Double(_) => Self::Double,
+            PropertyValue::Int(_) => Self::Int,
+            PropertyValue::UInt(_) => Self::UInt,
+            PropertyValue::Bool(_) => Self::Boolean,
+        }
+    }
+
+    /// Returns the type able to store values of both types.
+    fn merge(self, other: Self) -> Self {
+        let is_numeric =
+            |kind| matches!(kind, Self::Int | Self::UInt | Self::Double);
+        if self == other {
+            self
+        } else if is_numeric(self) && is_numeric(other) {
+            Self::Double
+        } else {
+            Self::String
+        }
+    }
+
+    /// Returns the code of the non-nullable column type.
+    const fn code(self) -> u64 {
+        match self {
+            Self::Boolean => BOOLEAN,
+            Self::Int => INT_64,
+            Self::UInt => UINT_64,
+            Self::Double => DOUBLE,
+            Self::String => STRING,
+        }
+    }
+}
+
+/// A property column.
+#[derive(Debug)]
+struct Column<'a> {
+    /// Property name.
+    name: &'a str,
+    /// Column type.
+    kind: Kind,
+    /// Value of each feature, if any.
+    values: Vec<Option<PropertyValue<'a>>>,
+}
+
+/// Splits the properties of the features into columns, in order of
+/// appearance.
+fn property_columns<'a>(features: &[TileFeature<'a>]) -> Vec<Column<'a>> {
+    let mut columns = Vec::<Column<'a>>::new();
+    for (index, feature) in features.iter().enumerate() {
+        for &(name, value) in feature.properties() {
+            let position = columns
+                .iter()
+                .position(|column| column.name == name)
+                .unwrap_or_else(|| {
+                    columns.push(Column {
+                        name,
+                        kind: Kind::of(value),
+                        values: vec![None; features.len()],
+                    });
+                    columns.len() - 1
+                });
+            let column = &mut columns[position];
+            // Only the first occurrence of a property is kept.
+            if column.values[index].is_none() {
+                column.kind = column.kind.merge(Kind::of(value));
+                column.values[index] = Some(value);
+            }
+        }
+    }
+    columns
+}
+
+/// Converts a value of a boolean column.
+fn as_bool(value: PropertyValue<'_>) -> bool {
+    match value {
+        PropertyValue::Bool(value) => value,
+        _ => unreachable!("boolean column"),
+    }
+}
+
+/// Converts a value of a signed integer column.
+fn as_int(value: PropertyValue<'_>) -> u64 {
+    match value {
+        PropertyValue::Int(value) => zigzag(value),
+        _ => unreachable!("integer column"),
+    }
+}
+
+/// Converts a value of an unsigned integer column.
+fn as_uint(value: PropertyValue<'_>) -> u64 {
+    match value {
+        PropertyValue::UInt(value) => value,
+        _ => unreachable!("unsigned integer column"),
+    }
+}
+
+/// Converts a value of a double column.
+#[expect(clippy::cast_precision_loss, reason = "mixed numeric column")]
+fn as_double(value: PropertyValue<'_>) -> f64 {
+    match value {
+        PropertyValue::Double(value) => value,
+        PropertyValue::Int(value) => value as f64,
+        PropertyValue::UInt(value) => value as f64,
+        _ => unreachable!("numeric column"),
+    }
+}
+
+/// Converts a value of a string column.
+fn as_string(value: PropertyValue<'_>) -> String {
+    match value {
+        PropertyValue::String(value) => value.to_owned(),
+        PropertyValue::Double(value) => value.to_string(),
+        PropertyValue::Int(value) => value.to_string(),
+        PropertyValue::UInt(value) => value.to_string(),
+        PropertyValue::Bool(value) => value.to_string(),
+    }
+}
+
+// -----------------------------------------------------------------------------
+
+/// Writes a stream, with its metadata.
+fn write_stream(
+    layer: &mut Vec<u8>,
+    kind: u8,
+    logical: u8,
+    physical: u8,
+    count: usize,
+    data: &[u8],
+) {
+    layer.push(kind);
+    layer.push((logical << 5) | physical);
+    write_varint(layer, to_u64(count));
+    write_varint(layer, to_u64(data.len()));
+    layer.extend_from_slice(data);
+}
+
+/// Writes a stream of variable-length integers.
+fn write_varints(layer: &mut Vec<u8>, kind: u8, logical: u8, values: &[u64]) {
+    let size = values.iter().map(|&value| varint_size(value)).sum();
+    layer.push(kind);
+    layer.push((logical << 5) | VARINT);
+    write_varint(layer, to_u64(values.len()));
+    write_varint(layer, to_u64(size));
+    for &value in values {
+        write_varint(layer, value);
+    }
+}
+
+/// Writes a bitmap (least significant bit first), using the byte-oriented
+/// RLE encoding of booleans (as literal runs only).
+fn write_bitmap(
+    layer: &mut Vec<u8>,
+    kind: u8,
+    bits: impl Iterator<Item = bool>,
+) {
+    let mut bytes = Vec::new();
+    let mut count = 0;
+    for (index, bit) in bits.enumerate() {
+        if index % 8 == 0 {
+            bytes.push(0);
+        }
+        if let Some(byte) = bytes.last_mut() {
+            *byte |= u8::from(bit) << (index % 8);
+        }
+        count += 1;
+    }
+    let mut data = Vec::with_capacity(bytes.len() + bytes.len().div_ceil(128));
+    for chunk in bytes.chunks(128) {
+        // Negative headers introduce literal runs.
+        let len = i8::try_from(chunk.len()).map_or(i8::MIN, |len| -len);
+        data.extend(len.to_le_bytes());
+        data.extend_from_slice(chunk);
+    }
+    write_stream(layer, kind, LOGICAL_NONE, PHYSICAL_NONE, count, &data);
+}
+
+/// Writes a length-prefixed UTF-8 string.
+fn write_string(buffer: &mut Vec<u8>, value: &str) {
+    write_varint(buffer, to_u64(value.len()));
+    buffer.extend_from_slice(value.as_bytes());
+}
+
+/// Writes a variable-length integer.
+#[expect(clippy::cast_possible_truncation, reason = "7-bit groups")]
+fn write_varint(buffer: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buffer.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    buffer.push(value as u8);
+}
+
+/// Returns the size of a variable-length integer.
+const fn varint_size(value: u64) -> usize {
+    (64 - (value | 1).leading_zeros() as usize).div_ceil(7)
+}
+
+/// Encodes a signed integer using zigzag encoding.
+#[expect(clippy::cast_sign_loss, reason = "zigzag encoding")]
+const fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+/// Converts a size into a 64-bit integer.
+fn to_u64(value: usize) -> u64 {
+    u64::try_from(value).expect("size fits in 64 bits")
+}
+
+#[cfg(test)]
+#[path = "./mlt_tests.rs"]
+mod tests;
@@ -0,0 +1,554 @@
+#![expect(clippy::panic, reason = "unit tests, this is fine")]
+
+use super::*;
+use crate::{MvtEncoder, render_with, tiles_for_cell};
+use geo::{LineString, MultiPolygon, Polygon, polygon};
+use geozero::mvt::tile::Layer;
+use h3o::CellIndex;
+use std::collections::BTreeMap;
+
+/// Rings of each polygon of a feature.
+type Geometry = Vec<Vec<Vec<(i32, i32)>>>;
+
+/// A decoded property value.
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Bool(bool),
+    Int(i64),
+    UInt(u64),
+    Double(f64),
+    String(String),
+}
+
+/// A decoded layer.
+#[derive(Debug, Default)]
+struct Decoded {
+    name: String,
+    extent: u64,
+    ids: Vec<Option<u64>>,
+    geometries: Vec<Geometry>,
+    properties: Vec<(String, Vec<Option<Value>>)>,
+}
+
+/// Minimal MLT reader, supporting what the encoder outputs.
+struct Reader<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn byte(&mut self) -> u8 {
+        let byte = self.data[self.offset];
+        self.offset += 1;
+        byte
+    }
+
+    fn bytes(&mut self, len: usize) -> &'a [u8] {
+        let bytes = &self.data[self.offset..self.offset + len];
+        self.offset += len;
+        bytes
+    }
+
+    fn varint(&mut self) -> u64 {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte();
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+        value
+    }
+
+    fn usize(&mut self) -> usize {
+        usize::try_from(self.varint()).expect("usize")
+    }
+
+    fn string(&mut self) -> String {
+        let len = self.usize();
+        String::from_utf8(self.bytes(len).to_vec()).expect("UTF-8")
+    }
+
+    /// Reads a stream, checking its type and returning its value count and
+    /// data.
+    fn stream(&mut self, kind: u8, encoding: u8) -> (usize, &'a [u8]) {
+        assert_eq!(self.byte(), kind, "stream type at {}", self.offset);
+        assert_eq!(self.byte(), encoding, "encoding at {}", self.offset);
+        let count = self.usize();
+        let len = self.usize();
+        (count, self.bytes(len))
+    }
+
+    fn varints(&mut self, kind: u8, logical: u8) -> Vec<u64> {
+        let (count, data) = self.stream(kind, (logical << 5) | VARINT);
+        let mut reader = Reader { data, offset: 0 };
+        let values = (0..count).map(|_| reader.varint()).collect();
+        assert_eq!(reader.offset, data.len());
+        values
+    }
+
+    fn bitmap(&mut self, kind: u8) -> Vec<bool> {
+        let (count, data) = self.stream(kind, 0);
+        let mut reader = Reader { data, offset: 0 };
+        let mut bytes = Vec::new();
+        while reader.offset < data.len() {
+            let header = i8::from_le_bytes([reader.byte()]);
+            assert!(header < 0, "literal run");
+            bytes.extend_from_slice(
+                reader.bytes(usize::from(header.unsigned_abs())),
+            );
+        }
+        (0..count)
+            .map(|index| bytes[index / 8] & (1 << (index % 8)) != 0)
+            .collect()
+    }
+
+    /// Reads a nullable column, given its non-null values.
+    fn nullable<T>(
+        &mut self,
+        nullable: bool,
+        read: impl FnOnce(&mut Self) -> Vec<T>,
+    ) -> Vec<Option<T>> {
+        if !nullable {
+            return read(self).into_iter().map(Some).collect();
+        }
+        let present = self.bitmap(PRESENT);
+        let mut values = read(self).into_iter();
+        let result = present
+            .into_iter()
+            .map(|present| present.then(|| values.next().expect("value")))
+            .collect();
+        assert!(values.next().is_none());
+        result
+    }
+}
+
+fn unzigzag(value: u64) -> i64 {
+    i64::try_from(value >> 1).expect("i64")
+        ^ -i64::try_from(value & 1).expect("i64")
+}
+
+fn decode(tile: &[u8]) -> Decoded {
+    let mut reader = Reader {
+        data: tile,
+        offset: 0,
+    };
+    let len = reader.usize();
+    assert_eq!(len, tile.len() - reader.offset, "layer size");
+    assert_eq!(reader.varint(), LAYER_TAG);
+
+    let mut layer = Decoded {
+        name: reader.string(),
+        extent: reader.varint(),
+        ..Decoded::default()
+    };
+    let columns = (0..reader.varint())
+        .map(|_| {
+            let kind = reader.varint();
+            let name = (kind >= BOOLEAN).then(|| reader.string());
+            (kind, name)
+        })
+        .collect::<Vec<_>>();
+
+    for (kind, name) in columns {
+        match kind {
+            ID | OPTIONAL_ID => {
+                layer.ids = reader.nullable(kind == OPTIONAL_ID, |reader| {
+                    reader.varints(DATA, LOGICAL_NONE)
+                });
+            }
+            GEOMETRY => layer.geometries = geometries(&mut reader),
+            _ => {
+                let values = property(&mut reader, kind);
+                layer.properties.push((name.expect("name"), values));
+            }
+        }
+    }
+    assert_eq!(reader.offset, tile.len(), "trailing bytes");
+    assert_reference(tile, &layer);
+
+    layer
+}
+
+/// Checks the tile against the reference decoder, from the specification
+/// repository.
+fn assert_reference(tile: &[u8], layer: &Decoded) {
+    let layers = mlt_core::Parser::default()
+        .parse_layers(tile)
+        .expect("reference parsing");
+    let layers = mlt_core::Decoder::default()
+        .decode_all(layers)
+        .expect("reference decoding");
+    let features = mlt_core::geojson::FeatureCollection::from_layers(layers)
+        .expect("reference features")
+        .features;
+
+    assert_eq!(features.len(), layer.geometries.len(), "feature count");
+    for (index, feature) in features.into_iter().enumerate() {
+        assert_eq!(feature.id, layer.ids.get(index).copied().flatten());
+        assert_eq!(feature.geometry, to_geometry(&layer.geometries[index]));
+
+        let mut expected = layer
+            .properties
+            .iter()
+            .filter_map(|(name, values)| {
+                let value = match values[index].clone()? {
+                    Value::Bool(value) => serde_json::Value::from(value),
+                    Value::Int(value) => serde_json::Value::from(value),
+                    Value::UInt(value) => serde_json::Value::from(value),
+                    Value::Double(value) => serde_json::Value::from(value),
+                    Value::String(value) => serde_json::Value::from(value),
+                };
+                Some((name.clone(), value))
+            })
+            .collect::<BTreeMap<_, _>>();
+        expected.insert("_layer".to_owned(), layer.name.clone().into());
+        expected.insert("_extent".to_owned(), layer.extent.into());
+        assert_eq!(feature.properties, expected, "feature {index}");
+    }
+}
+
+/// Converts decoded rings into a closed polygon, or multi-polygon.
+fn to_geometry(geometry: &Geometry) -> geo::Geometry<i32> {
+    let mut polygons = geometry
+        .iter()
+        .map(|rings| {
+            let mut rings = rings.iter().map(|ring| {
+                let mut ring = LineString::from(ring.clone());
+                ring.close();
+                ring
+            });
+            let exterior = rings.next().expect("exterior");
+            Polygon::new(exterior, rings.collect())
+        })
+        .collect::<Vec<_>>();
+    if polygons.len() == 1 {
+        geo::Geometry::Polygon(polygons.remove(0))
+    } else {
+        geo::Geometry::MultiPolygon(MultiPolygon::new(polygons))
+    }
+}
+
+fn geometries(reader: &mut Reader<'_>) -> Vec<Geometry> {
+    let streams = reader.varint();
+    let types = reader.varints(DATA, LOGICAL_NONE);
+    let mut geometries = if streams == 5 {
+        reader.varints(LENGTH_GEOMETRIES, LOGICAL_NONE).into_iter()
+    } else {
+        assert_eq!(streams, 4);
+        Vec::new().into_iter()
+    };
+    let mut parts = reader.varints(LENGTH_PARTS, LOGICAL_NONE).into_iter();
+    let mut rings = reader.varints(LENGTH_RINGS, LOGICAL_NONE).into_iter();
+    let mut vertices = reader
+        .varints(DATA_VERTEX, COMPONENTWISE_DELTA)
+        .into_iter()
+        .map(unzigzag);
+
+    let mut cursor = (0, 0);
+    types
+        .into_iter()
+        .map(|kind| {
+            let count = match kind {
+                POLYGON => 1,
+                MULTI_POLYGON => geometries.next().expect("geometry length"),
+                _ => panic!("unexpected geometry type {kind}"),
+            };
+            (0..count)
+                .map(|_| {
+                    (0..parts.next().expect("part length"))
+                        .map(|_| {
+                            (0..rings.next().expect("ring length"))
+                                .map(|_| {
+                                    cursor.0 += vertices.next().expect("x");
+                                    cursor.1 += vertices.next().expect("y");
+                                    (
+                                        i32::try_from(cursor.0).expect("x"),
+                                        i32::try_from(cursor.1).expect("y"),
+                                    )
+                                })
+                                .collect()
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn property(reader: &mut Reader<'_>, kind: u64) -> Vec<Option<Value>> {
+    let nullable = kind % 2 == 1;
+    match kind - u64::from(nullable) {
+        BOOLEAN => reader.nullable(nullable, |reader| {
+            reader.bitmap(DATA).into_iter().map(Value::Bool).collect()
+        }),
+        INT_64 => reader.nullable(nullable, |reader| {
+            let values = reader.varints(DATA, LOGICAL_NONE);
+            values
+                .into_iter()
+                .map(|value| Value::Int(unzigzag(value)))
+                .collect()
+        }),
+        UINT_64 => reader.nullable(nullable, |reader| {
+            let values = reader.varints(DATA, LOGICAL_NONE);
+            values.into_iter().map(Value::UInt).collect()
+        }),
+        DOUBLE => reader.nullable(nullable, |reader| {
+            let (count, data) = reader.stream(DATA, 0);
+            assert_eq!(data.len(), count * 8);
+            data.chunks(8)
+                .map(|bytes| {
+                    Value::Double(f64::from_le_bytes(
+                        bytes.try_into().expect("8 bytes"),
+                    ))
+                })
+                .collect()
+        }),
+        STRING => {
+            assert_eq!(reader.varint(), 2 + u64::from(nullable));
+            reader.nullable(nullable, |reader| {
+                let lengths = reader.varints(LENGTH_VAR_BINARY, LOGICAL_NONE);
+                let (len, mut data) = reader.stream(DATA, 0);
+                assert_eq!(len, data.len());
+                lengths
+                    .into_iter()
+                    .map(|len| {
+                        let (value, rest) =
+                            data.split_at(usize::try_from(len).expect("len"));
+                        data = rest;
+                        Value::String(
+                            String::from_utf8(value.to_vec()).expect("UTF-8"),
+                        )
+                    })
+                    .collect()
+            })
+        }
+        _ => panic!("unexpected column type {kind}"),
+    }
+}
+
+/// Decodes the geometry of the features of an MVT layer.
+fn mvt_geometries(layer: &Layer) -> Vec<Geometry> {
+    let mut cursor = (0, 0);
+    layer
+        .features
+        .iter()
+        .map(|feature| {
+            let mut polygons: Geometry = Vec::new();
+            let mut ring = Vec::new();
+            let mut geometry = feature.geometry.iter().copied();
+            while let Some(command) = geometry.next() {
+                if command & 7 == 7 {
+                    let ring = std::mem::take(&mut ring);
+                    // Exterior rings start a new polygon.
+                    if signed_area(&ring) > 0 {
+                        polygons.push(vec![ring]);
+                    } else {
+                        polygons.last_mut().expect("polygon").push(ring);
+                    }
+                    continue;
+                }
+                for _ in 0..command >> 3 {
+                    let x = unzigzag(geometry.next().expect("x").into());
+                    let y = unzigzag(geometry.next().expect("y").into());
+                    cursor.0 += i32::try_from(x).expect("x");
+                    cursor.1 += i32::try_from(y).expect("y");
+                    ring.push(cursor);
+                }
+            }
+            polygons
+        })
+        .collect()
+}
+
+fn signed_area(ring: &[(i32, i32)]) -> i64 {
+    ring.iter()
+        .zip(ring.iter().cycle().skip(1))
+        .map(|(&(x1, y1), &(x2, y2))| {
+            i64::from(x1) * i64::from(y2) - i64::from(x2) * i64::from(y1)
+        })
+        .sum()
+}
+
+fn square(x: f64, y: f64) -> MultiPolygon {
+    MultiPolygon::new(vec![polygon![
+        (x: x, y: y),
+        (x: x + 10., y: y),
+        (x: x + 10., y: y + 10.),
+        (x: x, y: y + 10.),
+    ]])
+}
+
+#[test]
+fn same_geometry_as_mvt() {
+    let center = CellIndex::try_from(0x891fb46622fffff).expect("cell");
+    let cells = center.grid_disk::<Vec<_>>(8);
+
+    for scratch in [false, true] {
+        for tile_id in tiles_for_cell(center, 5..=16)
+            .into_iter()
+            .chain([TileID::new(0, 0, 0).expect("tile")])
+        {
+            let expected = render_with(
+                tile_id,
+                cells.iter().copied(),
+                "h3".to_owned(),
+                scratch,
+                &mut MvtEncoder::new(),
+            )
+            .expect("render MVT");
+            let tile = render_with(
+                tile_id,
+                cells.iter().copied(),
+                "h3".to_owned(),
+                scratch,
+                &mut MltEncoder::new(),
+            )
+            .expect("render MLT");
+
+            if expected.features.is_empty() {
+                assert!(tile.is_empty(), "{tile_id:?}/{scratch}");
+                continue;
+            }
+            let layer = decode(&tile);
+
+            assert_eq!(layer.name, "h3");
+            assert_eq!(layer.extent, 4096);
+            assert_eq!(
+                layer.geometries,
+                mvt_geometries(&expected),
+                "{tile_id:?}/{scratch}"
+            );
+        }
+    }
+}
+
+#[test]
+fn multipolygon() {
+    let mut geometry = square(0., 0.);
+    geometry.0.extend(square(20., 20.));
+    let single = square(40., 40.);
+    let features = [TileFeature::new(&geometry), TileFeature::new(&single)];
+
+    let tile = MltEncoder::new().encode("test", &features);
+
+    let layer = decode(&tile);
+    assert_eq!(layer.ids, []);
+    assert_eq!(layer.geometries.len(), 2);
+    assert_eq!(layer.geometries[0].len(), 2);
+    assert_eq!(
+        layer.geometries[1],
+        [[[(40, 40), (50, 40), (50, 50), (40, 50)]]]
+    );
+}
+
+#[test]
+fn ids_and_properties() {
+    let (first, second, third, empty) = (
+        square(0., 0.),
+        square(20., 20.),
+        square(40., 40.),
+        MultiPolygon::new(vec![]),
+    );
+    let properties = [
+        ("name", PropertyValue::String("first")),
+        ("count", PropertyValue::UInt(3)),
+        ("valid", PropertyValue::Bool(true)),
+        ("delta", PropertyValue::Int(-5)),
+        ("mixed", PropertyValue::Int(-1)),
+        ("other", PropertyValue::Bool(false)),
+    ];
+    let others = [
+        ("count", PropertyValue::UInt(u64::MAX)),
+        ("ratio", PropertyValue::Double(0.5)),
+        ("valid", PropertyValue::Bool(false)),
+        ("delta", PropertyValue::Int(i64::MIN)),
+        ("mixed", PropertyValue::Double(2.5)),
+        ("other", PropertyValue::String("x")),
+    ];
+    let features = [
+        TileFeature::new(&first)
+            .with_id(1)
+            .with_properties(&properties),
+        TileFeature::new(&empty).with_id(2),
+        TileFeature::new(&second)
+            .with_id(3)
+            .with_properties(&others),
+        TileFeature::new(&third),
+    ];
+    let mut encoder = MltEncoder::new();
+
+    let tile = encoder.encode("test", &features);
+
+    let layer = decode(&tile);
+    // The empty feature is skipped.
+    assert_eq!(layer.geometries.len(), 3);
+    assert_eq!(layer.ids, [Some(1), Some(3), None]);
+    let names = layer
+        .properties
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        ["name", "count", "valid", "delta", "mixed", "other", "ratio"]
+    );
+    let values = layer
+        .properties
+        .into_iter()
+        .map(|(_, values)| values)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        values,
+        [
+            vec![Some(Value::String("first".to_owned())), None, None],
+            vec![Some(Value::UInt(3)), Some(Value::UInt(u64::MAX)), None],
+            vec![Some(Value::Bool(true)), Some(Value::Bool(false)), None],
+            vec![Some(Value::Int(-5)), Some(Value::Int(i64::MIN)), None],
+            vec![Some(Value::Double(-1.)), Some(Value::Double(2.5)), None],
+            vec![
+                Some(Value::String("false".to_owned())),
+                Some(Value::String("x".to_owned())),
+                None
+            ],
+            vec![None, Some(Value::Double(0.5)), None],
+        ]
+    );
+    // The encoder state doesn't leak between layers.
+    assert_eq!(encoder.encode("test", &features), tile);
+}
+
+#[test]
+fn non_nullable_columns() {
+    let geometry = square(0., 0.);
+    let properties = [("name", PropertyValue::String("a"))];
+    let features = [TileFeature::new(&geometry)
+        .with_id(7)
+        .with_properties(&properties)];
+
+    let tile = MltEncoder::new().encode("test", &features);
+
+    let layer = decode(&tile);
+    assert_eq!(layer.ids, [Some(7)]);
+    assert_eq!(
+        layer.properties,
+        [("name".to_owned(), vec![Some(Value::String("a".to_owned()))])]
+    );
+}
+
+#[test]
+fn empty() {
+    let geometry = MultiPolygon::new(vec![]);
+    let features = [TileFeature::new(&geometry).with_id(1)];
+
+    for features in [&[][..], &features] {
+        let tile = MltEncoder::new().encode("test", features);
+
+        assert!(tile.is_empty());
+        let layers = mlt_core::Parser::default()
+            .parse_layers(&tile)
+            .expect("reference parsing");
+        assert!(layers.is_empty());
+    }
+}
@@ -0,0 +1,340 @@
+use ahash::HashMap;
+use geo::{Coord, LineString, Polygon};
+use geozero::mvt::tile::{Feature, GeomType};
+
+/// Command to start a new ring.
+const MOVE_TO: u32 = 1;
+/// Command to extend the current ring.
+const LINE_TO: u32 = 2;
+/// Command to close the current ring.
+const CLOSE_PATH: u32 = 7;
+
+/// Encodes polygons into a new MVT feature.
+///
+/// Returns `None` if there is nothing to encode.
+#[cfg(test)]
+pub fn encode<'a>(
+    polygons: impl IntoIterator<Item = &'a Polygon>,
+) -> Option<Feature> {
+    let mut feature = Feature::default();
+    Encoder::default()
+        .encode(polygons, &mut feature)
+        .then_some(feature)
+}
+
+/// Incremental MVT geometry encoder.
+#[derive(Debug, Default)]
+pub struct Encoder {
+    /// Repaired polygons.
+    shape: Shape,
+    /// Scratch buffer for the snapped vertices of the current ring.
+    vertices: Vec<(i32, i32)>,
+    /// Scratch buffer for the path being split into simple loops.
+    path: Vec<(i32, i32)>,
+    /// Position of the vertices in the path.
+    positions: HashMap<(i32, i32), usize>,
+    /// Vertices of the simple loops of the current polygon, end to end.
+    loops: Vec<(i32, i32)>,
+    /// Exterior loops of the current polygon, as ranges in `loops`.
+    exteriors: Vec<(usize, usize)>,
+    /// Hole loops of the current polygon, as ranges in `loops`.
+    holes: Vec<(usize, usize)>,
+}
+
+impl Encoder {
+    /// Encodes polygons, in tile coordinates, into an MVT feature, reusing
+    /// the allocation of its geometry.
+    ///
+    /// The geometry command stream is written directly, as described in
+    /// <https://github.com/mapbox/vector-tile-spec/tree/master/2.1#43-geometry-encoding>
+    ///
+    /// Coordinates are rounded to the nearest integer, then the rings are
+    /// repaired: repeated vertices and spikes are removed, rings touching
+    /// themselves are split into simple loops, and degenerate loops (less than
+    /// 3 vertices or no area) are skipped, along with the holes of a skipped
+    /// exterior ring. Rings are reoriented as required by the specification:
+    /// exterior rings have a positive area and holes a negative one (in tile
+    /// coordinates, where Y points down, i.e. clockwise on screen).
+    ///
+    /// Returns false if there is nothing to encode.
+    pub fn encode<'a>(
+        &mut self,
+        polygons: impl IntoIterator<Item = &'a Polygon>,
+        feature: &mut Feature,
+    ) -> bool {
+        self.repair(polygons);
+        let mut geometry = std::mem::take(&mut feature.geometry);
+        geometry.clear();
+        let mut cursor = (0, 0);
+        for polygon in self.shape.polygons() {
+            for ring in polygon {
+                encode_ring(ring, &mut geometry, &mut cursor);
+            }
+        }
+
+        feature.r#type = Some(GeomType::Polygon.into());
+        feature.geometry = geometry;
+        !feature.geometry.is_empty()
+    }
+
+    /// Snaps polygons, in tile coordinates, on the integer grid and repairs
+    /// them as described in [`Encoder::encode`].
+    pub fn repair<'a>(
+        &mut self,
+        polygons: impl IntoIterator<Item = &'a Polygon>,
+    ) -> &Shape {
+        self.shape.vertices.clear();
+        self.shape.rings.clear();
+        self.shape.polygons.clear();
+        for polygon in polygons {
+            self.loops.clear();
+            self.exteriors.clear();
+            self.holes.clear();
+            self.split(polygon.exterior(), true);
+            if self.exteriors.is_empty() {
+                continue;
+            }
+            for interior in polygon.interiors() {
+                self.split(interior, false);
+            }
+            self.polygon();
+        }
+
+        &self.shape
+    }
+
+    /// Snaps a ring, then splits it into simple loops.
+    ///
+    /// Loops are classified by their orientation relative to the ring: loops
+    /// turning the other way are holes of an exterior ring, or dropped for a
+    /// hole.
+    fn split(&mut self, ring: &LineString, exterior: bool) {
+        snap(ring, &mut self.vertices);
+        let sign = double_signed_area(&self.vertices).signum();
+        if self.vertices.len() < 3 || sign == 0 {
+            return;
+        }
+
+        self.path.clear();
+        self.positions.clear();
+        let first = self.vertices[0];
+        for index in 0..=self.vertices.len() {
+            let vertex = self.vertices.get(index).copied().unwrap_or(first);
+            if let Some(&position) = self.positions.get(&vertex) {
+                // Close the loop, and resume the path from the pinch.
+                for removed in &self.path[position + 1..] {
+                    self.positions.remove(removed);
+                }
+                let start = self.loops.len();
+                self.loops.extend_from_slice(&self.path[position..]);
+                self.path.truncate(position);
+                self.classify(start, sign, exterior);
+            } else {
+                self.positions.insert(vertex, self.path.len());
+            }
+            self.path.push(vertex);
+        }
+    }
+
+    /// Classifies the loop starting at `start`, at the end of `loops`.
+    fn classify(&mut self, start: usize, sign: i64, exterior: bool) {
+        let end = self.loops.len();
+        let area = double_signed_area(&self.loops[start..]);
+        let is_exterior = (area.signum() == sign) == exterior;
+        if end - start < 3 || area == 0 || (is_exterior && !exterior) {
+            self.loops.truncate(start);
+            return;
+        }
+        if (area > 0) != is_exterior {
+            self.loops[start..].reverse();
+        }
+        if is_exterior {
+            self.exteriors.push((start, end));
+        } else {
+            self.holes.push((start, end));
+        }
+    }
+
+    /// Adds the loops of the current polygon to the shape, each exterior
+    /// followed by its holes.
+    fn polygon(&mut self) {
+        for index in 0..self.exteriors.len() {
+            let start = self.shape.rings.len();
+            self.ring(self.exteriors[index]);
+            for hole in 0..self.holes.len() {
+                if self.owner(self.holes[hole]) == index {
+                    self.ring(self.holes[hole]);
+                }
+            }
+            self.shape.polygons.push((start, self.shape.rings.len()));
+        }
+    }
+
+    /// Returns the index of the exterior containing the given hole.
+    fn owner(&self, (start, end): (usize, usize)) -> usize {
+        if self.exteriors.len() == 1 {
+            return 0;
+        }
+        let loop_ = &self.loops[start..end];
+        // Midpoint of an edge, which cannot be a vertex of an exterior.
+        let point = (
+            f64::midpoint(f64::from(loop_[0].0), f64::from(loop_[1].0)),
+            f64::midpoint(f64::from(loop_[0].1), f64::from(loop_[1].1)),
+        );
+        self.exteriors
+            .iter()
+            .position(|&(start, end)| contains(&self.loops[start..end], point))
+            .unwrap_or(0)
+    }
+
+    /// Adds a loop to the shape.
+    fn ring(&mut self, (start, end): (usize, usize)) {
+        let offset = self.shape.vertices.len();
+        self.shape
+            .vertices
+            .extend_from_slice(&self.loops[start..end]);
+        self.shape.rings.push((offset, self.shape.vertices.len()));
+    }
+}
+
+/// Polygons snapped on the integer grid and repaired, ready to be encoded.
+#[derive(Debug, Default)]
+pub struct Shape {
+    /// Vertices of the rings, end to end (without closing vertex).
+    vertices: Vec<(i32, i32)>,
+    /// Rings, as ranges in `vertices`.
+    rings: Vec<(usize, usize)>,
+    /// Polygons (exterior ring, then holes), as ranges in `rings`.
+    polygons: Vec<(usize, usize)>,
+}
+
+impl Shape {
+    /// Returns the rings of every polygon, without closing vertex.
+    pub fn polygons(
+        &self,
+    ) -> impl Iterator<Item = impl Iterator<Item = &[(i32, i32)]>> {
+        self.polygons.iter().map(|&(start, end)| {
+            self.rings[start..end]
+                .iter()
+                .map(|&(start, end)| &self.vertices[start..end])
+        })
+    }
+}
+
+/// Encodes a ring as MVT commands.
+fn encode_ring(
+    ring: &[(i32, i32)],
+    geometry: &mut Vec<u32>,
+    cursor: &mut (i32, i32),
+) {
+    let mut move_cursor = |geometry: &mut Vec<u32>, (x, y): (i32, i32)| {
+        geometry.push(zigzag(x - cursor.0));
+        geometry.push(zigzag(y - cursor.1));
+        *cursor = (x, y);
+    };
+    let count = u32::try_from(ring.len() - 1)
+        .expect("ring size fits in a command count");
+    geometry.reserve(ring.len() * 2 + 3);
+    geometry.push(command(MOVE_TO, 1));
+    move_cursor(geometry, ring[0]);
+    geometry.push(command(LINE_TO, count));
+    for &vertex in &ring[1..] {
+        move_cursor(geometry, vertex);
+    }
+    geometry.push(command(CLOSE_PATH, 1));
+}
+
+/// Snaps the ring on the integer grid, without the closing vertex.
+///
+/// Repeated vertices and spikes (edges folding back onto the previous one)
+/// are removed.
+fn snap(ring: &LineString, vertices: &mut Vec<(i32, i32)>) {
+    vertices.clear();
+    for &coord in ring.coords() {
+        let vertex = to_integer(coord);
+        while let [.., before, last] = vertices[..]
+            && is_spike(before, last, vertex)
+        {
+            vertices.pop();
+        }
+        if vertices.last() != Some(&vertex) {
+            vertices.push(vertex);
+        }
+    }
+
+    // Around the closing vertex.
+    loop {
+        match vertices[..] {
+            [first, .., last] if first == last => {
+                vertices.pop();
+            }
+            [first, .., before, last] if is_spike(before, last, first) => {
+                vertices.pop();
+            }
+            [first, second, .., last] if is_spike(last, first, second) => {
+                vertices.remove(0);
+            }
+            _ => break,
+        }
+    }
+}
+
+/// Tests if the path `a`, `b`, `c` folds back onto itself at `b`.
+fn is_spike(a: (i32, i32), b: (i32, i32), c: (i32, i32)) -> bool {
+    let (ux, uy) = (i64::from(b.0 - a.0), i64::from(b.1 - a.1));
+    let (vx, vy) = (i64::from(c.0 - b.0), i64::from(c.1 - b.1));
+    ux * vy == uy * vx && ux * vx + uy * vy < 0
+}
+
+/// Tests if the point is inside the ring, using the crossing number.
+fn contains(ring: &[(i32, i32)], (x, y): (f64, f64)) -> bool {
+    let next = ring.iter().cycle().skip(1);
+    ring.iter()
+        .zip(next)
+        .filter(|&(&(x0, y0), &(x1, y1))| {
+            let (x0, y0, x1, y1) =
+                (f64::from(x0), f64::from(y0), f64::from(x1), f64::from(y1));
+            (y0 > y) != (y1 > y)
+                && x < (x1 - x0).mul_add((y - y0) / (y1 - y0), x0)
+        })
+        .count()
+        % 2
+        == 1
+}
+
+/// Converts a tile coordinate into integers, rounding to the nearest.
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "clipped coordinates fit in the tile buffer"
+)]
+const fn to_integer(coord: Coord) -> (i32, i32) {
+    (coord.x.round() as i32, coord.y.round() as i32)
+}
+
+/// Computes twice the signed area of a ring (positive when the ring is
+/// clockwise in tile coordinates, i.e. counter-clockwise in a Y-up space).
+fn double_signed_area(vertices: &[(i32, i32)]) -> i64 {
+    let next = vertices.iter().cycle().skip(1);
+    vertices
+        .iter()
+        .zip(next)
+        .map(|(&(x0, y0), &(x1, y1))| {
+            i64::from(x0) * i64::from(y1) - i64::from(x1) * i64::from(y0)
+        })
+        .sum()
+}
+
+/// Encodes a command header.
+const fn command(id: u32, count: u32) -> u32 {
+    (id & 0x7) | (count << 3)
+}
+
+/// Encodes a parameter using zigzag encoding.
+#[expect(clippy::cast_sign_loss, reason = "zigzag encoding")]
+const fn zigzag(value: i32) -> u32 {
+    ((value << 1) ^ (value >> 31)) as u32
+}
+
+#[cfg(test)]
+#[path = "./mvt_tests.rs"]
+mod tests;
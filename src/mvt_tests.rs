@@ -0,0 +1,262 @@
+#![expect(clippy::panic, reason = "unit tests, this is fine")]
+
+use super::*;
+use geo::{BoundingRect, Geometry, MultiPolygon, Validation, polygon};
+
+fn decode(feature: &Feature) -> Geometry {
+    let mut writer = geozero::geo_types::GeoWriter::new();
+    geozero::mvt::process_geom(feature, &mut writer).expect("MVT geometry");
+    writer.take_geometry().expect("geometry")
+}
+
+#[test]
+fn commands() {
+    let polygon = polygon![
+        (x: 3., y: 6.),
+        (x: 8., y: 12.),
+        (x: 20., y: 34.),
+    ];
+
+    let feature = encode([&polygon]).expect("feature");
+
+    // Example from the specification.
+    assert_eq!(feature.r#type, Some(GeomType::Polygon.into()));
+    assert_eq!(feature.geometry, [9, 6, 12, 18, 10, 12, 24, 44, 15]);
+}
+
+#[test]
+fn multipolygon() {
+    let polygons = [
+        polygon![
+            exterior: [
+                (x: 0., y: 0.),
+                (x: 10., y: 0.),
+                (x: 10., y: 10.),
+                (x: 0., y: 10.),
+            ],
+            interiors: [[
+                (x: 2., y: 2.),
+                (x: 8., y: 2.),
+                (x: 8., y: 8.),
+                (x: 2., y: 8.),
+            ]],
+        ],
+        polygon![
+            (x: 20., y: 20.),
+            (x: 30., y: 20.),
+            (x: 30., y: 30.),
+        ],
+    ];
+
+    let feature = encode(&polygons).expect("feature");
+    let Geometry::MultiPolygon(MultiPolygon(result)) = decode(&feature) else {
+        panic!("expected a multipolygon");
+    };
+
+    assert_eq!(result.len(), 2);
+    assert_eq!(result[0].interiors().len(), 1);
+    assert_eq!(result[1].interiors().len(), 0);
+}
+
+#[test]
+fn orientation() {
+    // Exterior with a negative area, hole with a positive one.
+    let polygon = polygon![
+        exterior: [
+            (x: 0., y: 0.),
+            (x: 0., y: 10.),
+            (x: 10., y: 10.),
+            (x: 10., y: 0.),
+        ],
+        interiors: [[
+            (x: 2., y: 2.),
+            (x: 8., y: 2.),
+            (x: 8., y: 8.),
+            (x: 2., y: 8.),
+        ]],
+    ];
+
+    let feature = encode([&polygon]).expect("feature");
+    let Geometry::Polygon(result) = decode(&feature) else {
+        panic!("expected a polygon");
+    };
+
+    assert_eq!(result.interiors().len(), 1);
+}
+
+#[test]
+fn degenerate() {
+    let flat = polygon![
+        (x: 0., y: 0.),
+        (x: 5., y: 0.),
+        (x: 10., y: 0.),
+    ];
+    let duplicates = polygon![
+        (x: 0., y: 0.),
+        (x: 0., y: 0.),
+        (x: 10., y: 10.),
+        (x: 10., y: 10.),
+    ];
+
+    assert!(encode([&flat, &duplicates]).is_none());
+}
+
+#[test]
+fn duplicate_vertices() {
+    let polygon = polygon![
+        (x: 0., y: 0.),
+        (x: 10., y: 0.),
+        (x: 10., y: 0.),
+        (x: 10., y: 10.),
+    ];
+
+    let feature = encode([&polygon]).expect("feature");
+
+    // No zero-length segment.
+    assert_eq!(feature.geometry, [9, 0, 0, 18, 20, 0, 0, 20, 15]);
+}
+
+#[test]
+fn rounding() {
+    let polygon = polygon![
+        (x: -0.6, y: 0.4),
+        (x: 9.6, y: 0.5),
+        (x: 9.4, y: 9.5),
+    ];
+
+    let feature = encode([&polygon]).expect("feature");
+
+    // Rounded to (-1, 0), (10, 1) and (9, 10).
+    assert_eq!(feature.geometry, [9, 1, 0, 18, 22, 2, 1, 18, 15]);
+}
+
+#[test]
+fn spike() {
+    let polygon = polygon![
+        (x: 0., y: 0.),
+        (x: 10., y: 0.),
+        (x: 10., y: 10.),
+        (x: 10., y: 20.),
+        (x: 10.2, y: 10.),
+        (x: 0., y: 10.),
+    ];
+    let expected = polygon![
+        (x: 0., y: 0.),
+        (x: 10., y: 0.),
+        (x: 10., y: 10.),
+        (x: 0., y: 10.),
+    ];
+
+    let feature = encode([&polygon]).expect("feature");
+
+    assert_eq!(Some(feature), encode([&expected]));
+}
+
+#[test]
+fn collapsed_sliver() {
+    let flattened = polygon![
+        (x: 0., y: 0.),
+        (x: 10., y: 0.2),
+        (x: 20., y: 0.1),
+    ];
+    let folded = polygon![
+        (x: 0., y: 0.),
+        (x: 10., y: 0.3),
+        (x: 0., y: 0.4),
+    ];
+
+    assert!(encode([&flattened, &folded]).is_none());
+}
+
+#[test]
+fn pinched_exterior() {
+    // Two squares touching at a vertex.
+    let polygon = polygon![
+        exterior: [
+            (x: 0., y: 0.),
+            (x: 10., y: 0.),
+            (x: 10., y: 10.),
+            (x: 20., y: 10.),
+            (x: 20., y: 20.),
+            (x: 10., y: 20.),
+            (x: 10., y: 10.),
+            (x: 0., y: 10.),
+        ],
+        interiors: [[
+            (x: 12., y: 12.),
+            (x: 18., y: 12.),
+            (x: 18., y: 18.),
+        ]],
+    ];
+
+    let feature = encode([&polygon]).expect("feature");
+    let geometry = decode(&feature);
+    let Geometry::MultiPolygon(MultiPolygon(ref result)) = geometry else {
+        panic!("expected a multipolygon");
+    };
+
+    assert!(geometry.is_valid());
+    assert_eq!(result.len(), 2);
+    // The hole is attached to the square containing it.
+    for polygon in result {
+        let bbox = polygon.bounding_rect().expect("bounding box");
+        let expected = usize::from(bbox.min() == (10., 10.).into());
+        assert_eq!(polygon.interiors().len(), expected);
+    }
+}
+
+#[test]
+fn pinched_hole() {
+    // The exterior ring touches itself, enclosing a hole.
+    let polygon = polygon![
+        (x: 0., y: 0.),
+        (x: 10., y: 0.),
+        (x: 10., y: 10.),
+        (x: 0., y: 10.),
+        (x: 0., y: 5.),
+        (x: 3., y: 7.),
+        (x: 3., y: 3.),
+        (x: 0., y: 5.),
+    ];
+
+    let feature = encode([&polygon]).expect("feature");
+    let geometry = decode(&feature);
+    let Geometry::Polygon(ref result) = geometry else {
+        panic!("expected a polygon");
+    };
+
+    assert!(geometry.is_valid());
+    assert_eq!(result.interiors().len(), 1);
+}
+
+#[test]
+fn reuse() {
+    let square = polygon![
+        (x: 0., y: 0.),
+        (x: 10., y: 0.),
+        (x: 10., y: 10.),
+    ];
+    let flat = polygon![
+        (x: 0., y: 0.),
+        (x: 10., y: 0.),
+    ];
+    let mut encoder = Encoder::default();
+    let mut feature = Feature::default();
+
+    assert!(encoder.encode([&square], &mut feature));
+    assert!(!encoder.encode([&flat], &mut feature));
+    assert!(feature.geometry.is_empty());
+    // The cursor is reset between features.
+    assert!(encoder.encode([&square], &mut feature));
+    assert_eq!(Some(feature), encode([&square]));
+}
+
+#[test]
+fn zigzag_encoding() {
+    assert_eq!(zigzag(0), 0);
+    assert_eq!(zigzag(-1), 1);
+    assert_eq!(zigzag(1), 2);
+    assert_eq!(zigzag(-2), 3);
+    assert_eq!(zigzag(i32::MAX), u32::MAX - 1);
+    assert_eq!(zigzag(i32::MIN), u32::MAX);
+}
@@ -0,0 +1,177 @@
+use crate::{
+    Limits, RenderingError, TileID,
+    render::{
+        Buffers, clip_around, is_transmeridian, project_polygon_into_world,
+        render_world_into,
+    },
+};
+use geo::{BoundingRect, Polygon, Rect};
+use geozero::mvt::tile::Layer;
+use h3o::{CellIndex, geom::SolventBuilder};
+use rstar::{
+    RTree, RTreeObject,
+    primitives::{GeomWithData, Rectangle},
+};
+
+/// Bounding box of a polygon, tagged with its position in the dataset.
+type Entry = GeomWithData<Rectangle<[f64; 2]>, usize>;
+
+/// A dataset dissolved once, ahead of time, into a global shape.
+///
+/// The shape is kept in world coordinates (Web Mercator), from which a tile
+/// is only a scale and a translation away: rendering a tile clips the polygons
+/// intersecting it to the tile surroundings, then moves what's left into the
+/// tile, instead of dissolving the cells of each tile. This is cheaper when
+/// the dataset spans many tiles, and it guarantees that tiles match exactly at
+/// their seams (and the ones rendered by [`render`](crate::render)).
+#[derive(Debug, Clone)]
+pub struct PreparedDataset {
+    /// Dissolved polygons, in world coordinates.
+    ///
+    /// Polygons crossing the antimeridian are stored twice, tagged with the
+    /// hemisphere of the tiles they are rendered in: shifted to the east for
+    /// the eastern tiles (`Some(true)`), and to the west for the western ones
+    /// (`Some(false)`).
+    polygons: Vec<(Polygon, Option<bool>)>,
+    /// Number of polygons in the dissolved shape.
+    len: usize,
+    /// Spatial index over the polygons bounding boxes.
+    index: RTree<Entry>,
+}
+
+impl PreparedDataset {
+    /// Dissolves the given cells into a prepared dataset.
+    ///
+    /// # Errors
+    ///
+    /// All cell indexes must be unique and have the same resolution, otherwise
+    /// a `RenderingError::InvalidInput` is returned.
+    pub fn new(
+        cells: impl IntoIterator<Item = CellIndex>,
+    ) -> Result<Self, RenderingError> {
+        let solvent = SolventBuilder::new().build();
+        let shape = solvent
+            .dissolve(cells)
+            .map_err(RenderingError::InvalidInput)?;
+        let len = shape.0.len();
+
+        // Any tile of a hemisphere shifts the shape the same way.
+        let (east, west) = (
+            TileID::new_unchecked(1, 0, 1),
+            TileID::new_unchecked(0, 0, 1),
+        );
+        let mut polygons = Vec::with_capacity(len);
+        for polygon in shape {
+            let is_transmeridian = std::iter::once(polygon.exterior())
+                .chain(polygon.interiors())
+                .any(is_transmeridian);
+            if is_transmeridian {
+                for (tile_id, eastern) in [(east, true), (west, false)] {
+                    let mut polygon = polygon.clone();
+                    project_polygon_into_world(&mut polygon, tile_id);
+                    polygons.push((polygon, Some(eastern)));
+                }
+            } else {
+                let mut polygon = polygon;
+                project_polygon_into_world(&mut polygon, east);
+                polygons.push((polygon, None));
+            }
+        }
+        let entries = polygons
+            .iter()
+            .enumerate()
+            .map(|(id, (polygon, _))| {
+                let bbox = polygon.bounding_rect().expect("non-empty polygon");
+                Entry::new(rectangle(bbox), id)
+            })
+            .collect();
+
+        Ok(Self {
+            polygons,
+            len,
+            index: RTree::bulk_load(entries),
+        })
+    }
+
+    /// Returns the number of polygons in the dissolved shape.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if the dataset is empty.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Render the dataset into the specified tile.
+    #[must_use]
+    pub fn render(
+        &self,
+        tile_id: TileID,
+        name: String,
+        scratch: bool,
+    ) -> Layer {
+        self.render_with_limits(tile_id, name, scratch, &Limits::new())
+            .expect("unlimited rendering")
+    }
+
+    /// Render the dataset into the specified tile, within the given limits.
+    ///
+    /// The maximum number of cells doesn't apply here, as the dataset is
+    /// already dissolved, and only the vertices around the tile count toward
+    /// the maximum number of vertices.
+    ///
+    /// # Errors
+    ///
+    /// A `RenderingError::Aborted` is returned if a limit is exceeded.
+    pub fn render_with_limits(
+        &self,
+        tile_id: TileID,
+        name: String,
+        scratch: bool,
+        limits: &Limits,
+    ) -> Result<Layer, RenderingError> {
+        let bbox = rectangle(tile_id.world_bbox(2 * TileID::buffer()));
+        let eastern = tile_id.is_eastern();
+        let mut ids = self
+            .index
+            .locate_in_envelope_intersecting(&bbox.envelope())
+            .map(|entry| entry.data)
+            .filter(|&id| {
+                self.polygons[id].1.is_none_or(|side| side == eastern)
+            })
+            .collect::<Vec<_>>();
+        // Keep the dissolution order.
+        ids.sort_unstable();
+
+        let geometry = (!ids.is_empty()).then(|| {
+            clip_around(ids.iter().map(|&id| &self.polygons[id].0), tile_id)
+        });
+
+        let mut layer = Layer {
+            name,
+            ..Layer::default()
+        };
+        render_world_into(
+            tile_id,
+            geometry,
+            scratch,
+            limits,
+            &mut Buffers::default(),
+            &mut layer,
+        )?;
+
+        Ok(layer)
+    }
+}
+
+/// Converts a bounding box into an R-tree rectangle.
+fn rectangle(bbox: Rect) -> Rectangle<[f64; 2]> {
+    Rectangle::from_corners(bbox.min().into(), bbox.max().into())
+}
+
+#[cfg(test)]
+#[path = "./prepared_tests.rs"]
+mod tests;
@@ -0,0 +1,131 @@
+use super::*;
+use crate::{
+    fixtures::{assert_same_rings, coarse_disk, disk, tiles},
+    render,
+};
+use geo::CoordsIter;
+use h3o::Resolution;
+
+#[test]
+fn same_as_render() {
+    let cells = disk();
+    let dataset = PreparedDataset::new(cells.iter().copied()).expect("dataset");
+
+    for scratch in [false, true] {
+        for tile_id in tiles(&cells, 15) {
+            let expected = render(
+                tile_id,
+                cells.iter().copied(),
+                "test".to_owned(),
+                scratch,
+            )
+            .expect("render");
+            let result = dataset.render(tile_id, "test".to_owned(), scratch);
+
+            assert_same_rings(tile_id, &result, &expected);
+        }
+    }
+}
+
+#[test]
+fn coarse_same_as_render() {
+    let cells = coarse_disk();
+    let dataset = PreparedDataset::new(cells.iter().copied()).expect("dataset");
+    let tile_ids = (8..=11).flat_map(|zoom| tiles(&cells, zoom)).chain(
+        ["12/2112/1197", "18/135211/76422"]
+            .map(|tile_id| tile_id.parse::<TileID>().expect("tile")),
+    );
+
+    for tile_id in tile_ids {
+        for scratch in [false, true] {
+            let expected = render(
+                tile_id,
+                cells.iter().copied(),
+                "test".to_owned(),
+                scratch,
+            )
+            .expect("render");
+            let result = dataset.render(tile_id, "test".to_owned(), scratch);
+
+            assert_same_rings(tile_id, &result, &expected);
+        }
+    }
+}
+
+#[test]
+fn outside() {
+    let dataset = PreparedDataset::new(disk()).expect("dataset");
+    let tile_id = TileID::new(0, 0, 15).expect("tile");
+
+    let layer = dataset.render(tile_id, "test".to_owned(), false);
+    assert!(layer.features.is_empty());
+
+    let layer = dataset.render(tile_id, "test".to_owned(), true);
+    assert_eq!(layer.features.len(), 1);
+}
+
+#[test]
+fn antimeridian() {
+    let cell = CellIndex::try_from(0x8a9b4361e0effff).expect("cell");
+    let parent = cell.parent(Resolution::Zero).expect("parent");
+    let dataset = PreparedDataset::new([parent]).expect("dataset");
+    assert_eq!(dataset.len(), 1);
+
+    // Both sides of the antimeridian see the shape.
+    for (x, y) in [(0, 34), (63, 34)] {
+        let tile_id = TileID::new(x, y, 6).expect("tile");
+        let layer = dataset.render(tile_id, "test".to_owned(), false);
+        assert_eq!(layer.features.len(), 1, "{tile_id:?}");
+    }
+}
+
+#[test]
+fn transmeridian_polygons() {
+    let cell = CellIndex::try_from(0x8a9b4361e0effff).expect("cell");
+    let parent = cell.parent(Resolution::Zero).expect("parent");
+
+    let dataset = PreparedDataset::new([parent]).expect("dataset");
+
+    // Stored once per hemisphere, on each side of the antimeridian.
+    let sides = dataset
+        .polygons
+        .iter()
+        .map(|(polygon, side)| {
+            let bbox = polygon.bounding_rect().expect("bbox");
+            (*side, bbox.min().x < 0., bbox.max().x > 1.)
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        sides,
+        [(Some(true), false, true), (Some(false), true, false)]
+    );
+}
+
+#[test]
+fn empty() {
+    let dataset = PreparedDataset::new([]).expect("dataset");
+
+    assert!(dataset.is_empty());
+}
+
+#[test]
+fn clipped_before_projection() {
+    let center = CellIndex::try_from(0x891fb46622fffff).expect("cell");
+    let dataset =
+        PreparedDataset::new(center.grid_disk::<Vec<_>>(30)).expect("dataset");
+    let vertices = dataset
+        .polygons
+        .iter()
+        .map(|(polygon, _)| polygon.coords_count())
+        .sum::<usize>();
+    let tile_id = tiles(&[center], 18).into_iter().next().expect("tile");
+
+    // Only the vertices around the tile count against the limit.
+    let limits = Limits::new().max_vertices(vertices / 10);
+    let layer = dataset
+        .render_with_limits(tile_id, "test".to_owned(), false, &limits)
+        .expect("render");
+
+    assert_eq!(layer, dataset.render(tile_id, "test".to_owned(), false));
+    assert_eq!(layer.features.len(), 1);
+}
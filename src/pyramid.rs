@@ -0,0 +1,240 @@
+use crate::{PyramidError, TileID};
+use ahash::HashMap;
+use h3o::{CellIndex, Resolution};
+
+/// Aggregation used to compute the value of a cell from the values of its
+/// descendants at the finest resolution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Aggregation {
+    /// Sum of the values.
+    Sum,
+    /// Mean of the values.
+    Mean,
+    /// Smallest value.
+    Min,
+    /// Largest value.
+    Max,
+    /// Number of descendants with a value.
+    Count,
+    /// Most frequent value (the smallest one wins in case of tie).
+    Mode,
+    /// Fraction, in `[0; 1]`, of the descendants with a value.
+    Coverage,
+}
+
+/// Selects the resolution to use when rendering a tile.
+pub trait ResolutionPolicy {
+    /// Returns the resolution to use for the given tile.
+    fn resolution(&self, tile_id: TileID) -> Resolution;
+}
+
+impl<F> ResolutionPolicy for F
+where
+    F: Fn(TileID) -> Resolution,
+{
+    fn resolution(&self, tile_id: TileID) -> Resolution {
+        self(tile_id)
+    }
+}
+
+/// A multi-resolution dataset, where coarser resolutions are built from the
+/// finest one.
+#[derive(Debug, Clone)]
+pub struct Pyramid {
+    /// Finest resolution.
+    finest: Resolution,
+    /// Coarsest resolution.
+    coarsest: Resolution,
+    /// Cell values, per resolution (from the coarsest to the finest).
+    levels: Vec<HashMap<CellIndex, f64>>,
+}
+
+impl Pyramid {
+    /// Builds a pyramid from the values of cells at the finest resolution, up
+    /// to the `coarsest` resolution.
+    ///
+    /// If a cell appears several times, the last value wins.
+    ///
+    /// # Errors
+    ///
+    /// All cell indexes must have the same resolution, not coarser than
+    /// `coarsest`, otherwise [`PyramidError`] is returned.
+    pub fn new(
+        values: impl IntoIterator<Item = (CellIndex, f64)>,
+        coarsest: Resolution,
+        aggregation: Aggregation,
+    ) -> Result<Self, PyramidError> {
+        let values = values.into_iter().collect::<HashMap<_, _>>();
+        let Some(&first) = values.keys().next() else {
+            return Ok(Self {
+                finest: coarsest,
+                coarsest,
+                levels: vec![HashMap::default()],
+            });
+        };
+        let finest = first.resolution();
+        if let Some(&cell) =
+            values.keys().find(|cell| cell.resolution() != finest)
+        {
+            return Err(PyramidError::HeterogeneousResolution(cell));
+        }
+        if finest < coarsest {
+            return Err(PyramidError::UnsupportedResolution(first));
+        }
+
+        let levels = Resolution::range(coarsest, finest)
+            .map(|resolution| {
+                aggregate(&values, finest, resolution, aggregation)
+            })
+            .collect();
+
+        Ok(Self {
+            finest,
+            coarsest,
+            levels,
+        })
+    }
+
+    /// Returns the finest resolution of the pyramid.
+    #[must_use]
+    pub const fn finest(&self) -> Resolution {
+        self.finest
+    }
+
+    /// Returns the coarsest resolution of the pyramid.
+    #[must_use]
+    pub const fn coarsest(&self) -> Resolution {
+        self.coarsest
+    }
+
+    /// Returns the cell values at the given resolution, if available.
+    #[must_use]
+    pub fn level(
+        &self,
+        resolution: Resolution,
+    ) -> Option<&HashMap<CellIndex, f64>> {
+        if resolution < self.coarsest || resolution > self.finest {
+            return None;
+        }
+        let index = usize::from(u8::from(resolution) - u8::from(self.coarsest));
+
+        self.levels.get(index)
+    }
+
+    /// Returns the level to use to render the given tile.
+    ///
+    /// The resolution selected by the policy is clamped to the resolutions
+    /// available in the pyramid.
+    #[must_use]
+    pub fn level_for(
+        &self,
+        tile_id: TileID,
+        policy: &impl ResolutionPolicy,
+    ) -> (Resolution, &HashMap<CellIndex, f64>) {
+        let resolution =
+            policy.resolution(tile_id).clamp(self.coarsest, self.finest);
+        let level = self.level(resolution).expect("clamped resolution");
+
+        (resolution, level)
+    }
+}
+
+/// Aggregates the values, defined at the `finest` resolution, at the given
+/// resolution.
+fn aggregate(
+    values: &HashMap<CellIndex, f64>,
+    finest: Resolution,
+    resolution: Resolution,
+    aggregation: Aggregation,
+) -> HashMap<CellIndex, f64> {
+    let mut accumulators = HashMap::<CellIndex, Accumulator>::default();
+    for (&cell, &value) in values {
+        let parent = cell.parent(resolution).expect("coarser resolution");
+        accumulators
+            .entry(parent)
+            .or_insert_with(|| Accumulator::new(aggregation))
+            .add(value);
+    }
+
+    accumulators
+        .into_iter()
+        .map(|(cell, accumulator)| {
+            let value = match aggregation {
+                Aggregation::Sum => accumulator.sum,
+                Aggregation::Mean => accumulator.sum / accumulator.count(),
+                Aggregation::Min => accumulator.min,
+                Aggregation::Max => accumulator.max,
+                Aggregation::Count => accumulator.count(),
+                Aggregation::Mode => accumulator.mode(),
+                Aggregation::Coverage => {
+                    #[expect(
+                        clippy::cast_precision_loss,
+                        reason = "a fraction doesn't need to be exact"
+                    )]
+                    let total = cell.children_count(finest) as f64;
+                    accumulator.count() / total
+                }
+            };
+            (cell, value)
+        })
+        .collect()
+}
+
+/// Accumulates the values of the descendants of a cell.
+struct Accumulator {
+    sum: f64,
+    count: usize,
+    min: f64,
+    max: f64,
+    /// Values frequency, only tracked for `Aggregation::Mode`.
+    histogram: Option<HashMap<u64, usize>>,
+}
+
+impl Accumulator {
+    fn new(aggregation: Aggregation) -> Self {
+        Self {
+            sum: 0.,
+            count: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            histogram: (aggregation == Aggregation::Mode)
+                .then(HashMap::default),
+        }
+    }
+
+    fn add(&mut self, value: f64) {
+        self.sum += value;
+        self.count += 1;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        if let Some(histogram) = self.histogram.as_mut() {
+            *histogram.entry(value.to_bits()).or_default() += 1;
+        }
+    }
+
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "we won't have more than 2^52 descendants"
+    )]
+    const fn count(&self) -> f64 {
+        self.count as f64
+    }
+
+    fn mode(&self) -> f64 {
+        self.histogram
+            .as_ref()
+            .expect("tracked histogram")
+            .iter()
+            .map(|(&bits, &count)| (count, f64::from_bits(bits)))
+            .max_by(|lhs, rhs| {
+                // Most frequent first, then smallest value.
+                lhs.0.cmp(&rhs.0).then_with(|| rhs.1.total_cmp(&lhs.1))
+            })
+            .map_or(f64::NAN, |(_, value)| value)
+    }
+}
+
+#[cfg(test)]
+#[path = "./pyramid_tests.rs"]
+mod tests;
@@ -0,0 +1,149 @@
+use super::*;
+use float_eq::assert_float_eq;
+
+fn cell(value: u64) -> CellIndex {
+    CellIndex::try_from(value).expect("valid cell")
+}
+
+// Three children (out of 7) of the same parent, and one child of another one.
+fn dataset() -> (CellIndex, CellIndex, Vec<(CellIndex, f64)>) {
+    let parent = cell(0x851fb467fffffff);
+    let other = cell(0x851fb463fffffff);
+    let mut values = parent
+        .children(Resolution::Six)
+        .take(3)
+        .zip([1., 2., 2.])
+        .collect::<Vec<_>>();
+    values.push((other.center_child(Resolution::Six).expect("child"), 10.));
+
+    (parent, other, values)
+}
+
+fn aggregate_with(aggregation: Aggregation) -> (f64, f64) {
+    let (parent, other, values) = dataset();
+    let pyramid =
+        Pyramid::new(values, Resolution::Four, aggregation).expect("pyramid");
+    let level = pyramid.level(Resolution::Five).expect("level");
+
+    assert_eq!(level.len(), 2);
+    (level[&parent], level[&other])
+}
+
+#[test]
+fn sum() {
+    let (lhs, rhs) = aggregate_with(Aggregation::Sum);
+    assert_float_eq!(lhs, 5., abs <= f64::EPSILON);
+    assert_float_eq!(rhs, 10., abs <= f64::EPSILON);
+}
+
+#[test]
+fn mean() {
+    let (lhs, rhs) = aggregate_with(Aggregation::Mean);
+    assert_float_eq!(lhs, 5. / 3., abs <= f64::EPSILON);
+    assert_float_eq!(rhs, 10., abs <= f64::EPSILON);
+}
+
+#[test]
+fn min_max() {
+    let (lhs, rhs) = aggregate_with(Aggregation::Min);
+    assert_float_eq!(lhs, 1., abs <= f64::EPSILON);
+    assert_float_eq!(rhs, 10., abs <= f64::EPSILON);
+
+    let (lhs, rhs) = aggregate_with(Aggregation::Max);
+    assert_float_eq!(lhs, 2., abs <= f64::EPSILON);
+    assert_float_eq!(rhs, 10., abs <= f64::EPSILON);
+}
+
+#[test]
+fn count() {
+    let (lhs, rhs) = aggregate_with(Aggregation::Count);
+    assert_float_eq!(lhs, 3., abs <= f64::EPSILON);
+    assert_float_eq!(rhs, 1., abs <= f64::EPSILON);
+}
+
+#[test]
+fn mode() {
+    let (lhs, rhs) = aggregate_with(Aggregation::Mode);
+    assert_float_eq!(lhs, 2., abs <= f64::EPSILON);
+    assert_float_eq!(rhs, 10., abs <= f64::EPSILON);
+}
+
+#[test]
+fn coverage() {
+    let (lhs, rhs) = aggregate_with(Aggregation::Coverage);
+    assert_float_eq!(lhs, 3. / 7., abs <= f64::EPSILON);
+    assert_float_eq!(rhs, 1. / 7., abs <= f64::EPSILON);
+
+    // Coverage is relative to the finest resolution, not the previous level.
+    let (_, _, values) = dataset();
+    let pyramid = Pyramid::new(values, Resolution::Four, Aggregation::Coverage)
+        .expect("pyramid");
+    let level = pyramid.level(Resolution::Four).expect("level");
+    assert_eq!(level.len(), 1);
+    let value = level.values().next().copied().expect("value");
+    assert_float_eq!(value, 4. / 49., abs <= f64::EPSILON);
+}
+
+#[test]
+fn levels() {
+    let (_, _, values) = dataset();
+    let pyramid = Pyramid::new(values, Resolution::Two, Aggregation::Sum)
+        .expect("pyramid");
+
+    assert_eq!(pyramid.coarsest(), Resolution::Two);
+    assert_eq!(pyramid.finest(), Resolution::Six);
+    assert!(pyramid.level(Resolution::One).is_none());
+    assert!(pyramid.level(Resolution::Seven).is_none());
+    for resolution in Resolution::range(Resolution::Two, Resolution::Six) {
+        let level = pyramid.level(resolution).expect("level");
+        let total = level.values().sum::<f64>();
+        assert_float_eq!(total, 15., abs <= f64::EPSILON);
+    }
+}
+
+#[test]
+fn level_for() {
+    let (_, _, values) = dataset();
+    let pyramid = Pyramid::new(values, Resolution::Four, Aggregation::Count)
+        .expect("pyramid");
+    let policy = |tile_id: TileID| match tile_id.zoom() {
+        0..=4 => Resolution::Two,
+        5..=7 => Resolution::Five,
+        _ => Resolution::Ten,
+    };
+
+    let tile = TileID::new(0, 0, 0).expect("tile");
+    assert_eq!(pyramid.level_for(tile, &policy).0, Resolution::Four);
+    let tile = TileID::new(0, 0, 6).expect("tile");
+    assert_eq!(pyramid.level_for(tile, &policy).0, Resolution::Five);
+    let tile = TileID::new(0, 0, 12).expect("tile");
+    let (resolution, level) = pyramid.level_for(tile, &policy);
+    assert_eq!(resolution, Resolution::Six);
+    assert_eq!(level.len(), 4);
+}
+
+#[test]
+fn invalid_input() {
+    let (_, _, mut values) = dataset();
+    let result =
+        Pyramid::new(values.clone(), Resolution::Seven, Aggregation::Sum);
+    assert!(matches!(
+        result,
+        Err(PyramidError::UnsupportedResolution(_))
+    ));
+
+    values.push((cell(0x851fb467fffffff), 1.));
+    let result = Pyramid::new(values, Resolution::Four, Aggregation::Sum);
+    assert!(matches!(
+        result,
+        Err(PyramidError::HeterogeneousResolution(_))
+    ));
+}
+
+#[test]
+fn empty() {
+    let pyramid =
+        Pyramid::new([], Resolution::Four, Aggregation::Sum).expect("pyramid");
+
+    assert!(pyramid.level(Resolution::Four).expect("level").is_empty());
+}
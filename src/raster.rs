@@ -0,0 +1,473 @@
+use crate::{PropertyValue, TileEncoder, TileFeature, TileID};
+use geo::{Coord, MultiPolygon};
+
+/// Number of sub-scanlines per pixel row, when anti-aliasing.
+const SAMPLES: u32 = 4;
+
+/// Size of a raster tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum RasterSize {
+    /// 256×256 pixels.
+    #[default]
+    Px256,
+    /// 512×512 pixels (high DPI).
+    Px512,
+}
+
+impl RasterSize {
+    /// Returns the width (and height) of the tile, in pixels.
+    #[must_use]
+    pub const fn pixels(self) -> u32 {
+        match self {
+            Self::Px256 => 256,
+            Self::Px512 => 512,
+        }
+    }
+}
+
+/// Fill color of the features, as non-premultiplied RGBA.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Fill {
+    /// Same color for every feature.
+    Solid([u8; 4]),
+    /// Color picked from the value of a property (compared as a string).
+    ///
+    /// Features without a matching class are not filled.
+    Classes {
+        /// Name of the property.
+        property: String,
+        /// Color of each class.
+        colors: Vec<(String, [u8; 4])>,
+    },
+    /// Color interpolated from the value of a numeric property.
+    ///
+    /// Values outside of the ramp are clamped, and features without a numeric
+    /// value are not filled.
+    Ramp {
+        /// Name of the property.
+        property: String,
+        /// Color stops, sorted by value.
+        stops: Vec<(f64, [u8; 4])>,
+    },
+}
+
+impl Fill {
+    /// Returns the fill color of a feature, if any.
+    fn color(&self, feature: &TileFeature<'_>) -> Option<[u8; 4]> {
+        let value = |name: &str| {
+            feature
+                .properties()
+                .iter()
+                .find_map(|&(key, value)| (key == name).then_some(value))
+        };
+        match *self {
+            Self::Solid(color) => Some(color),
+            Self::Classes {
+                ref property,
+                ref colors,
+            } => {
+                let class = match value(property)? {
+                    PropertyValue::String(value) => value.to_owned(),
+                    PropertyValue::Double(value) => value.to_string(),
+                    PropertyValue::Int(value) => value.to_string(),
+                    PropertyValue::UInt(value) => value.to_string(),
+                    PropertyValue::Bool(value) => value.to_string(),
+                };
+                colors
+                    .iter()
+                    .find_map(|(key, color)| (*key == class).then_some(*color))
+            }
+            Self::Ramp {
+                ref property,
+                ref stops,
+            } => {
+                #[expect(clippy::cast_precision_loss, reason = "color ramp")]
+                let value = match value(property)? {
+                    PropertyValue::Double(value) => value,
+                    PropertyValue::Int(value) => value as f64,
+                    PropertyValue::UInt(value) => value as f64,
+                    _ => return None,
+                };
+                interpolate(stops, value)
+            }
+        }
+    }
+}
+
+/// Interpolates the color of a value along a ramp.
+fn interpolate(stops: &[(f64, [u8; 4])], value: f64) -> Option<[u8; 4]> {
+    let index = stops.partition_point(|&(stop, _)| stop <= value);
+    let (lower, upper) = match (index.checked_sub(1), stops.get(index)) {
+        (Some(lower), Some(upper)) => (stops[lower], *upper),
+        (Some(lower), None) => return Some(stops[lower].1),
+        (None, upper) => return upper.map(|upper| upper.1),
+    };
+    let ratio = (value - lower.0) / (upper.0 - lower.0);
+    let mut color = [0; 4];
+    for (channel, (from, to)) in
+        color.iter_mut().zip(lower.1.into_iter().zip(upper.1))
+    {
+        let (from, to) = (f64::from(from), f64::from(to));
+        *channel = to_u8(ratio.mul_add(to - from, from));
+    }
+    Some(color)
+}
+
+// -----------------------------------------------------------------------------
+
+/// Raster encoder, rendering the features into a RGBA PNG image.
+///
+/// Features are filled (holes included) in order, then outlined if requested.
+/// Only the tile itself is rendered: geometry overflowing into the buffer is
+/// ignored.
+///
+/// Requires the `raster` feature.
+///
+/// # Example
+///
+/// ```
+/// use h3o::CellIndex;
+/// use h3o_mvt::{Fill, RasterEncoder, RasterSize, render_with, tiles_for_cell};
+///
+/// let cell = CellIndex::try_from(0x891fb46622fffff)?;
+/// let tile_id = tiles_for_cell(cell, 14..=14).into_iter().next().expect("tile");
+/// let mut encoder = RasterEncoder::new(RasterSize::Px256)
+///     .with_fill(Fill::Solid([255, 0, 0, 128]))
+///     .with_outline([0, 0, 0, 255], 1.);
+///
+/// let png = render_with(tile_id, [cell], "h3".to_owned(), false, &mut encoder)?;
+/// assert!(png.starts_with(b"\x89PNG"));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct RasterEncoder {
+    /// Size of the image.
+    size: RasterSize,
+    /// Fill color.
+    fill: Fill,
+    /// Outline color and width (in pixels), if any.
+    outline: Option<([u8; 4], f64)>,
+    /// Whether the edges are anti-aliased.
+    antialiasing: bool,
+    /// Canvas, as premultiplied RGBA.
+    canvas: Vec<[f64; 4]>,
+    /// Coverage of each pixel, for the shape being drawn.
+    coverage: Vec<f64>,
+    /// Edges of the shape being drawn.
+    edges: Vec<Edge>,
+}
+
+impl RasterEncoder {
+    /// Initializes a new encoder, filling the features in opaque black, with
+    /// anti-aliasing and without outline.
+    #[must_use]
+    pub const fn new(size: RasterSize) -> Self {
+        Self {
+            size,
+            fill: Fill::Solid([0, 0, 0, 255]),
+            outline: None,
+            antialiasing: true,
+            canvas: Vec::new(),
+            coverage: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /// Sets the fill color of the features.
+    #[must_use]
+    pub fn with_fill(mut self, fill: Fill) -> Self {
+        self.fill = fill;
+        self
+    }
+
+    /// Outlines the rings of the features, with the given color and width (in
+    /// pixels).
+    #[must_use]
+    pub const fn with_outline(mut self, color: [u8; 4], width: f64) -> Self {
+        self.outline = Some((color, width));
+        self
+    }
+
+    /// Enables or disables anti-aliasing.
+    #[must_use]
+    pub const fn with_antialiasing(mut self, antialiasing: bool) -> Self {
+        self.antialiasing = antialiasing;
+        self
+    }
+
+    /// Fills a shape, in tile coordinates.
+    fn fill(&mut self, geometry: &MultiPolygon, color: [u8; 4]) {
+        let scale = f64::from(self.size.pixels()) / f64::from(TileID::extent());
+        self.edges.clear();
+        for polygon in geometry {
+            for ring in
+                std::iter::once(polygon.exterior()).chain(polygon.interiors())
+            {
+                self.edges.extend(ring.lines().filter_map(|line| {
+                    Edge::new(line.start * scale, line.end * scale)
+                }));
+            }
+        }
+        self.draw(Rule::EvenOdd, color);
+    }
+
+    /// Outlines a shape, in tile coordinates.
+    fn outline(&mut self, geometry: &MultiPolygon, color: [u8; 4], width: f64) {
+        let scale = f64::from(self.size.pixels()) / f64::from(TileID::extent());
+        self.edges.clear();
+        for polygon in geometry {
+            for ring in
+                std::iter::once(polygon.exterior()).chain(polygon.interiors())
+            {
+                for line in ring.lines() {
+                    let (start, end) = (line.start * scale, line.end * scale);
+                    let delta = end - start;
+                    let length = delta.x.hypot(delta.y);
+                    if length == 0. {
+                        continue;
+                    }
+                    // Each segment is drawn as a rectangle, counter-clockwise
+                    // so that overlaps are merged by the non-zero rule.
+                    let normal = Coord {
+                        x: -delta.y,
+                        y: delta.x,
+                    } * (width / 2. / length);
+                    let corners = [
+                        start - normal,
+                        end - normal,
+                        end + normal,
+                        start + normal,
+                    ];
+                    for index in 0..4 {
+                        self.edges.extend(Edge::new(
+                            corners[index],
+                            corners[(index + 1) % 4],
+                        ));
+                    }
+                }
+            }
+        }
+        self.draw(Rule::NonZero, color);
+    }
+
+    /// Rasterizes the current edges, and blends the color on the canvas.
+    fn draw(&mut self, rule: Rule, color: [u8; 4]) {
+        let size = self.size.pixels();
+        self.coverage.clear();
+        self.coverage.resize(pixel_count(size * size), 0.);
+        self.edges
+            .sort_unstable_by(|lhs, rhs| lhs.from.y.total_cmp(&rhs.from.y));
+
+        let samples = if self.antialiasing { SAMPLES } else { 1 };
+        let weight = 1. / f64::from(samples);
+        let mut next = 0;
+        let mut active = Vec::new();
+        let mut crossings = Vec::new();
+        for row in 0..size {
+            let offset = pixel_count(row * size);
+            let line = &mut self.coverage[offset..offset + pixel_count(size)];
+            for sample in 0..samples {
+                let y = f64::from(row)
+                    + (f64::from(sample) + 0.5) / f64::from(samples);
+                while next < self.edges.len() && self.edges[next].from.y <= y {
+                    active.push(next);
+                    next += 1;
+                }
+                active.retain(|&index| self.edges[index].to.y > y);
+
+                crossings.clear();
+                crossings.extend(active.iter().filter_map(|&index| {
+                    let edge = &self.edges[index];
+                    (edge.from.y <= y).then(|| (edge.x_at(y), edge.winding))
+                }));
+                crossings.sort_unstable_by(|lhs, rhs| lhs.0.total_cmp(&rhs.0));
+
+                let mut winding = 0;
+                for pair in crossings.windows(2) {
+                    winding += pair[0].1;
+                    let inside = match rule {
+                        Rule::EvenOdd => winding % 2 != 0,
+                        Rule::NonZero => winding != 0,
+                    };
+                    if inside {
+                        add_span(
+                            line,
+                            pair[0].0,
+                            pair[1].0,
+                            weight,
+                            self.antialiasing,
+                        );
+                    }
+                }
+            }
+        }
+
+        let alpha = f64::from(color[3]) / 255.;
+        for (pixel, &coverage) in self.canvas.iter_mut().zip(&self.coverage) {
+            let coverage = coverage.min(1.) * alpha;
+            if coverage <= 0. {
+                continue;
+            }
+            for channel in 0..3 {
+                pixel[channel] = (f64::from(color[channel]) / 255.)
+                    .mul_add(coverage, pixel[channel] * (1. - coverage));
+            }
+            pixel[3] = pixel[3].mul_add(1. - coverage, coverage);
+        }
+    }
+}
+
+impl TileEncoder for RasterEncoder {
+    type Output = Vec<u8>;
+
+    fn encode(&mut self, _name: &str, features: &[TileFeature<'_>]) -> Vec<u8> {
+        let size = self.size.pixels();
+        self.canvas.clear();
+        self.canvas
+            .resize(pixel_count(size) * pixel_count(size), [0.; 4]);
+
+        for feature in features {
+            if let Some(color) = self.fill.color(feature) {
+                self.fill(feature.geometry(), color);
+            }
+            if let Some((color, width)) = self.outline {
+                self.outline(feature.geometry(), color, width);
+            }
+        }
+
+        let pixels = self
+            .canvas
+            .iter()
+            .map(|&[red, green, blue, alpha]| {
+                if alpha <= 0. {
+                    return [0; 4];
+                }
+                [
+                    to_u8(red / alpha * 255.),
+                    to_u8(green / alpha * 255.),
+                    to_u8(blue / alpha * 255.),
+                    to_u8(alpha * 255.),
+                ]
+            })
+            .collect::<Vec<_>>();
+        encode_png(size, &pixels)
+    }
+}
+
+/// Filling rule.
+#[derive(Debug, Clone, Copy)]
+enum Rule {
+    /// Inside if crossed an odd number of times.
+    EvenOdd,
+    /// Inside if the winding number is not zero.
+    NonZero,
+}
+
+/// An edge, in pixel coordinates, going down.
+#[derive(Debug, Clone, Copy)]
+struct Edge {
+    /// Upper end.
+    from: Coord,
+    /// Lower end.
+    to: Coord,
+    /// Original direction (1 if going down, -1 if going up).
+    winding: i32,
+}
+
+impl Edge {
+    /// Initializes a new edge, unless horizontal.
+    fn new(start: Coord, end: Coord) -> Option<Self> {
+        if start.y < end.y {
+            Some(Self {
+                from: start,
+                to: end,
+                winding: 1,
+            })
+        } else if start.y > end.y {
+            Some(Self {
+                from: end,
+                to: start,
+                winding: -1,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the X coordinate of the edge at the given height.
+    fn x_at(&self, y: f64) -> f64 {
+        let ratio = (y - self.from.y) / (self.to.y - self.from.y);
+        ratio.mul_add(self.to.x - self.from.x, self.from.x)
+    }
+}
+
+/// Adds the coverage of a span (on a sub-scanline) to a row of pixels.
+fn add_span(line: &mut [f64], start: f64, end: f64, weight: f64, exact: bool) {
+    #[expect(clippy::cast_precision_loss, reason = "tile size")]
+    let width = line.len() as f64;
+    let (start, end) = (start.clamp(0., width), end.clamp(0., width));
+    if exact {
+        // Partially covered pixels get a partial coverage.
+        let first = start.floor();
+        let mut x = first;
+        while x < end {
+            let covered = end.min(x + 1.) - start.max(x);
+            line[to_index(x)] += covered * weight;
+            x += 1.;
+        }
+    } else {
+        // Pixels are covered if their center is.
+        let (first, last) = ((start - 0.5).ceil(), (end - 0.5).ceil());
+        let mut x = first;
+        while x < last {
+            line[to_index(x)] += weight;
+            x += 1.;
+        }
+    }
+}
+
+/// Converts a pixel coordinate into an index.
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "clamped in the image"
+)]
+const fn to_index(x: f64) -> usize {
+    x as usize
+}
+
+/// Converts a color channel.
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "clamped in range"
+)]
+const fn to_u8(value: f64) -> u8 {
+    value.round().clamp(0., 255.) as u8
+}
+
+/// Converts a number of pixels into a size.
+fn pixel_count(value: u32) -> usize {
+    usize::try_from(value).expect("image size")
+}
+
+// -----------------------------------------------------------------------------
+
+/// Encodes a RGBA image as PNG.
+fn encode_png(size: u32, pixels: &[[u8; 4]]) -> Vec<u8> {
+    let mut png = Vec::new();
+    let mut encoder = png::Encoder::new(&mut png, size, size);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(pixels.as_flattened()))
+        .expect("in-memory PNG encoding");
+    png
+}
+
+#[cfg(test)]
+#[path = "./raster_tests.rs"]
+mod tests;
@@ -0,0 +1,201 @@
+use super::*;
+use geo::polygon;
+use std::io::Cursor;
+
+/// Decodes a PNG produced by the encoder.
+fn decode(png: &[u8]) -> (u32, Vec<[u8; 4]>) {
+    let mut reader = png::Decoder::new(Cursor::new(png))
+        .read_info()
+        .expect("PNG header");
+    let mut data = vec![0; reader.output_buffer_size().expect("buffer size")];
+    let info = reader.next_frame(&mut data).expect("PNG image");
+    assert_eq!(info.color_type, png::ColorType::Rgba);
+    assert_eq!(info.bit_depth, png::BitDepth::Eight);
+    assert_eq!(info.width, info.height);
+
+    let pixels = data
+        .chunks(4)
+        .map(|pixel| pixel.try_into().expect("pixel"))
+        .collect();
+    (info.width, pixels)
+}
+
+/// Returns a square, in tile coordinates.
+fn square(min: f64, max: f64) -> MultiPolygon {
+    MultiPolygon::new(vec![polygon![
+        (x: min, y: min),
+        (x: max, y: min),
+        (x: max, y: max),
+        (x: min, y: max),
+    ]])
+}
+
+#[test]
+fn solid() {
+    // Left half of the tile.
+    let geometry = MultiPolygon::new(vec![polygon![
+        (x: 0., y: 0.),
+        (x: 2048., y: 0.),
+        (x: 2048., y: 4096.),
+        (x: 0., y: 4096.),
+    ]]);
+    let mut encoder = RasterEncoder::new(RasterSize::Px512)
+        .with_fill(Fill::Solid([255, 0, 0, 255]));
+
+    let (size, pixels) =
+        decode(&encoder.encode("h3", &[TileFeature::new(&geometry)]));
+
+    assert_eq!(size, 512);
+    for (index, pixel) in pixels.iter().enumerate() {
+        let expected = if index % 512 < 256 {
+            [255, 0, 0, 255]
+        } else {
+            [0; 4]
+        };
+        assert_eq!(*pixel, expected, "{index}");
+    }
+}
+
+#[test]
+fn holes() {
+    let mut geometry = square(1024., 3072.);
+    geometry.0[0].interiors_push(vec![
+        (1536., 1536.),
+        (1536., 2560.),
+        (2560., 2560.),
+        (2560., 1536.),
+    ]);
+    let mut encoder = RasterEncoder::new(RasterSize::Px256);
+
+    let (_, pixels) =
+        decode(&encoder.encode("h3", &[TileFeature::new(&geometry)]));
+
+    let pixel = |x: usize, y: usize| pixels[y * 256 + x];
+    assert_eq!(pixel(10, 10), [0; 4]);
+    assert_eq!(pixel(70, 70), [0, 0, 0, 255]);
+    assert_eq!(pixel(128, 128), [0; 4]);
+}
+
+#[test]
+fn antialiasing() {
+    // Edges in the middle of pixels.
+    let geometry = square(8., 4088.);
+
+    for (antialiasing, expected) in [(true, 128), (false, 255)] {
+        let mut encoder = RasterEncoder::new(RasterSize::Px256)
+            .with_antialiasing(antialiasing);
+        let (_, pixels) =
+            decode(&encoder.encode("h3", &[TileFeature::new(&geometry)]));
+
+        assert_eq!(pixels[256 * 128][3], expected, "{antialiasing}");
+        assert_eq!(pixels[256 * 128 + 128][3], 255, "{antialiasing}");
+        // Corner is a quarter covered.
+        if antialiasing {
+            assert_eq!(pixels[0][3], 64);
+        }
+    }
+}
+
+#[test]
+fn outline() {
+    let geometry = square(1024., 3072.);
+    let mut encoder = RasterEncoder::new(RasterSize::Px256)
+        .with_fill(Fill::Solid([0, 0, 255, 255]))
+        .with_outline([255, 255, 255, 255], 2.)
+        .with_antialiasing(false);
+
+    let (_, pixels) =
+        decode(&encoder.encode("h3", &[TileFeature::new(&geometry)]));
+
+    let pixel = |x: usize, y: usize| pixels[y * 256 + x];
+    assert_eq!(pixel(63, 128), [255, 255, 255, 255]);
+    assert_eq!(pixel(64, 128), [255, 255, 255, 255]);
+    assert_eq!(pixel(62, 128), [0; 4]);
+    assert_eq!(pixel(65, 128), [0, 0, 255, 255]);
+    // Corners are covered too.
+    assert_eq!(pixel(64, 64), [255, 255, 255, 255]);
+}
+
+#[test]
+fn blending() {
+    let geometry = square(0., 4096.);
+    let features = [TileFeature::new(&geometry), TileFeature::new(&geometry)];
+    let mut encoder = RasterEncoder::new(RasterSize::Px256)
+        .with_fill(Fill::Solid([255, 0, 0, 128]));
+
+    let (_, pixels) = decode(&encoder.encode("h3", &features));
+
+    // 1 - (1 - 0.5)²
+    assert_eq!(pixels[0], [255, 0, 0, 192]);
+}
+
+#[test]
+fn classes() {
+    let (first, second) = (square(0., 2048.), square(2048., 4096.));
+    let first_properties = [("kind", PropertyValue::String("water"))];
+    let second_properties = [("kind", PropertyValue::Int(3))];
+    let features = [
+        TileFeature::new(&first).with_properties(&first_properties),
+        TileFeature::new(&second).with_properties(&second_properties),
+    ];
+
+    for (colors, expected) in [
+        (
+            vec![
+                ("water".to_owned(), [0, 0, 255, 255]),
+                ("3".to_owned(), [0, 255, 0, 255]),
+            ],
+            [[0, 0, 255, 255], [0, 255, 0, 255]],
+        ),
+        (
+            vec![("water".to_owned(), [0, 0, 255, 255])],
+            [[0, 0, 255, 255], [0; 4]],
+        ),
+    ] {
+        let mut encoder =
+            RasterEncoder::new(RasterSize::Px256).with_fill(Fill::Classes {
+                property: "kind".to_owned(),
+                colors,
+            });
+
+        let (_, pixels) = decode(&encoder.encode("h3", &features));
+
+        assert_eq!([pixels[0], pixels[256 * 256 - 1]], expected);
+    }
+}
+
+#[test]
+fn ramp() {
+    let stops = vec![(0., [0, 0, 0, 255]), (10., [200, 100, 0, 255])];
+
+    assert_eq!(interpolate(&stops, -5.), Some([0, 0, 0, 255]));
+    assert_eq!(interpolate(&stops, 5.), Some([100, 50, 0, 255]));
+    assert_eq!(interpolate(&stops, 10.), Some([200, 100, 0, 255]));
+    assert_eq!(interpolate(&stops, 15.), Some([200, 100, 0, 255]));
+    assert_eq!(interpolate(&[], 15.), None);
+
+    let geometry = square(0., 4096.);
+    let properties = [("value", PropertyValue::UInt(5))];
+    let mut encoder =
+        RasterEncoder::new(RasterSize::Px256).with_fill(Fill::Ramp {
+            property: "value".to_owned(),
+            stops,
+        });
+
+    let (_, pixels) = decode(&encoder.encode(
+        "h3",
+        &[TileFeature::new(&geometry).with_properties(&properties)],
+    ));
+
+    assert_eq!(pixels[0], [100, 50, 0, 255]);
+}
+
+#[test]
+fn empty() {
+    let mut encoder = RasterEncoder::new(RasterSize::Px256);
+
+    let (size, pixels) = decode(&encoder.encode("h3", &[]));
+
+    assert_eq!(size, 256);
+    assert!(pixels.iter().all(|pixel| *pixel == [0; 4]));
+}
@@ -1,14 +1,15 @@
 use crate::{
-    RenderingError, TileID, ring_hierarchy::RingHierarchy, tile::TileCoord,
+    Limits, MvtEncoder, Renderer, RenderingError, TileEncoder, TileFeature,
+    TileID, clip, mvt, ring_hierarchy::RingHierarchy, tile::TileCoord,
 };
 use ahash::HashSet;
 use geo::{
-    BooleanOps, BoundingRect, Contains, Coord, Geometry, Intersects,
-    LineString, MultiPolygon, Polygon, Rect, Winding, line_string,
+    BoundingRect, Contains, Coord, CoordsIter, Intersects, LineString,
+    MapCoordsInPlace, MultiPolygon, Polygon, Rect, Winding, line_string,
 };
-use geozero::{ToMvt, mvt::tile::Layer};
-use h3o::{CellIndex, LatLng, geom::SolventBuilder};
-use std::{collections::VecDeque, ops::RangeInclusive};
+use geozero::mvt::tile::{Feature, Layer};
+use h3o::{CellIndex, LatLng};
+use std::{cmp::Ordering, collections::VecDeque, ops::RangeInclusive};
 
 /// Returns every tile ID touched by a given cell index in the specified zoom
 /// range.
@@ -65,79 +66,248 @@ pub fn tiles_for_cell(
 ///
 /// All cell indexes must be unique and have the same resolution, otherwise a
 /// `RenderingError::InvalidInput` is returned.
-///
-/// If for some reason the geometry cannot be encoded into an MVT layer, a
-/// `RenderingError::Encoding` is returned, carrying the underlying error.
 pub fn render(
     tile_id: TileID,
     cells: impl IntoIterator<Item = CellIndex>,
     name: String,
     scratch: bool,
 ) -> Result<Layer, RenderingError> {
-    let solvent = SolventBuilder::new().build();
-    let geometry = solvent
-        .dissolve(cells)
-        .map_err(RenderingError::InvalidInput)
-        .map(|shape| (!shape.0.is_empty()).then_some(shape))?;
-
-    let mut features = Vec::with_capacity(1);
-    if let Some(geometry) = geometry {
-        let mut geometry = MultiPolygon::new(
-            geometry
-                .into_iter()
-                .map(|mut polygon| {
-                    project_polygon_into_grid(&mut polygon, tile_id);
-                    polygon
-                })
-                // Ideally we should filter before the map, but it's easier to
-                // filter after the reprojection.
-                .filter(polygon_is_visible)
-                .collect(),
-        );
-
-        if scratch {
-            geometry = carve_out_from_tile(geometry);
-        }
+    let mut layer = Layer::default();
+    Renderer::new(name, scratch).render_into(tile_id, cells, &mut layer)?;
 
-        // Clip the resulting geometry using the buffered tile shape.
-        //
-        // This results in more correct line interpolations, thus preventing
-        // distortions and mismatches at the tile edges for shape that overlap
-        // several tiles (this tend to become visible at high zoom levels such
-        // as 19+).
-        let bbox = TileID::buffered_shape().to_polygon();
-        geometry = geometry.intersection(&MultiPolygon(vec![bbox]));
-
-        if !geometry.0.is_empty() {
-            features.push(
-                Geometry::MultiPolygon(geometry)
-                    .to_mvt_unscaled()
-                    .map_err(RenderingError::Encoding)?,
-            );
-        }
-    } else if scratch {
+    Ok(layer)
+}
+
+/// Render the given shape, in EPSG:4326 coordinates, into the specified tile.
+#[cfg(test)]
+pub fn render_shape(
+    tile_id: TileID,
+    geometry: Option<MultiPolygon>,
+    name: String,
+    scratch: bool,
+) -> Layer {
+    let mut buffers = Buffers::default();
+    let geometry = shape_into_tile(
+        tile_id,
+        geometry,
+        scratch,
+        &Limits::new(),
+        &mut buffers,
+    )
+    .expect("unlimited rendering");
+    let mut layer = Layer {
+        name,
+        ..Layer::default()
+    };
+    encode_into(&geometry, &mut buffers, &mut layer);
+
+    layer
+}
+
+/// Render the given cells into the specified tile, as polygons in tile
+/// coordinates.
+///
+/// This is the geometry encoded by [`render`]: the cells are dissolved,
+/// projected into the tile (where the extent spans from 0 to 4096, Y pointing
+/// down), carved out of the tile in scratch mode, and clipped to the tile
+/// padded by its buffer. Coordinates are not rounded yet.
+///
+/// No limits are applied: to bound the work, set them with
+/// [`Renderer::set_limits`] and use [`Renderer::render_geometry`] instead.
+///
+/// # Errors
+///
+/// All cell indexes must be unique and have the same resolution, otherwise a
+/// `RenderingError::InvalidInput` is returned.
+pub fn render_geometry(
+    tile_id: TileID,
+    cells: impl IntoIterator<Item = CellIndex>,
+    scratch: bool,
+) -> Result<MultiPolygon, RenderingError> {
+    Renderer::new(String::new(), scratch).render_geometry(tile_id, cells)
+}
+
+/// Encodes polygons, in tile coordinates, into a MVT layer.
+///
+/// This is the encoding stage of [`render`]: coordinates are rounded, and the
+/// rings repaired and oriented as required by the MVT specification.
+#[must_use]
+pub fn encode_layer(geometry: &MultiPolygon, name: &str) -> Layer {
+    MvtEncoder::new().encode(name, &[TileFeature::new(geometry)])
+}
+
+/// Render the given cells into the specified tile, using a custom encoder.
+///
+/// The rendered geometry (see [`render_geometry`]) is given to the encoder as
+/// a single feature, without properties, or no feature at all if the tile is
+/// empty.
+///
+/// # Errors
+///
+/// All cell indexes must be unique and have the same resolution, otherwise a
+/// `RenderingError::InvalidInput` is returned.
+pub fn render_with<E: TileEncoder>(
+    tile_id: TileID,
+    cells: impl IntoIterator<Item = CellIndex>,
+    name: String,
+    scratch: bool,
+    encoder: &mut E,
+) -> Result<E::Output, RenderingError> {
+    Renderer::new(name, scratch).render_with(tile_id, cells, encoder)
+}
+
+/// Render the given shape, in world coordinates, into the specified tile.
+///
+/// The shape must have been projected by [`project_polygon_into_world`] for
+/// this tile (or one on the same side of the antimeridian), and it's usually
+/// clipped around it with [`clip_around`].
+///
+/// The content of the layer is replaced, except for its name, and both the
+/// layer and the scratch buffers are reused to save on allocations.
+///
+/// The limits are checked before each stage (projection, clipping and
+/// encoding). On error, the layer is left untouched.
+pub fn render_world_into(
+    tile_id: TileID,
+    geometry: Option<MultiPolygon>,
+    scratch: bool,
+    limits: &Limits,
+    buffers: &mut Buffers,
+    layer: &mut Layer,
+) -> Result<(), RenderingError> {
+    let geometry = into_tile(
+        tile_id,
+        geometry,
+        scratch,
+        limits,
+        buffers,
+        project_world_polygon_into_grid,
+    )?;
+    limits.check()?;
+    encode_into(&geometry, buffers, layer);
+
+    Ok(())
+}
+
+/// Projects the given shape, in EPSG:4326 coordinates, into the specified
+/// tile, then clips it.
+///
+/// The limits are checked before each stage (projection and clipping).
+pub fn shape_into_tile(
+    tile_id: TileID,
+    geometry: Option<MultiPolygon>,
+    scratch: bool,
+    limits: &Limits,
+    buffers: &mut Buffers,
+) -> Result<MultiPolygon, RenderingError> {
+    into_tile(
+        tile_id,
+        geometry,
+        scratch,
+        limits,
+        buffers,
+        project_polygon_into_grid,
+    )
+}
+
+/// Projects the given shape into the specified tile, using `project`, then
+/// clips it.
+fn into_tile(
+    tile_id: TileID,
+    geometry: Option<MultiPolygon>,
+    scratch: bool,
+    limits: &Limits,
+    buffers: &mut Buffers,
+    project: fn(&mut Polygon, TileID),
+) -> Result<MultiPolygon, RenderingError> {
+    limits.check()?;
+    let Some(geometry) = geometry else {
         // If there are no shape in scratch mode, we still need to render the
         // tile itself.
-        features.push(
-            Geometry::Rect(TileID::buffered_shape())
-                .to_mvt_unscaled()
-                .map_err(RenderingError::Encoding)?,
-        );
+        return Ok(if scratch {
+            MultiPolygon::from(TileID::buffered_shape().to_polygon())
+        } else {
+            MultiPolygon::new(Vec::new())
+        });
+    };
+
+    limits.check_vertices(geometry.coords_count())?;
+    buffers.polygons.extend(
+        geometry
+            .into_iter()
+            .map(|mut polygon| {
+                project(&mut polygon, tile_id);
+                polygon
+            })
+            // Ideally we should filter before the map, but it's easier to
+            // filter after the reprojection.
+            .filter(polygon_is_visible),
+    );
+    let mut geometry = MultiPolygon::new(std::mem::take(&mut buffers.polygons));
+
+    if scratch {
+        geometry = carve_out_from_tile(geometry);
     }
+    limits.check()?;
+
+    // Clip the resulting geometry using the buffered tile shape.
+    //
+    // This results in more correct line interpolations, thus preventing
+    // distortions and mismatches at the tile edges for shape that overlap
+    // several tiles (this tend to become visible at high zoom levels such
+    // as 19+).
+    let mut clipped =
+        clip::clip_with(&geometry, TileID::buffered_shape(), &mut buffers.clip);
+    buffers.polygons = geometry.0;
+    buffers.polygons.clear();
+    canonicalize(&mut clipped);
+
+    Ok(clipped)
+}
 
-    Ok(Layer {
-        extent: Some(TileID::extent()),
-        version: 2,
-        name,
-        features,
-        ..Layer::default()
-    })
+/// Encodes polygons, in tile coordinates, into the given layer.
+///
+/// The content of the layer is replaced, except for its name.
+pub fn encode_into(
+    geometry: &MultiPolygon,
+    buffers: &mut Buffers,
+    layer: &mut Layer,
+) {
+    layer.extent = Some(TileID::extent());
+    layer.version = 2;
+    layer.keys.clear();
+    layer.values.clear();
+    layer.features.truncate(1);
+    if layer.features.is_empty() {
+        layer.features.push(Feature::default());
+    }
+    // Only the geometry allocation is reused, the rest is reset.
+    let feature = &mut layer.features[0];
+    feature.id = None;
+    feature.tags.clear();
+    if !buffers.encoder.encode(geometry, feature) {
+        layer.features.clear();
+    }
+}
+
+/// Scratch buffers, reusable across renderings.
+#[derive(Debug, Default)]
+pub struct Buffers {
+    /// Projected polygons.
+    polygons: Vec<Polygon>,
+    /// Clipping buffers.
+    clip: clip::Buffers,
+    /// MVT geometry encoder.
+    encoder: mvt::Encoder,
 }
 
 /// Convert the given MVT layer into a Geometry object.
 #[expect(clippy::unimplemented, reason = "it's ok, this is for test only")]
 #[cfg(test)]
-pub fn layer_to_geometry(tile_id: TileID, layer: &Layer) -> Option<Geometry> {
+pub fn layer_to_geometry(
+    tile_id: TileID,
+    layer: &Layer,
+) -> Option<geo::Geometry> {
     // No feature, no geometry.
     if layer.features.is_empty() {
         return None;
@@ -148,10 +318,10 @@ pub fn layer_to_geometry(tile_id: TileID, layer: &Layer) -> Option<Geometry> {
         .expect("read MVT geometry");
     geo_writer.take_geometry().map(|mut geometry| {
         match geometry {
-            Geometry::Polygon(ref mut polygon) => {
+            geo::Geometry::Polygon(ref mut polygon) => {
                 project_polygon_into_epgs4326(polygon, tile_id);
             }
-            Geometry::MultiPolygon(ref mut multipolygon) => {
+            geo::Geometry::MultiPolygon(ref mut multipolygon) => {
                 for polygon in multipolygon {
                     project_polygon_into_epgs4326(polygon, tile_id);
                 }
@@ -165,16 +335,18 @@ pub fn layer_to_geometry(tile_id: TileID, layer: &Layer) -> Option<Geometry> {
 
 // -----------------------------------------------------------------------------
 
+/// Tests if the ring crosses the antimeridian.
+pub fn is_transmeridian(ring: &LineString<f64>) -> bool {
+    ring.lines()
+        .any(|line| (line.start.x - line.end.x).abs() > 180.)
+}
+
 /// Fix shape crossing the antimeridian.
 ///
 /// The shape need to be translated to the east or west, depending on the tile
 /// we want to render.
-fn fix_transmeridian(tile_id: TileID, ring: &mut LineString<f64>) {
-    let is_transmeridian = ring
-        .lines()
-        .any(|line| (line.start.x - line.end.x).abs() > 180.);
-
-    if is_transmeridian {
+pub fn fix_transmeridian(tile_id: TileID, ring: &mut LineString<f64>) {
+    if is_transmeridian(ring) {
         if tile_id.is_eastern() {
             for coord in ring.coords_mut() {
                 coord.x += f64::from(u8::from(coord.x < 0.)) * 360.;
@@ -210,6 +382,64 @@ fn project_polygon_into_grid(polygon: &mut Polygon, tile_id: TileID) {
     });
 }
 
+/// Projects the polygon, in EPSG:4326 coordinates, into world coordinates
+/// (see [`TileCoord::world`]), on the same side of the antimeridian as the
+/// tile.
+///
+/// Tiles then only need a scale and a translation, which preserves the edges:
+/// shapes can be clipped in world coordinates, around a tile, without
+/// changing the result of the rendering.
+pub fn project_polygon_into_world(polygon: &mut Polygon, tile_id: TileID) {
+    polygon.exterior_mut(|ring| {
+        fix_transmeridian(tile_id, ring);
+        for coord in ring.coords_mut() {
+            *coord = TileCoord::world(*coord);
+        }
+    });
+    polygon.interiors_mut(|interiors| {
+        for ring in interiors {
+            fix_transmeridian(tile_id, ring);
+            for coord in ring.coords_mut() {
+                *coord = TileCoord::world(*coord);
+            }
+        }
+    });
+}
+
+/// Reprojects the polygon, in world coordinates, into the tile.
+///
+/// This gives the same coordinates as [`project_polygon_into_grid`], bit for
+/// bit.
+fn project_world_polygon_into_grid(polygon: &mut Polygon, tile_id: TileID) {
+    let zoom = tile_id.zoom();
+    polygon.map_coords_in_place(|coord| {
+        TileCoord::from_world(coord, zoom).project(tile_id)
+    });
+}
+
+/// Clips a shape, in world coordinates, around the given tile.
+///
+/// The clipping box is twice as large as the tile buffer: the new edges are
+/// never rendered, and as the edges kept are exactly the original ones, the
+/// final clipping (done in tile coordinates) gives the same result.
+pub fn clip_around<'a>(
+    polygons: impl IntoIterator<Item = &'a Polygon>,
+    tile_id: TileID,
+) -> MultiPolygon {
+    let bbox = tile_id.world_bbox(2 * TileID::buffer());
+    let candidates = polygons
+        .into_iter()
+        .filter(|polygon| {
+            polygon
+                .bounding_rect()
+                .is_some_and(|rect| rect.intersects(&bbox))
+        })
+        .cloned()
+        .collect::<MultiPolygon>();
+
+    clip::clip(&candidates, bbox)
+}
+
 /// Convert a polygon in the given tile to the EPSG:4326 coordinate system.
 #[cfg(test)]
 fn project_polygon_into_epgs4326(polygon: &mut Polygon, tile_id: TileID) {
@@ -239,6 +469,54 @@ fn polygon_is_visible(polygon: &Polygon) -> bool {
     tile.intersects(&bbox)
 }
 
+/// Puts the shape in a canonical form, to get the same encoding regardless of
+/// the input order.
+///
+/// Every ring starts from its smallest vertex, and holes and polygons are
+/// sorted.
+pub fn canonicalize(shape: &mut MultiPolygon) {
+    fn rotate(ring: &mut LineString) {
+        // Remove the closing vertex, it's restored after the rotation.
+        ring.0.pop();
+        let start = ring
+            .0
+            .iter()
+            .enumerate()
+            .min_by(|(_, lhs), (_, rhs)| compare_coords(lhs, rhs))
+            .map_or(0, |(index, _)| index);
+        ring.0.rotate_left(start);
+        ring.close();
+    }
+
+    for polygon in &mut shape.0 {
+        polygon.exterior_mut(rotate);
+        polygon.interiors_mut(|interiors| {
+            for ring in interiors.iter_mut() {
+                rotate(ring);
+            }
+            interiors.sort_unstable_by(compare_rings);
+        });
+    }
+    shape.0.sort_unstable_by(|lhs, rhs| {
+        compare_rings(lhs.exterior(), rhs.exterior())
+    });
+}
+
+/// Orders rings lexicographically.
+fn compare_rings(lhs: &LineString, rhs: &LineString) -> Ordering {
+    lhs.0
+        .iter()
+        .zip(&rhs.0)
+        .map(|(lhs, rhs)| compare_coords(lhs, rhs))
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or_else(|| lhs.0.len().cmp(&rhs.0.len()))
+}
+
+/// Orders coordinates by Y, then by X.
+fn compare_coords(lhs: &Coord, rhs: &Coord) -> Ordering {
+    lhs.y.total_cmp(&rhs.y).then(lhs.x.total_cmp(&rhs.x))
+}
+
 fn carve_out_from_tile(shape: MultiPolygon) -> MultiPolygon {
     // Compute the exterior of the carved out shape.
     // If there are no shape, easy: it's the tile shape.
@@ -331,12 +609,8 @@ impl CellBoundary {
 impl From<CellIndex> for CellBoundary {
     fn from(value: CellIndex) -> Self {
         let boundary = Polygon::new(value.boundary().into(), Vec::new());
-        let is_transmeridian = boundary
-            .exterior()
-            .lines()
-            .any(|line| (line.start.x - line.end.x).abs() > 180.);
 
-        if is_transmeridian {
+        if is_transmeridian(boundary.exterior()) {
             let mut fixed_east = boundary.clone();
             fixed_east.exterior_mut(|exterior| {
                 for coord in exterior.coords_mut() {
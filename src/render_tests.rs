@@ -1,14 +1,12 @@
 #![expect(clippy::panic, reason = "unit tests, this is fine")]
 
 use super::*;
+use crate::fixtures::assert_golden_tiles;
 use ahash::HashMap;
-use float_eq::{assert_float_eq, float_eq};
-use geo::{Geometry, LineString, Polygon, polygon};
-use geojson::{Feature, FeatureCollection, feature::Id as FeatureId};
-use std::{
-    fs,
-    path::{Path, PathBuf},
-};
+use float_eq::assert_float_eq;
+use geo::{Area, BooleanOps, LineString, Validation, polygon};
+use geozero::mvt::Message as _;
+use h3o::geom::SolventBuilder;
 
 #[test]
 fn culling_outside() {
@@ -210,25 +208,26 @@ fn tiles_for_cell_antimeridian() {
     assert_eq!(result, expected);
 }
 
+#[test]
+fn render_cells_antimeridian_low_zoom() {
+    let cells = antimeridian_cells();
+    // At zoom 1, the eastern tile starts at the prime meridian.
+    let east = TileID::new_unchecked(1, 1, 1);
+    let west = TileID::new_unchecked(0, 1, 1);
+
+    for (tile_id, edge) in [(east, 4096.), (west, 0.)] {
+        let geometry = render_geometry(tile_id, cells.iter().copied(), false)
+            .expect("render geometry");
+
+        let bbox = geometry.bounding_rect().expect("non-empty tile");
+        assert!((bbox.min().x - edge).abs() < 1., "{tile_id:?}");
+    }
+}
+
 #[ignore = "https://github.com/georust/geozero/issues/218"]
 #[test]
 fn render_cells_antimeridian() {
-    let cells = vec![
-        0x8a9b4361e0effff,
-        0x8a9b4361e0c7fff,
-        0x8a9b4361e0d7fff,
-        0x8a9b4361e72ffff,
-        0x8a9b4361e777fff,
-        0x8a9b4361e767fff,
-        0x8a9b4361e0cffff,
-        0x8a9b4361e05ffff,
-        0x8a9b4361e70ffff,
-        0x8a9b4361e71ffff,
-    ]
-    .into_iter()
-    .map(CellIndex::try_from)
-    .collect::<Result<Vec<_>, _>>()
-    .expect("valid cell indexes");
+    let cells = antimeridian_cells();
 
     let tiles: HashMap<u8, HashSet<TileID>> = cells
         .iter()
@@ -242,183 +241,265 @@ fn render_cells_antimeridian() {
     test_rendering(&cells, &tiles, "antimeridian", true);
 }
 
-// -----------------------------------------------------------------------------
-
-// Test MVT tile rendering against the golden files.
-fn test_rendering(
-    cells: &[CellIndex],
-    tiles: &HashMap<u8, HashSet<TileID>>,
-    testcase: &'static str,
-    scratch: bool,
-) {
-    let basedir: PathBuf = [env!("CARGO_MANIFEST_DIR"), "testdata", testcase]
+#[test]
+fn clip_antimeridian() {
+    let cells = antimeridian_cells();
+    let shape = SolventBuilder::new()
+        .build()
+        .dissolve(cells.iter().copied())
+        .expect("dissolved shape");
+    let tiles = cells
         .iter()
-        .collect();
-    // If this variable is set, we update the golden files.
-    // If not, we compare the render output against those golden files.
-    let update_mode = std::env::var_os("UPDATE_GOLDEN_FILE").is_some();
-
-    for (zoom, tile_ids) in tiles {
-        // Render the requested tiles, skipping the empty ones.
-        let geometries = render_tiles_to_geometries(cells, tile_ids, scratch);
-
-        // Build the corresponding filename.
-        let suffix = if scratch { "hollow" } else { "filled" };
-        let filename = PathBuf::from(format!("{zoom}-{suffix}.json"));
-        let filepath = [basedir.clone(), filename].iter().collect::<PathBuf>();
-
-        // In update mode, save the result as a GeoJSON FeatureCollection.
-        if update_mode {
-            return save_golden_file(&filepath, geometries);
+        .flat_map(|&cell| tiles_for_cell(cell, 15..=19))
+        .collect::<HashSet<_>>();
+    let bbox = TileID::buffered_shape();
+
+    for tile_id in tiles {
+        let geometry = MultiPolygon::new(
+            shape
+                .iter()
+                .cloned()
+                .map(|mut polygon| {
+                    project_polygon_into_grid(&mut polygon, tile_id);
+                    polygon
+                })
+                .filter(polygon_is_visible)
+                .collect(),
+        );
+        let carved = carve_out_from_tile(geometry.clone());
+
+        for geometry in [geometry, carved] {
+            let result = clip::clip(&geometry, bbox);
+            let expected = geometry
+                .intersection(&MultiPolygon::new(vec![bbox.to_polygon()]));
+
+            assert_eq!(result.0.len(), expected.0.len(), "{tile_id:?}");
+            assert_float_eq!(
+                result.unsigned_area(),
+                expected.unsigned_area(),
+                r2nd <= 1e-6,
+                "{tile_id:?}"
+            );
         }
+    }
+}
 
-        // Otherwise, check the geometries against the correspondin golden file.
-        let references = load_golden_file(&filepath);
-        for (tile_name, result) in geometries {
-            let expected = references[&tile_name].clone();
-            assert_geometry_equals(&result, &expected);
+#[test]
+fn deterministic() {
+    let center = CellIndex::try_from(0x891fb46622fffff).expect("cell");
+    // Holes, islands and scattered cells.
+    let cells = center
+        .grid_disk::<Vec<_>>(8)
+        .into_iter()
+        .filter(|&cell| {
+            let distance = center.grid_distance(cell).expect("distance");
+            distance % 3 != 1 && u64::from(cell) % 5 != 0
+        })
+        .collect::<Vec<_>>();
+    let mut sorted = cells.clone();
+    sorted.sort_unstable();
+    let mut reversed = sorted.clone();
+    reversed.reverse();
+    let (even, odd): (Vec<_>, Vec<_>) =
+        sorted.iter().partition(|&&cell| u64::from(cell) % 2 == 0);
+    let interleaved = odd.into_iter().chain(even).collect::<Vec<_>>();
+    let hashed = cells.iter().copied().collect::<HashSet<_>>();
+    let tiles = cells
+        .iter()
+        .flat_map(|&cell| tiles_for_cell(cell, 12..=15))
+        .collect::<HashSet<_>>();
+
+    for scratch in [false, true] {
+        for &tile_id in &tiles {
+            let encode = |cells: Vec<CellIndex>| {
+                let layer = render(tile_id, cells, "test".to_owned(), scratch)
+                    .expect("render");
+                geozero::mvt::Tile {
+                    layers: vec![layer],
+                }
+                .encode_to_vec()
+            };
+            let expected = encode(sorted.clone());
+
+            assert_eq!(encode(reversed.clone()), expected, "{tile_id:?}");
+            assert_eq!(encode(interleaved.clone()), expected, "{tile_id:?}");
+            assert_eq!(
+                encode(hashed.iter().copied().collect()),
+                expected,
+                "{tile_id:?}"
+            );
         }
     }
 }
 
-// Render the specified tiles into geometries, skip empty ones.
-fn render_tiles_to_geometries(
-    cells: &[CellIndex],
-    tile_ids: &HashSet<TileID>,
-    scratch: bool,
-) -> HashMap<String, Geometry> {
-    tile_ids
+#[test]
+fn valid_geometry() {
+    let center = CellIndex::try_from(0x891fb46622fffff).expect("cell");
+    let cells = center
+        .grid_disk::<Vec<_>>(8)
+        .into_iter()
+        .filter(|&cell| u64::from(cell) % 3 != 0)
+        .collect::<Vec<_>>();
+    let tiles = cells
         .iter()
-        .filter_map(|&tile_id| {
+        .flat_map(|&cell| tiles_for_cell(cell, 5..=16))
+        .collect::<HashSet<_>>();
+
+    for scratch in [false, true] {
+        for &tile_id in &tiles {
             let layer = render(
                 tile_id,
                 cells.iter().copied(),
                 "test".to_owned(),
                 scratch,
             )
-            .unwrap_or_else(|err| {
-                panic!("tile rendering failed for {tile_id:?}: {err}")
-            });
-
-            layer_to_geometry(tile_id, &layer).map(|geometry| {
-                let ((x, y), z) = (tile_id.xy(), tile_id.zoom());
-                let tile_name = format!("{z}/{x}/{y}");
+            .expect("render");
+            let Some(feature) = layer.features.first() else {
+                continue;
+            };
+            let mut writer = geozero::geo_types::GeoWriter::new();
+            geozero::mvt::process_geom(feature, &mut writer)
+                .expect("MVT geometry");
+            let geometry = writer.take_geometry().expect("geometry");
 
-                (tile_name, geometry)
-            })
-        })
-        .collect()
+            assert!(geometry.is_valid(), "{tile_id:?}/{scratch}");
+        }
+    }
 }
 
-// Persists the specified geometries on disk.
-fn save_golden_file(filepath: &Path, geometries: HashMap<String, Geometry>) {
-    let feature_collection = FeatureCollection {
-        bbox: None,
-        features: geometries
-            .into_iter()
-            .map(|(tile_name, geometry)| Feature {
-                bbox: None,
-                geometry: Some((&geometry).into()),
-                id: Some(FeatureId::String(tile_name)),
-                properties: None,
-                foreign_members: None,
-            })
-            .collect(),
-        foreign_members: None,
-    };
-
-    let payload = feature_collection.to_string();
-    fs::write(filepath, payload)
-        .unwrap_or_else(|err| panic!("writing {}: {err}", filepath.display()));
+#[test]
+fn canonical_shape() {
+    let tile_id = TileID::new(0, 0, 2).expect("tile");
+    let first = polygon![
+        exterior: [
+            (x: -170., y: 80.),
+            (x: -100., y: 80.),
+            (x: -100., y: 70.),
+            (x: -170., y: 70.),
+        ],
+        interiors: [
+            [
+                (x: -160., y: 78.),
+                (x: -150., y: 78.),
+                (x: -150., y: 75.),
+            ],
+            [
+                (x: -120., y: 78.),
+                (x: -110., y: 78.),
+                (x: -110., y: 75.),
+            ],
+        ],
+    ];
+    let second = polygon![
+        (x: -170., y: 60.),
+        (x: -100., y: 60.),
+        (x: -100., y: 50.),
+        (x: -170., y: 50.),
+    ];
+    // Same shape, with rings starting elsewhere and listed in another order.
+    let mut shuffled = first.clone();
+    shuffled.exterior_mut(|ring| {
+        ring.0.pop();
+        ring.0.rotate_left(2);
+        ring.close();
+    });
+    shuffled.interiors_mut(<[LineString]>::reverse);
+
+    for scratch in [false, true] {
+        let expected = render_shape(
+            tile_id,
+            Some(MultiPolygon::new(vec![first.clone(), second.clone()])),
+            "test".to_owned(),
+            scratch,
+        );
+        let result = render_shape(
+            tile_id,
+            Some(MultiPolygon::new(vec![second.clone(), shuffled.clone()])),
+            "test".to_owned(),
+            scratch,
+        );
+
+        assert!(!expected.features.is_empty());
+        assert_eq!(result, expected);
+    }
 }
 
-// Load geometries from a golden file.
-fn load_golden_file(filepath: &Path) -> HashMap<String, Geometry> {
-    let payload = fs::read_to_string(filepath)
-        .unwrap_or_else(|err| panic!("reading {}: {err}", filepath.display()));
-    let feature_collection = payload
-        .parse::<FeatureCollection>()
-        .unwrap_or_else(|err| panic!("parsing {}: {err}", filepath.display()));
-    feature_collection
-        .features
+#[test]
+fn geometry_stage() {
+    let center = CellIndex::try_from(0x891fb46622fffff).expect("cell");
+    let cells = center.grid_disk::<Vec<_>>(8);
+    let tiles = tiles_for_cell(center, 10..=16)
         .into_iter()
-        .map(|feature| {
-            let Some(FeatureId::String(tile_name)) = feature.id else {
-                panic!(
-                    "invalid or missing feature ID in {}",
-                    filepath.display()
-                )
-            };
+        .chain([TileID::new(0, 0, 16).expect("tile")])
+        .collect::<Vec<_>>();
+    let bbox = TileID::buffered_shape();
 
-            let geometry: Geometry = feature
-                .geometry
-                .unwrap_or_else(|| panic!("missing geometry in {tile_name}"))
-                .try_into()
-                .unwrap_or_else(|err| {
-                    panic!("invalid geometry in {tile_name}: {err}")
-                });
-
-            (tile_name, geometry)
-        })
-        .collect()
-}
-
-fn assert_geometry_equals(lhs: &Geometry, rhs: &Geometry) {
-    match (lhs, rhs) {
-        (Geometry::MultiPolygon(lhs), Geometry::MultiPolygon(rhs)) => {
-            assert_eq!(lhs.0.len(), rhs.0.len(), "polygon count");
-            for (lhs, rhs) in lhs.0.iter().zip(rhs.0.iter()) {
-                assert_polygon_equals(lhs, rhs);
-            }
-        }
-        (Geometry::Polygon(lhs), Geometry::Polygon(rhs)) => {
-            assert_polygon_equals(lhs, rhs);
+    for scratch in [false, true] {
+        for tile_id in tiles.clone() {
+            let expected = render(
+                tile_id,
+                cells.iter().copied(),
+                "test".to_owned(),
+                scratch,
+            )
+            .expect("render");
+
+            let geometry =
+                render_geometry(tile_id, cells.iter().copied(), scratch)
+                    .expect("render geometry");
+            let result = encode_layer(&geometry, "test");
+
+            assert!(
+                geometry.coords_iter().all(|coord| bbox.intersects(&coord)),
+                "{tile_id:?}/{scratch}"
+            );
+            assert_eq!(result, expected, "{tile_id:?}/{scratch}");
         }
-        _ => panic!("unexpected geometry types"),
-    }
-}
-
-fn assert_polygon_equals(lhs: &Polygon, rhs: &Polygon) {
-    assert_linestring_equals(lhs.exterior(), rhs.exterior());
-    assert_eq!(lhs.interiors().len(), rhs.interiors().len(), "hole count");
-    // TODO: be order-agnostic?
-    for (lhs, rhs) in lhs.interiors().iter().zip(rhs.interiors().iter()) {
-        assert_linestring_equals(lhs, rhs);
     }
 }
 
-/// Assert that two `LineString` are equivalent.
-///
-/// `LineString` are equivalent if they contains the same point in the same order
-/// (but they don't necessarily start at the same point).
-fn assert_linestring_equals(lhs: &LineString, rhs: &LineString) {
-    // Based on https://gis.stackexchange.com/a/8674
-    const EPSILON: f64 = 1e-5;
-
-    let lhs_is_closed = lhs.is_closed();
-    let mut lhs = lhs.coords().collect::<Vec<_>>();
-    if lhs_is_closed {
-        lhs.pop(); // Remove the duplicated coord that close the ring.
-    }
+// -----------------------------------------------------------------------------
 
-    let rhs_is_closed = rhs.is_closed();
-    let mut rhs = rhs.coords().collect::<Vec<_>>();
-    if rhs_is_closed {
-        rhs.pop(); // Remove the duplicated coord that close the ring
-    }
+fn antimeridian_cells() -> Vec<CellIndex> {
+    [
+        0x8a9b4361e0effff,
+        0x8a9b4361e0c7fff,
+        0x8a9b4361e0d7fff,
+        0x8a9b4361e72ffff,
+        0x8a9b4361e777fff,
+        0x8a9b4361e767fff,
+        0x8a9b4361e0cffff,
+        0x8a9b4361e05ffff,
+        0x8a9b4361e70ffff,
+        0x8a9b4361e71ffff,
+    ]
+    .into_iter()
+    .map(CellIndex::try_from)
+    .collect::<Result<Vec<_>, _>>()
+    .expect("valid cell indexes")
+}
 
-    assert_eq!(lhs.len(), rhs.len(), "linestring size mismatch");
-    let offset = rhs
-        .iter()
-        .position(|&coord| {
-            float_eq!(coord.x, lhs[0].x, abs <= EPSILON)
-                && float_eq!(coord.y, lhs[0].y, abs <= EPSILON)
-        })
-        .expect("linestring are different");
-    for (i, lhs) in lhs.iter().enumerate() {
-        let j = (i + offset) % rhs.len();
+// Test MVT tile rendering against the golden files.
+fn test_rendering(
+    cells: &[CellIndex],
+    tiles: &HashMap<u8, HashSet<TileID>>,
+    testcase: &'static str,
+    scratch: bool,
+) {
+    for (&zoom, tile_ids) in tiles {
+        let layers = tile_ids.iter().map(|&tile_id| {
+            let layer = render(
+                tile_id,
+                cells.iter().copied(),
+                "test".to_owned(),
+                scratch,
+            )
+            .unwrap_or_else(|err| {
+                panic!("tile rendering failed for {tile_id:?}: {err}")
+            });
+            (tile_id, layer)
+        });
 
-        assert_float_eq!(lhs.x, rhs[j].x, abs <= EPSILON);
-        assert_float_eq!(lhs.y, rhs[j].y, abs <= EPSILON);
+        assert_golden_tiles(testcase, zoom, scratch, layers);
     }
 }
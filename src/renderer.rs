@@ -0,0 +1,193 @@
+use crate::{
+    Limits, RenderingError, TileEncoder, TileFeature, TileID,
+    render::{Buffers, encode_into, shape_into_tile},
+};
+use geo::MultiPolygon;
+use geozero::mvt::{Message as _, tile::Layer};
+use h3o::{
+    CellIndex,
+    geom::{Solvent, SolventBuilder},
+};
+
+/// Field tag and wire type of a layer, in an encoded MVT tile.
+const LAYER_KEY: u8 = (3 << 3) | 2;
+
+/// A reusable renderer, for high-throughput serving.
+///
+/// The renderer keeps its solvent and scratch buffers between calls, and
+/// renders into a caller-provided layer or byte buffer, which saves most of
+/// the allocations done by [`render`](crate::render). The output is the same.
+///
+/// It is meant to be owned by a worker, and reused for every tile it renders.
+#[derive(Debug)]
+pub struct Renderer {
+    /// Name of the rendered layers.
+    name: String,
+    /// Whether the shape is scratched off the tiles.
+    scratch: bool,
+    /// Limits applied to each rendering.
+    limits: Limits,
+    /// Solvent used to compute the outlines of the cells.
+    solvent: Solvent,
+    /// Input cells, counted before being dissolved when there is a limit.
+    cells: Vec<CellIndex>,
+    /// Scratch buffers.
+    buffers: Buffers,
+    /// Layer reused when rendering into bytes.
+    layer: Layer,
+}
+
+impl Renderer {
+    /// Initializes a new renderer.
+    #[must_use]
+    pub fn new(name: String, scratch: bool) -> Self {
+        Self {
+            name,
+            scratch,
+            limits: Limits::new(),
+            solvent: SolventBuilder::new().build(),
+            cells: Vec::new(),
+            buffers: Buffers::default(),
+            layer: Layer::default(),
+        }
+    }
+
+    /// Sets the limits applied to the next renderings.
+    ///
+    /// As they usually depend on the request (e.g. deadline), they can be
+    /// updated before each rendering.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Render the given cells into the specified tile, replacing the content
+    /// of `layer`.
+    ///
+    /// The allocations of the layer are reused. On error, it's left untouched.
+    ///
+    /// # Errors
+    ///
+    /// All cell indexes must be unique and have the same resolution, otherwise
+    /// a `RenderingError::InvalidInput` is returned.
+    ///
+    /// A `RenderingError::Aborted` is returned if a limit is exceeded.
+    pub fn render_into(
+        &mut self,
+        tile_id: TileID,
+        cells: impl IntoIterator<Item = CellIndex>,
+        layer: &mut Layer,
+    ) -> Result<(), RenderingError> {
+        let geometry = self.render_geometry(tile_id, cells)?;
+        self.limits.check()?;
+        encode_into(&geometry, &mut self.buffers, layer);
+        layer.name.clone_from(&self.name);
+
+        Ok(())
+    }
+
+    /// Render the given cells into the specified tile, as polygons in tile
+    /// coordinates.
+    ///
+    /// This is the geometry encoded by [`Renderer::render_into`], see
+    /// [`render_geometry`](crate::render_geometry) for details.
+    ///
+    /// # Errors
+    ///
+    /// All cell indexes must be unique and have the same resolution, otherwise
+    /// a `RenderingError::InvalidInput` is returned.
+    ///
+    /// A `RenderingError::Aborted` is returned if a limit is exceeded.
+    pub fn render_geometry(
+        &mut self,
+        tile_id: TileID,
+        cells: impl IntoIterator<Item = CellIndex>,
+    ) -> Result<MultiPolygon, RenderingError> {
+        self.limits.check()?;
+        let geometry = if let Some(max) = self.limits.cell_limit() {
+            // Count the cells before dissolving them, and stop reading the
+            // input as soon as the limit is exceeded.
+            self.cells.clear();
+            self.cells.extend(cells.into_iter().take(max + 1));
+            self.limits.check_cells(self.cells.len())?;
+            self.solvent.dissolve(self.cells.iter().copied())
+        } else {
+            self.solvent.dissolve(cells)
+        }
+        .map_err(RenderingError::InvalidInput)?;
+        let geometry = (!geometry.0.is_empty()).then_some(geometry);
+
+        shape_into_tile(
+            tile_id,
+            geometry,
+            self.scratch,
+            &self.limits,
+            &mut self.buffers,
+        )
+    }
+
+    /// Render the given cells into the specified tile, using a custom
+    /// encoder.
+    ///
+    /// The rendered geometry is given to the encoder as a single feature,
+    /// without properties, or no feature at all if the tile is empty.
+    ///
+    /// # Errors
+    ///
+    /// All cell indexes must be unique and have the same resolution, otherwise
+    /// a `RenderingError::InvalidInput` is returned.
+    ///
+    /// A `RenderingError::Aborted` is returned if a limit is exceeded.
+    pub fn render_with<E: TileEncoder>(
+        &mut self,
+        tile_id: TileID,
+        cells: impl IntoIterator<Item = CellIndex>,
+        encoder: &mut E,
+    ) -> Result<E::Output, RenderingError> {
+        let geometry = self.render_geometry(tile_id, cells)?;
+        self.limits.check()?;
+        let features = if geometry.0.is_empty() {
+            &[][..]
+        } else {
+            &[TileFeature::new(&geometry)][..]
+        };
+
+        Ok(encoder.encode(&self.name, features))
+    }
+
+    /// Render the given cells into the specified tile, appending the encoded
+    /// MVT layer to `buffer`.
+    ///
+    /// The layer is written as a field of an MVT tile, even when it's empty:
+    /// once cleared, `buffer` contains a single-layer tile, and appending
+    /// the output of several renderers (with distinct names) yields a
+    /// multi-layer tile.
+    ///
+    /// # Errors
+    ///
+    /// All cell indexes must be unique and have the same resolution, otherwise
+    /// a `RenderingError::InvalidInput` is returned.
+    ///
+    /// A `RenderingError::Aborted` is returned if a limit is exceeded.
+    pub fn render_to_bytes(
+        &mut self,
+        tile_id: TileID,
+        cells: impl IntoIterator<Item = CellIndex>,
+        buffer: &mut Vec<u8>,
+    ) -> Result<(), RenderingError> {
+        let mut layer = std::mem::take(&mut self.layer);
+        let result = self.render_into(tile_id, cells, &mut layer);
+        if result.is_ok() {
+            buffer.push(LAYER_KEY);
+            layer
+                .encode_length_delimited(buffer)
+                .expect("vector grows as needed");
+        }
+        self.layer = layer;
+
+        result
+    }
+}
+
+#[cfg(test)]
+#[path = "./renderer_tests.rs"]
+mod tests;
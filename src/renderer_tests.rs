@@ -0,0 +1,176 @@
+use super::*;
+use crate::{
+    fixtures::{disk, tiles},
+    render,
+    render::render_shape,
+    render_geometry, validate_layer,
+};
+use geozero::mvt::{
+    Tile,
+    tile::{Feature, Value},
+};
+use h3o::Resolution;
+
+/// Renders a tile the way `render` did before `Renderer`: a fresh solvent
+/// and fresh buffers for every tile.
+fn baseline(tile_id: TileID, cells: &[CellIndex], scratch: bool) -> Layer {
+    let geometry = SolventBuilder::new()
+        .build()
+        .dissolve(cells.iter().copied())
+        .expect("dissolve");
+    let geometry = (!geometry.0.is_empty()).then_some(geometry);
+
+    render_shape(tile_id, geometry, "test".to_owned(), scratch)
+}
+
+#[test]
+fn same_as_baseline() {
+    let cells = disk();
+    // Tiles of different zoom levels, in and out of the dataset.
+    let tile_ids = tiles(&cells, 15)
+        .into_iter()
+        .chain(tiles(&cells, 12))
+        .chain([TileID::new(0, 0, 15).expect("tile")])
+        .collect::<Vec<_>>();
+
+    for scratch in [false, true] {
+        // A single renderer and layer, reused for every tile.
+        let mut renderer = Renderer::new("test".to_owned(), scratch);
+        let mut layer = Layer::default();
+        for &tile_id in &tile_ids {
+            let expected = baseline(tile_id, &cells, scratch);
+
+            renderer
+                .render_into(tile_id, cells.iter().copied(), &mut layer)
+                .expect("render into layer");
+
+            assert_eq!(layer, expected, "{tile_id:?}/{scratch}");
+        }
+    }
+}
+
+#[test]
+fn reused_layer() {
+    let cells = disk();
+    let tile_id = tiles(&cells, 14).into_iter().min().expect("tile");
+    let expected =
+        render(tile_id, cells.iter().copied(), "test".to_owned(), false)
+            .expect("render");
+    let mut renderer = Renderer::new("test".to_owned(), false);
+    // A layer left over by another encoder: tagged features, keys, values.
+    let mut layer = Layer {
+        name: "other".to_owned(),
+        features: vec![
+            Feature {
+                id: Some(42),
+                tags: vec![0, 0],
+                r#type: Some(1),
+                geometry: vec![9, 2, 2],
+            };
+            2
+        ],
+        keys: vec!["name".to_owned()],
+        values: vec![Value {
+            string_value: Some("value".to_owned()),
+            ..Value::default()
+        }],
+        extent: Some(512),
+        version: 1,
+    };
+
+    renderer
+        .render_into(tile_id, cells.iter().copied(), &mut layer)
+        .expect("render into layer");
+
+    assert_eq!(layer, expected);
+    assert_eq!(validate_layer(&layer, 80), []);
+}
+
+#[test]
+fn geometry() {
+    let cells = disk();
+    let mut renderer = Renderer::new("test".to_owned(), true);
+
+    for tile_id in tiles(&cells, 14) {
+        let expected = render_geometry(tile_id, cells.iter().copied(), true)
+            .expect("render geometry");
+
+        let result = renderer
+            .render_geometry(tile_id, cells.iter().copied())
+            .expect("renderer geometry");
+
+        assert_eq!(result, expected, "{tile_id:?}");
+    }
+}
+
+#[test]
+fn to_bytes() {
+    let cells = disk();
+    let mut renderer = Renderer::new("test".to_owned(), false);
+    let mut buffer = Vec::new();
+
+    for tile_id in tiles(&cells, 14) {
+        let layer =
+            render(tile_id, cells.iter().copied(), "test".to_owned(), false)
+                .expect("render");
+        let expected = Tile {
+            layers: vec![layer],
+        };
+
+        buffer.clear();
+        renderer
+            .render_to_bytes(tile_id, cells.iter().copied(), &mut buffer)
+            .expect("render to bytes");
+
+        assert_eq!(buffer, expected.encode_to_vec(), "{tile_id:?}");
+    }
+}
+
+#[test]
+fn multiple_layers() {
+    let cells = disk();
+    let tile_id = tiles(&cells, 14).into_iter().min().expect("tile");
+    let mut filled = Renderer::new("filled".to_owned(), false);
+    let mut carved = Renderer::new("carved".to_owned(), true);
+    let mut buffer = Vec::new();
+
+    filled
+        .render_to_bytes(tile_id, cells.iter().copied(), &mut buffer)
+        .expect("render filled");
+    carved
+        .render_to_bytes(tile_id, cells.iter().copied(), &mut buffer)
+        .expect("render carved");
+    let tile = Tile::decode(buffer.as_slice()).expect("MVT tile");
+
+    let names = tile
+        .layers
+        .iter()
+        .map(|layer| layer.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["filled", "carved"]);
+}
+
+#[test]
+fn invalid_input() {
+    let cells = disk();
+    let tile_id = tiles(&cells, 14).into_iter().min().expect("tile");
+    let mut renderer = Renderer::new("test".to_owned(), false);
+    let mut layer = Layer::default();
+    let mut buffer = Vec::new();
+    renderer
+        .render_into(tile_id, cells.iter().copied(), &mut layer)
+        .expect("render into layer");
+    let expected = layer.clone();
+
+    let mixed = cells[0]
+        .children(Resolution::Ten)
+        .chain(cells[1..].iter().copied())
+        .collect::<Vec<_>>();
+    let result = renderer.render_into(tile_id, mixed.clone(), &mut layer);
+    assert!(matches!(result, Err(RenderingError::InvalidInput(_))));
+    assert_eq!(layer, expected);
+
+    let result = renderer.render_to_bytes(tile_id, mixed, &mut buffer);
+    assert!(matches!(result, Err(RenderingError::InvalidInput(_))));
+    assert!(buffer.is_empty());
+}
@@ -1,207 +1,250 @@
-use geo::{Contains, LineString, MultiPolygon, Polygon};
-use std::{iter::Peekable, vec, vec::Vec};
+use geo::{
+    BoundingRect, LineString, MultiPolygon, Polygon, Winding,
+    coordinate_position::{CoordPos, coord_pos_relative_to_ring},
+};
+use rstar::{
+    RTree,
+    primitives::{GeomWithData, Rectangle},
+};
+
+/// Bounding box of a ring, tagged with its index.
+type Entry = GeomWithData<Rectangle<[f64; 2]>, usize>;
 
 /// A hierarchy of non overlapping rings.
+///
+/// Rings are nested, each ring having a depth (how many rings contain it) and
+/// a parent (the innermost ring containing it).
+///
+/// # Nesting
+///
+/// The following rings:
+///
+/// ```text
+/// ┏━A━━━━━┓
+/// ┃┏━B━━━┓┃
+/// ┃┃┏━━C┓┃┃
+/// ┃┃┃┏━┓┃┃┃
+/// ┃┃┃┗D┛┃┃┃
+/// ┃┃┗━━━┛┃┃
+/// ┃┗━━━━━┛┃
+/// ┗━━━━━━━┛
+/// ```
+///
+/// Have the following depths and parents:
+///
+/// ```text
+/// ┌───┬───────┬────────┐
+/// │   │ depth │ parent │
+/// ├───┼───────┼────────┤
+/// │ A │ 0     │        │
+/// ├───┼───────┼────────┤
+/// │ B │ 1     │ A      │
+/// ├───┼───────┼────────┤
+/// │ C │ 2     │ B      │
+/// ├───┼───────┼────────┤
+/// │ D │ 3     │ C      │
+/// └───┴───────┴────────┘
+/// ```
+///
+/// By default, rings at an even depth are outer rings, and rings at an odd
+/// depth are the holes of their parent (see [`Nesting`] for the alternative).
+///
+/// The resulting polygons have a consistent orientation: counter-clockwise
+/// outer rings and clockwise holes (i.e. positive and negative signed areas,
+/// respectively).
+///
+/// # Example
+///
+/// ```
+/// use geo::{MultiPolygon, line_string};
+/// use h3o_mvt::RingHierarchy;
+///
+/// let rings = vec![
+///     line_string![(x: 2., y: 2.), (x: 4., y: 2.), (x: 4., y: 4.), (x: 2., y: 4.)],
+///     line_string![(x: 1., y: 1.), (x: 1., y: 5.), (x: 5., y: 5.), (x: 5., y: 1.)],
+/// ];
+/// let shape = MultiPolygon::from(RingHierarchy::new(rings));
+///
+/// assert_eq!(shape.0.len(), 1);
+/// assert_eq!(shape.0[0].interiors().len(), 1);
+/// ```
+#[derive(Debug, Clone)]
 pub struct RingHierarchy {
     /// Rings geometry.
     rings: Vec<LineString<f64>>,
-
-    /// Bitmap keeping track of which ring have already been assigned to a
-    /// polygon.
-    is_assigned: Vec<bool>,
-
-    /// Hierarchy matrix.
-    ///
-    /// Rows represent the `contained by` relation and columns represent the
-    /// `contains` one.
-    ///
-    /// # Example
-    ///
-    /// The following rings:
-    ///
-    /// ```text
-    // ┏━A━━━━━┓
-    // ┃┏━B━━━┓┃
-    // ┃┃┏━━C┓┃┃
-    // ┃┃┃┏━┓┃┃┃
-    // ┃┃┃┗D┛┃┃┃
-    // ┃┃┗━━━┛┃┃
-    // ┃┗━━━━━┛┃
-    // ┗━━━━━━━┛
-    /// ```
-    ///
-    /// Will generate this matrix
-    ///
-    /// ```text
-    /// ┌───┬───┬───┬───┬───┐
-    /// │   │ A │ B │ C │ D │
-    /// ├───┼───┼───┼───┼───┤
-    /// │ A │   │   │   │   │
-    /// ├───┼───┼───┼───┼───┤
-    /// │ B │ 1 │   │   │   │
-    /// ├───┼───┼───┼───┼───┤
-    /// │ C │ 1 │ 1 │   │   │
-    /// ├───┼───┼───┼───┼───┤
-    /// │ D │ 1 │ 1 │ 1 │   │
-    /// └───┴───┴───┴───┴───┘
-    /// ```
-    matrix: Vec<bool>,
+    /// Depth of each ring.
+    depths: Vec<usize>,
+    /// Whether each ring is an outer ring.
+    is_outer: Vec<bool>,
+    /// Outer ring owning each hole.
+    owners: Vec<Option<usize>>,
 }
 
 impl RingHierarchy {
-    /// Builds a new hierarchy of rings.
+    /// Builds a new hierarchy of rings, with nesting from containment.
+    #[must_use]
     pub fn new(rings: Vec<LineString<f64>>) -> Self {
-        let is_assigned = vec![false; rings.len()];
-
-        // Compute the hierarchy matrix.
-        let mut matrix = vec![false; rings.len() * rings.len()];
-        for (i, r1) in rings.iter().enumerate() {
-            for (j, r2) in rings.iter().enumerate() {
-                // One cannot contains itself.
-                if i == j {
-                    continue;
-                }
-
-                // We are guaranteed not to overlap, so just test the first
-                // point.
-                // Need to convert to Polygon to have the right `contains`
-                // algorithm.
-                if Polygon::new(r1.clone(), vec![]).contains(&r2.0[0]) {
-                    matrix[j * rings.len() + i] = true;
-                }
-            }
-        }
+        Self::with_nesting(rings, Nesting::Containment)
+    }
 
-        Self {
-            rings,
-            is_assigned,
-            matrix,
+    /// Builds a new hierarchy of rings, with the given nesting rule.
+    #[must_use]
+    pub fn with_nesting(
+        mut rings: Vec<LineString<f64>>,
+        nesting: Nesting,
+    ) -> Self {
+        for ring in &mut rings {
+            ring.close();
         }
-    }
 
-    /// Consumes the hierarchy into a stream of Polygon.
-    pub fn into_iter(mut self) -> impl Iterator<Item = Polygon<f64>> {
-        type OuterRingIterator =
-            Peekable<vec::IntoIter<(usize, LineString<f64>)>>;
-
-        // Outers ring at the current nesting level.
-        let mut outers: Option<OuterRingIterator> = None;
-
-        core::iter::from_fn(move || {
-            // If the current layer is exhausted, peel the next one.
-            if outers.as_mut().is_none_or(|rings| rings.peek().is_none()) {
-                outers = self
-                    .peel_outers()
-                    .map(|rings| rings.into_iter().peekable());
+        let index = RTree::bulk_load(
+            rings
+                .iter()
+                .enumerate()
+                .filter_map(|(id, ring)| {
+                    let bbox = ring.bounding_rect()?;
+                    let rect = Rectangle::from_corners(
+                        bbox.min().into(),
+                        bbox.max().into(),
+                    );
+                    Some(Entry::new(rect, id))
+                })
+                .collect(),
+        );
+
+        // Containers of each ring, stored end to end.
+        let mut containers = Vec::new();
+        let mut ranges = Vec::with_capacity(rings.len());
+        for (id, ring) in rings.iter().enumerate() {
+            let start = containers.len();
+            // We are guaranteed not to overlap, so just test the first point.
+            if let Some(&point) = ring.0.first() {
+                containers.extend(
+                    index
+                        .locate_all_at_point(&point.into())
+                        .map(|entry| entry.data)
+                        .filter(|&other| {
+                            // One cannot contains itself.
+                            other != id
+                                && coord_pos_relative_to_ring(
+                                    point,
+                                    &rings[other],
+                                ) == CoordPos::Inside
+                        }),
+                );
             }
+            ranges.push(start..containers.len());
+        }
 
-            outers.as_mut().map(|outers| {
-                let (id, outer) = outers.next().expect("peeked above");
-                let inners = self.inners(id);
+        let depths = ranges
+            .iter()
+            .map(ExactSizeIterator::len)
+            .collect::<Vec<_>>();
+        // Nested rings form a chain: the deepest container is the parent.
+        let parents = ranges
+            .into_iter()
+            .map(|range| {
+                containers[range]
+                    .iter()
+                    .copied()
+                    .max_by_key(|&id| depths[id])
+            })
+            .collect::<Vec<_>>();
 
-                // Mark the outer as assigned.
-                self.is_assigned[id] = true;
+        let (is_outer, owners) = match nesting {
+            Nesting::Containment => (
+                depths.iter().map(|depth| depth.is_multiple_of(2)).collect(),
+                parents,
+            ),
+            Nesting::Winding => {
+                let is_outer =
+                    rings.iter().map(Winding::is_ccw).collect::<Vec<_>>();
+                // Holes belong to the innermost outer ring containing them,
+                // degenerate rings (neither clockwise nor counter-clockwise)
+                // to none.
+                let owners = parents
+                    .iter()
+                    .zip(&rings)
+                    .map(|(&parent, ring)| {
+                        std::iter::successors(parent, |&id| parents[id])
+                            .find(|&id| is_outer[id])
+                            .filter(|_| ring.is_cw())
+                    })
+                    .collect();
+                (is_outer, owners)
+            }
+        };
 
-                Polygon::new(outer, inners)
-            })
-        })
+        Self {
+            rings,
+            depths,
+            is_outer,
+            owners,
+        }
     }
 
-    /// Peels one layer of outer rings and return them.
-    ///
-    /// An outer ring is a ring that is not contained by any other ring.
+    /// Consumes the hierarchy into a stream of Polygon.
     ///
-    /// Note that in case of nested polygons, you may need to call this function
-    /// several time to extract outer rings at every nesting level.
-    fn peel_outers(&mut self) -> Option<Vec<(usize, LineString<f64>)>> {
-        #[expect(
-            clippy::filter_map_bool_then,
-            reason = "borrow issue with filter+map"
-        )]
-        let outers = (0..self.rings.len())
-            .filter_map(|i| {
-                // Skip assigned rings and non-outer ones.
-                (!self.is_assigned[i] && self.is_outer(i)).then(|| {
-                    // Extract the ring in place to preserve `rings` size and
-                    // ordering.
-                    let ring = core::mem::replace(
-                        &mut self.rings[i],
-                        LineString(vec![]),
-                    );
-                    (i, ring)
-                })
-            })
+    /// Polygons are sorted by nesting level, then by the position of their
+    /// outer ring in the input. Holes are sorted by their position in the
+    /// input.
+    pub fn into_polygons(mut self) -> impl Iterator<Item = Polygon<f64>> {
+        let mut outers = (0..self.rings.len())
+            .filter(|&id| self.is_outer[id])
             .collect::<Vec<_>>();
+        outers.sort_by_key(|&id| self.depths[id]);
 
-        (!outers.is_empty()).then_some(outers)
-    }
-
-    /// Returns the inners of the given outer ring.
-    ///
-    /// An inner ring belongs to an outer ring if it's only contained by this
-    /// outer ring.
-    fn inners(&mut self, outer_id: usize) -> Vec<LineString<f64>> {
-        // Walk by column to find candidate and then check their parents using
-        // the row-order
-        #[expect(
-            clippy::filter_map_bool_then,
-            reason = "borrow issue with filter+map"
-        )]
-        let (ids, rings) = (0..self.rings.len())
-            .filter_map(|inner_id| {
-                (!self.is_assigned[inner_id]
-                    && self.belongs_to(inner_id, outer_id))
-                .then(|| {
-                    // Extract the ring in place to preserve `rings` size and
-                    // ordering.
-                    let ring = core::mem::replace(
-                        &mut self.rings[inner_id],
-                        LineString(vec![]),
-                    );
-                    (inner_id, ring)
+        // Holes, grouped by owner (orphan holes are dropped).
+        let mut holes = (0..self.rings.len())
+            .filter(|&id| !self.is_outer[id])
+            .filter_map(|id| Some((self.owners[id]?, id)))
+            .collect::<Vec<_>>();
+        holes.sort_unstable();
+
+        outers.into_iter().map(move |outer| {
+            let start = holes.partition_point(|&(parent, _)| parent < outer);
+            let end = holes.partition_point(|&(parent, _)| parent <= outer);
+            let inners = holes[start..end]
+                .iter()
+                .map(|&(_, id)| {
+                    let mut ring = self.take(id);
+                    ring.make_cw_winding();
+                    ring
                 })
-            })
-            .unzip::<_, _, Vec<_>, Vec<_>>();
-
-        // Mark the ring as assigned.
-        for inner_id in ids {
-            self.is_assigned[inner_id] = true;
-        }
+                .collect();
+            let mut exterior = self.take(outer);
+            exterior.make_ccw_winding();
 
-        rings
-    }
-
-    /// Tests if `outer` contains `inner`.
-    fn contains(&self, outer: usize, inner: usize) -> bool {
-        self.matrix[inner * self.rings.len() + outer]
+            Polygon::new(exterior, inners)
+        })
     }
 
-    /// Tests if the given ring is an outer ring (e.g. row `id` is all false).
-    fn is_outer(&self, id: usize) -> bool {
-        self.count_parents(id) == 0
+    /// Extracts a ring in place, to preserve `rings` size and ordering.
+    fn take(&mut self, id: usize) -> LineString<f64> {
+        std::mem::replace(&mut self.rings[id], LineString(Vec::new()))
     }
+}
 
-    /// Tests if `inner_id` belongs to to `outer_id`.
+/// Rule used to decide which rings are outer rings, and which are holes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Nesting {
+    /// Rings at an even nesting depth are outer rings, rings at an odd one are
+    /// holes. The winding of the input is ignored.
+    #[default]
+    Containment,
+    /// Counter-clockwise rings (positive signed area) are outer rings,
+    /// clockwise ones are holes of the innermost outer ring containing them.
     ///
-    /// A ring belong to another one if it's a direct child of it.
-    fn belongs_to(&self, inner_id: usize, outer_id: usize) -> bool {
-        self.contains(outer_id, inner_id) && self.count_parents(inner_id) == 1
-    }
-
-    /// Counts how many ring contains the ring `id`.
-    fn count_parents(&self, id: usize) -> usize {
-        (0..self.rings.len()).fold(0, |acc, i| {
-            if self.is_assigned[i] {
-                return acc;
-            }
-            acc + usize::from(self.matrix[id * self.rings.len() + i])
-        })
-    }
+    /// In MVT tile coordinates (Y pointing down), this is the orientation of
+    /// exterior rings and holes mandated by the specification. Holes outside
+    /// of any outer ring, and rings without area, are dropped.
+    Winding,
 }
 
 impl From<RingHierarchy> for MultiPolygon<f64> {
     fn from(value: RingHierarchy) -> Self {
-        Self(value.into_iter().collect())
+        Self(value.into_polygons().collect())
     }
 }
 
@@ -1,5 +1,10 @@
 use super::*;
-use geo::{line_string, polygon};
+use geo::{Orient, line_string, orient::Direction, polygon};
+
+/// Expected output, with the consistent orientation of the hierarchy.
+fn oriented(shape: &MultiPolygon) -> MultiPolygon {
+    shape.orient(Direction::Default)
+}
 
 #[test]
 fn test_simple() {
@@ -17,7 +22,7 @@ fn test_simple() {
 
     let res = MultiPolygon::from(RingHierarchy::new(rings));
 
-    assert_eq!(res, expected);
+    assert_eq!(res, oriented(&expected));
 }
 
 #[test]
@@ -40,7 +45,7 @@ fn test_with_one_hole() {
 
     let res = MultiPolygon::from(RingHierarchy::new(rings));
 
-    assert_eq!(res, expected);
+    assert_eq!(res, oriented(&expected));
 }
 
 #[test]
@@ -66,7 +71,7 @@ fn test_with_multiple_holes() {
     ]]);
     let res = MultiPolygon::from(RingHierarchy::new(rings));
 
-    assert_eq!(res, expected);
+    assert_eq!(res, oriented(&expected));
 }
 
 #[test]
@@ -86,7 +91,7 @@ fn test_with_multiple_outers() {
     ]);
     let res = MultiPolygon::from(RingHierarchy::new(rings));
 
-    assert_eq!(res, expected);
+    assert_eq!(res, oriented(&expected));
 }
 
 #[test]
@@ -116,7 +121,7 @@ fn test_nested() {
 
     let res = MultiPolygon::from(RingHierarchy::new(rings));
 
-    assert_eq!(res, expected);
+    assert_eq!(res, oriented(&expected));
 }
 
 #[test]
@@ -165,7 +170,7 @@ fn test_gamut() {
 
     let res = MultiPolygon::from(RingHierarchy::new(rings));
 
-    assert_eq!(res, expected);
+    assert_eq!(res, oriented(&expected));
 }
 
 #[test]
@@ -189,5 +194,87 @@ fn tile_coordinate() {
 
     let res = MultiPolygon::from(RingHierarchy::new(rings));
 
+    assert_eq!(res, oriented(&expected));
+}
+
+#[test]
+fn many_holes() {
+    // A large square, riddled with 100×100 small square holes.
+    let square = |x: f64, y: f64, size: f64| line_string![(x: x, y: y), (x: x, y: y + size), (x: x + size, y: y + size), (x: x + size, y: y), (x: x, y: y)];
+    let holes = (0..100)
+        .flat_map(|i| (0..100).map(move |j| (3 * i + 1, 3 * j + 1)))
+        .map(|(x, y)| square(f64::from(x), f64::from(y), 1.))
+        .collect::<Vec<_>>();
+    let exterior = square(0., 0., 300.);
+    let rings = holes
+        .iter()
+        .cloned()
+        .chain(std::iter::once(exterior.clone()))
+        .collect();
+    let expected = MultiPolygon(vec![Polygon::new(exterior, holes)]);
+
+    let res = MultiPolygon::from(RingHierarchy::new(rings));
+
+    assert_eq!(res, oriented(&expected));
+}
+
+#[test]
+fn any_winding() {
+    #[rustfmt::skip]
+    let rings = vec![
+        line_string![(x: 2., y: 2.), (x: 2., y: 4.), (x: 4., y: 4.), (x: 4., y: 2.), (x: 2., y: 2.)],
+        line_string![(x: 1., y: 1.), (x: 1., y: 5.), (x: 5., y: 5.), (x: 5., y: 1.), (x: 1., y: 1.)],
+    ];
+    let mut reversed = rings.clone();
+    reversed[1].0.reverse();
+
+    let res = MultiPolygon::from(RingHierarchy::new(rings));
+
+    assert!(res.0[0].exterior().is_ccw());
+    assert!(res.0[0].interiors()[0].is_cw());
+    assert_eq!(MultiPolygon::from(RingHierarchy::new(reversed)), res);
+}
+
+#[test]
+fn winding_nesting() {
+    // ┏━━━━━━━┓
+    // ┃┏━━━━━┓┃  ┏━┓
+    // ┃┃┏━━━┓┃┃  ┗━┛
+    // ┃┃┃┏━┓┃┃┃
+    // ┃┃┃┗━┛┃┃┃
+    // ┃┃┗━━━┛┃┃
+    // ┃┗━━━━━┛┃
+    // ┗━━━━━━━┛
+    #[rustfmt::skip]
+    let mut rings = vec![
+        // Outer ring, with an outer ring inside (overlapping).
+        line_string![(x: 1., y: 1.), (x: 9., y: 1.), (x: 9., y: 9.), (x: 1., y: 9.), (x: 1., y: 1.)],
+        line_string![(x: 2., y: 2.), (x: 8., y: 2.), (x: 8., y: 8.), (x: 2., y: 8.), (x: 2., y: 2.)],
+        // Hole of the innermost outer ring, and an island inside it.
+        line_string![(x: 3., y: 3.), (x: 3., y: 7.), (x: 7., y: 7.), (x: 7., y: 3.), (x: 3., y: 3.)],
+        line_string![(x: 4., y: 4.), (x: 6., y: 4.), (x: 6., y: 6.), (x: 4., y: 6.), (x: 4., y: 4.)],
+        // Orphan hole.
+        line_string![(x: 11., y: 1.), (x: 11., y: 2.), (x: 12., y: 2.), (x: 12., y: 1.), (x: 11., y: 1.)],
+    ];
+    let expected = MultiPolygon(vec![
+        Polygon::new(rings[0].clone(), vec![]),
+        Polygon::new(rings[1].clone(), vec![rings[2].clone()]),
+        Polygon::new(rings[3].clone(), vec![]),
+    ]);
+
+    let res = MultiPolygon::from(RingHierarchy::with_nesting(
+        rings.clone(),
+        Nesting::Winding,
+    ));
     assert_eq!(res, expected);
+
+    // Containment ignores the winding.
+    rings.truncate(4);
+    let expected = MultiPolygon(vec![
+        Polygon::new(rings[0].clone(), vec![rings[1].clone()]),
+        Polygon::new(rings[2].clone(), vec![rings[3].clone()]),
+    ]);
+
+    let res = MultiPolygon::from(RingHierarchy::new(rings));
+    assert_eq!(res, oriented(&expected));
 }
@@ -0,0 +1,311 @@
+use crate::{StoreError, TileID};
+use geo::Coord;
+use h3o::{CellIndex, Resolution, geom::ContainmentMode};
+use std::{io, ops::Range};
+
+/// Offset of the resolution in the index bits.
+const RESOLUTION_OFFSET: u64 = 52;
+/// Bitmask to select the resolution bits.
+const RESOLUTION_MASK: u64 = 0b1111 << RESOLUTION_OFFSET;
+/// Size, in bits, of a direction.
+const DIRECTION_BITSIZE: u64 = 3;
+/// Bitmask to select a direction (at resolution 15).
+const DIRECTION_MASK: u64 = 0b111;
+
+/// A dataset of cells, stored as a sorted array of indexes.
+///
+/// Every cell must have the same resolution: thanks to the way H3 indexes are
+/// ordered, the descendants of a cell are stored contiguously and can be
+/// retrieved with a binary search.
+///
+/// The on-disk representation (see [`CellStore::write`]) is the raw sorted
+/// array, as little-endian 64-bit integers, which can be memory-mapped (with
+/// the `mmap` feature) to serve datasets larger than RAM.
+#[derive(Debug)]
+pub struct CellStore {
+    /// Resolution of the cells (`None` if empty).
+    resolution: Option<Resolution>,
+    /// Cells storage.
+    storage: Storage,
+}
+
+impl CellStore {
+    /// Builds a new store from a set of cells.
+    ///
+    /// # Errors
+    ///
+    /// All cell indexes must have the same resolution, otherwise
+    /// [`StoreError::HeterogeneousResolution`] is returned.
+    pub fn new(
+        cells: impl IntoIterator<Item = CellIndex>,
+    ) -> Result<Self, StoreError> {
+        let mut cells = cells.into_iter().map(u64::from).collect::<Vec<_>>();
+        cells.sort_unstable();
+        cells.dedup();
+
+        Self::with_storage(Storage::Memory(cells))
+    }
+
+    /// Memory-maps a store previously saved with [`CellStore::write`].
+    ///
+    /// The whole file is scanned once to be validated, and it must not be
+    /// modified while the store is alive.
+    ///
+    /// # Errors
+    ///
+    /// [`StoreError::Io`] is returned if the file cannot be mapped, and
+    /// [`StoreError::InvalidData`] if it doesn't contain a sorted array of
+    /// valid cells.
+    #[cfg(feature = "mmap")]
+    pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, StoreError> {
+        let file = std::fs::File::open(path).map_err(StoreError::Io)?;
+        #[expect(unsafe_code, reason = "memory-mapping is inherently unsafe")]
+        // SAFETY: the file is opened in read-only mode and must not be
+        // modified while being mapped (which is documented).
+        let mmap =
+            unsafe { memmap2::Mmap::map(&file) }.map_err(StoreError::Io)?;
+
+        Self::with_storage(Storage::Mapped(mmap))
+    }
+
+    /// Writes the store, in its on-disk representation, to `writer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the I/O error that occurred while writing, if any.
+    pub fn write(&self, mut writer: impl io::Write) -> io::Result<()> {
+        match self.storage {
+            Storage::Memory(ref cells) => {
+                for cell in cells {
+                    writer.write_all(&cell.to_le_bytes())?;
+                }
+                Ok(())
+            }
+            #[cfg(feature = "mmap")]
+            Storage::Mapped(ref mmap) => writer.write_all(mmap),
+        }
+    }
+
+    /// Returns the number of cells in the store.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.storage.len()
+    }
+
+    /// Returns true if the store is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the resolution of the cells, if any.
+    #[must_use]
+    pub const fn resolution(&self) -> Option<Resolution> {
+        self.resolution
+    }
+
+    /// Returns true if the store contains the given cell.
+    #[must_use]
+    pub fn contains(&self, cell: CellIndex) -> bool {
+        let value = u64::from(cell);
+        let index = self.storage.partition_point(|other| other < value);
+        index < self.len() && self.storage.get(index) == value
+    }
+
+    /// Returns an iterator over the cells of the store, in order.
+    pub fn iter(&self) -> impl Iterator<Item = CellIndex> + '_ {
+        self.range(0..self.len())
+    }
+
+    /// Returns the cells under the given cell.
+    ///
+    /// If `parent` is finer than the cells in the store, the result is empty.
+    pub fn descendants(
+        &self,
+        parent: CellIndex,
+    ) -> impl Iterator<Item = CellIndex> + '_ {
+        let range = self
+            .resolution
+            .and_then(|resolution| descendants_range(parent, resolution))
+            .map_or(0..0, |Range { start, end }| {
+                let start = self.storage.partition_point(|value| value < start);
+                let end = self.storage.partition_point(|value| value < end);
+                start..end
+            });
+
+        self.range(range)
+    }
+
+    /// Returns the cells covering the given tile, buffer included.
+    #[must_use]
+    pub fn cells(&self, tile_id: TileID) -> Vec<CellIndex> {
+        let Some(resolution) = self.resolution else {
+            return Vec::new();
+        };
+        let coverage = tile_id.coverage(
+            resolution,
+            ContainmentMode::Covers,
+            TileID::buffer(),
+        );
+
+        coverage
+            .compacted()
+            .iter()
+            .flat_map(|&parent| self.descendants(parent))
+            .collect()
+    }
+
+    /// Returns the cell of the store under a point of the tile (in tile extent
+    /// units), if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are not finite.
+    #[must_use]
+    pub fn cell_at(&self, tile_id: TileID, coord: Coord) -> Option<CellIndex> {
+        self.resolution
+            .map(|resolution| tile_id.cell_at(coord, resolution))
+            .filter(|&cell| self.contains(cell))
+    }
+
+    /// Initializes a store from unvalidated storage.
+    fn with_storage(storage: Storage) -> Result<Self, StoreError> {
+        let resolution = storage.validate()?;
+
+        Ok(Self {
+            resolution,
+            storage,
+        })
+    }
+
+    /// Returns the cells in the given range of the storage.
+    fn range(
+        &self,
+        range: Range<usize>,
+    ) -> impl Iterator<Item = CellIndex> + '_ {
+        range.map(|index| {
+            CellIndex::try_from(self.storage.get(index))
+                .expect("validated cell")
+        })
+    }
+}
+
+/// Returns the range of indexes covering the descendants, at `resolution`, of
+/// the given cell.
+fn descendants_range(
+    parent: CellIndex,
+    resolution: Resolution,
+) -> Option<Range<u64>> {
+    let parent_resolution = u8::from(parent.resolution());
+    let resolution = u8::from(resolution);
+    if parent_resolution > resolution {
+        return None;
+    }
+
+    // Unused directions are already set to 7, thus the last descendant is
+    // the parent at a finer resolution.
+    let bits = u64::from(parent);
+    let last = (bits & !RESOLUTION_MASK)
+        | (u64::from(resolution) << RESOLUTION_OFFSET);
+    // And the first one is obtained by zeroing the new directions.
+    let first =
+        ((parent_resolution + 1)..=resolution).fold(last, |acc, res| {
+            let offset = u64::from(15 - res) * DIRECTION_BITSIZE;
+            acc & !(DIRECTION_MASK << offset)
+        });
+
+    Some(first..last + 1)
+}
+
+// -----------------------------------------------------------------------------
+
+/// Backing storage of a cell store.
+#[derive(Debug)]
+enum Storage {
+    /// Sorted cells, in memory.
+    Memory(Vec<u64>),
+    /// Sorted cells, as little-endian bytes in a memory-mapped file.
+    #[cfg(feature = "mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl Storage {
+    /// Returns the number of values.
+    #[cfg_attr(
+        not(feature = "mmap"),
+        expect(clippy::missing_const_for_fn, reason = "not with mmap")
+    )]
+    fn len(&self) -> usize {
+        match *self {
+            Self::Memory(ref values) => values.len(),
+            #[cfg(feature = "mmap")]
+            Self::Mapped(ref bytes) => bytes.len() / size_of::<u64>(),
+        }
+    }
+
+    /// Returns the value at `index`.
+    fn get(&self, index: usize) -> u64 {
+        match *self {
+            Self::Memory(ref values) => values[index],
+            #[cfg(feature = "mmap")]
+            Self::Mapped(ref bytes) => {
+                let offset = index * size_of::<u64>();
+                let chunk = &bytes[offset..offset + size_of::<u64>()];
+                u64::from_le_bytes(chunk.try_into().expect("8-byte chunk"))
+            }
+        }
+    }
+
+    /// Returns the index of the first value for which `predicate` is false.
+    ///
+    /// Values must be partitioned according to `predicate`.
+    fn partition_point(&self, predicate: impl Fn(u64) -> bool) -> usize {
+        let (mut low, mut high) = (0, self.len());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            if predicate(self.get(middle)) {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+        low
+    }
+
+    /// Checks that the storage contains a sorted array of unique cells, with
+    /// the same resolution.
+    ///
+    /// Returns the resolution of the cells, if any.
+    fn validate(&self) -> Result<Option<Resolution>, StoreError> {
+        #[cfg(feature = "mmap")]
+        if let Self::Mapped(ref bytes) = *self
+            && bytes.len() % size_of::<u64>() != 0
+        {
+            return Err(StoreError::InvalidData(bytes.len()));
+        }
+
+        let mut previous = None;
+        let mut resolution = None;
+        for index in 0..self.len() {
+            let value = self.get(index);
+            let Ok(cell) = CellIndex::try_from(value) else {
+                return Err(StoreError::InvalidData(index * size_of::<u64>()));
+            };
+            if previous.is_some_and(|previous| previous >= value) {
+                return Err(StoreError::InvalidData(index * size_of::<u64>()));
+            }
+            if *resolution.get_or_insert_with(|| cell.resolution())
+                != cell.resolution()
+            {
+                return Err(StoreError::HeterogeneousResolution(cell));
+            }
+            previous = Some(value);
+        }
+
+        Ok(resolution)
+    }
+}
+
+#[cfg(test)]
+#[path = "./store_tests.rs"]
+mod tests;
@@ -0,0 +1,170 @@
+use super::*;
+use ahash::HashSet;
+use h3o::LatLng;
+
+fn cell(value: u64) -> CellIndex {
+    CellIndex::try_from(value).expect("valid cell")
+}
+
+#[test]
+fn descendants() {
+    let parent = cell(0x851fb467fffffff);
+    let other = cell(0x851fb463fffffff);
+    let cells = parent
+        .children(Resolution::Nine)
+        .chain(other.children(Resolution::Nine))
+        .collect::<Vec<_>>();
+    let store = CellStore::new(cells.iter().copied()).expect("store");
+
+    for res in Resolution::range(Resolution::Zero, Resolution::Five) {
+        let ancestor = parent.parent(res).expect("ancestor");
+        let expected = cells
+            .iter()
+            .copied()
+            .filter(|cell| cell.parent(res) == Some(ancestor))
+            .collect::<HashSet<_>>();
+        let result = store.descendants(ancestor).collect::<HashSet<_>>();
+
+        assert_eq!(result, expected, "resolution {res}");
+    }
+
+    let child = cells[0].center_child(Resolution::Ten).expect("child");
+    assert_eq!(store.descendants(child).count(), 0);
+}
+
+#[test]
+fn descendants_pentagon() {
+    let pentagon = cell(0x8009fffffffffff);
+    let store =
+        CellStore::new(pentagon.children(Resolution::Three)).expect("store");
+
+    let result = store.descendants(pentagon).collect::<Vec<_>>();
+
+    assert_eq!(result.len(), 286);
+    assert_eq!(
+        result,
+        pentagon.children(Resolution::Three).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn tile_cells() {
+    let tile = TileID::new(3266, 1926, 12).expect("tile");
+    let cells = [
+        0x8a65b508518ffff,
+        0x8a65b5085c57fff,
+        0x8a65b50858dffff,
+        0x8a65b519b66ffff,
+        0x8a65b5083457fff,
+        0x8a65b50855a7fff,
+        0x8a65b50b434ffff,
+        0x8a65b50b4757fff,
+    ]
+    .into_iter()
+    .map(cell)
+    .collect::<HashSet<_>>();
+    let store = CellStore::new(cells.iter().copied()).expect("store");
+    let expected = &cells & &tile.cells(Resolution::Ten);
+
+    let result = store.cells(tile);
+
+    assert!(!expected.is_empty());
+    assert_eq!(result.len(), expected.len());
+    assert_eq!(result.into_iter().collect::<HashSet<_>>(), expected);
+}
+
+#[test]
+fn contains() {
+    let store = CellStore::new([cell(0x8a1fb46622dffff)]).expect("store");
+
+    assert!(store.contains(cell(0x8a1fb46622dffff)));
+    assert!(!store.contains(cell(0x8a1fb46622d7fff)));
+}
+
+#[test]
+fn cell_at() {
+    let store = CellStore::new([cell(0x8a1fb46622dffff)]).expect("store");
+    let center = |cell| {
+        let center =
+            crate::tile::TileCoord::from_ll(LatLng::from(cell).into(), 18);
+        (center.tile_id(), center.project(center.tile_id()))
+    };
+
+    let (tile, point) = center(cell(0x8a1fb46622dffff));
+    assert_eq!(store.cell_at(tile, point), Some(cell(0x8a1fb46622dffff)));
+    let (tile, point) = center(cell(0x8a1fb46622d7fff));
+    assert_eq!(store.cell_at(tile, point), None);
+    let empty = CellStore::new([]).expect("store");
+    assert_eq!(empty.cell_at(tile, point), None);
+}
+
+#[test]
+fn empty() {
+    let store = CellStore::new([]).expect("store");
+
+    assert!(store.is_empty());
+    assert_eq!(store.resolution(), None);
+    assert!(store.cells(TileID::new(0, 0, 0).expect("tile")).is_empty());
+}
+
+#[test]
+fn heterogeneous_resolution() {
+    let result =
+        CellStore::new([cell(0x8a1fb46622dffff), cell(0x851fb467fffffff)]);
+
+    assert!(matches!(
+        result,
+        Err(StoreError::HeterogeneousResolution(_))
+    ));
+}
+
+#[test]
+fn write() {
+    let cells = [cell(0x8a1fb46622dffff), cell(0x8a1fb46622d7fff)];
+    let store = CellStore::new(cells).expect("store");
+    let mut bytes = Vec::new();
+
+    store.write(&mut bytes).expect("write");
+
+    assert_eq!(bytes.len(), 16);
+    assert_eq!(&bytes[..8], &0x8a1fb46622d7fff_u64.to_le_bytes());
+    assert_eq!(&bytes[8..], &0x8a1fb46622dffff_u64.to_le_bytes());
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn open() {
+    let cells = cell(0x851fb467fffffff)
+        .children(Resolution::Eight)
+        .collect::<Vec<_>>();
+    let store = CellStore::new(cells.iter().copied()).expect("store");
+    let path = std::env::temp_dir().join("h3o-mvt-store-open.bin");
+    let file = std::fs::File::create(&path).expect("create");
+    store.write(file).expect("write");
+
+    let mapped = CellStore::open(&path).expect("open");
+
+    assert_eq!(mapped.resolution(), Some(Resolution::Eight));
+    assert_eq!(mapped.iter().collect::<Vec<_>>(), cells);
+    assert_eq!(
+        mapped
+            .descendants(cells[0].parent(Resolution::Seven).expect("parent"))
+            .count(),
+        7
+    );
+    std::fs::remove_file(&path).expect("cleanup");
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn open_invalid() {
+    let path = std::env::temp_dir().join("h3o-mvt-store-invalid.bin");
+    let mut bytes = 0x8a1fb46622dffff_u64.to_le_bytes().to_vec();
+    bytes.extend_from_slice(&0x8a1fb46622d7fff_u64.to_le_bytes());
+    std::fs::write(&path, &bytes).expect("write");
+
+    let result = CellStore::open(&path);
+
+    assert!(matches!(result, Err(StoreError::InvalidData(8))));
+    std::fs::remove_file(&path).expect("cleanup");
+}
@@ -1,11 +1,11 @@
-use crate::error::InvalidTileID;
+use crate::{Limits, RenderingError, coverage, error::InvalidTileID};
 use ahash::HashSet;
-use geo::{Coord, MultiPolygon, Rect, coord};
+use geo::{BoundingRect, Coord, MultiPolygon, Rect, coord};
 use h3o::{
-    CellIndex, Resolution,
+    CellIndex, LatLng, Resolution,
     geom::{ContainmentMode, TilerBuilder},
 };
-use std::f64::consts::PI;
+use std::{f64::consts::PI, fmt, str::FromStr};
 
 /// Maximum zoom level.
 const MAX_ZOOM: u8 = 31;
@@ -14,14 +14,14 @@ const MAX_ZOOM: u8 = 31;
 /// Cf. <https://github.com/mapbox/vector-tile-spec/blob/master/2.1/README.md>
 const TILE_SIZE: u32 = 4096;
 
+/// Number of cells expanded between two checks of the limits.
+const CHECK_INTERVAL: usize = 4096;
+
 /// Default buffer size.
 ///
 /// Used to render shapes that overlap multiple adjacent tiles.
 const BUFFER: u32 = 80;
 
-/// Tile padding.
-const PADDING: f64 = BUFFER as f64 * (1. / TILE_SIZE as f64);
-
 /// A tile identifier in a `xy` grid at zoom level `z`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Ord, PartialOrd)]
 pub struct TileID {
@@ -38,7 +38,7 @@ impl TileID {
     /// Returns an error if the tile coordinates (`x` and/or `y`) are invalid.
     pub const fn new(x: u32, y: u32, z: u8) -> Result<Self, InvalidTileID> {
         if z > MAX_ZOOM {
-            return Err(InvalidTileID::InvalidZ(x));
+            return Err(InvalidTileID::InvalidZ(z as u32));
         }
         let bound = (1 << z) - 1;
         if x > bound {
@@ -66,13 +66,68 @@ impl TileID {
     /// Returns cells covering the bounding box of the tile.
     #[must_use]
     pub fn cells(self, resolution: Resolution) -> HashSet<CellIndex> {
+        self.coverage(resolution, ContainmentMode::Covers, BUFFER)
+            .collect()
+    }
+
+    /// Returns cells covering the bounding box of the tile, like
+    /// [`TileID::cells`], within the given limits.
+    ///
+    /// The number of cells is known before expanding the coverage, so an
+    /// oversized coverage is rejected upfront.
+    ///
+    /// # Errors
+    ///
+    /// A `RenderingError::Aborted` is returned if a limit is exceeded.
+    pub fn cells_with_limits(
+        self,
+        resolution: Resolution,
+        limits: &Limits,
+    ) -> Result<HashSet<CellIndex>, RenderingError> {
+        limits.check()?;
+        let coverage =
+            self.coverage(resolution, ContainmentMode::Covers, BUFFER);
+        let (count, _) = coverage.size_hint();
+        limits.check_cells(count)?;
+
+        let mut cells = HashSet::default();
+        cells.reserve(count);
+        for (index, cell) in coverage.enumerate() {
+            if index % CHECK_INTERVAL == 0 {
+                limits.check()?;
+            }
+            cells.insert(cell);
+        }
+
+        Ok(cells)
+    }
+
+    /// Returns a lazy iterator over the cells covering the tile.
+    ///
+    /// The tile bounding box is padded by `buffer` (expressed in tile extent
+    /// units, e.g. `80` for the default buffer) before being polyfilled using
+    /// the specified containment mode.
+    ///
+    /// The coverage is first computed as a set of compacted cells (see
+    /// [`TileCoverage::compacted`]) that are only expanded to the requested
+    /// resolution on demand. Whatever the containment mode, only the cells
+    /// along the edges of the bounding box are tested at the requested
+    /// resolution.
+    #[must_use]
+    pub fn coverage(
+        self,
+        resolution: Resolution,
+        mode: ContainmentMode,
+        buffer: u32,
+    ) -> TileCoverage {
         let zoom_level = self.zoom();
 
         // At zoom level 0, the whole world is covered.
         if zoom_level == 0 {
-            return CellIndex::base_cells()
-                .flat_map(move |index| index.children(resolution))
-                .collect();
+            return TileCoverage::new(
+                CellIndex::base_cells().collect(),
+                resolution,
+            );
         }
 
         // Select a polyfill resolution that minimize the computation time.
@@ -82,7 +137,6 @@ impl TileID {
         // Since intersection is based on H3 cells anyway, a slightly bigger
         // bbox won't be slower, and the buffer allow to clip the protruding
         // parts at rendering time.
-        //
         let polyfill_res = std::cmp::min(
             resolution,
             match zoom_level {
@@ -101,17 +155,156 @@ impl TileID {
             },
         );
 
-        // We use the `Covers` containment mode to ensure 100% coverage.
+        let bbox = self.compute_bbox(buffer);
         let mut tiler = TilerBuilder::new(polyfill_res)
             .containment_mode(ContainmentMode::Covers)
             .build();
         // Compute the shape of the bounding box.
         // Note that in some cases it can be more complex than a simple rect.
-        tiler.add_batch(self.compute_bbox()).expect("invalid bbox");
-        // Polyfill at the selected resolution and convert to the requested one.
-        tiler
-            .into_coverage()
-            .flat_map(move |cell| cell.children(resolution))
+        tiler.add_batch(bbox.0.clone()).expect("invalid bbox");
+
+        // The bounding box may be split in several parts, which can results in
+        // duplicates along the seams.
+        let mut cells = tiler.into_coverage().collect::<Vec<_>>();
+        cells.sort_unstable();
+        cells.dedup();
+
+        // This trick only works with the `Covers` containment mode though, the
+        // other ones are sensitive to the polyfill resolution: the coarse
+        // coverage is refined along the edges of the bounding box instead.
+        if mode == ContainmentMode::Covers {
+            CellIndex::compact(&mut cells).expect("compactable coverage");
+        } else {
+            let rects = bbox
+                .iter()
+                .map(|part| part.bounding_rect().expect("bbox part"))
+                .collect::<Vec<_>>();
+            cells = coverage::polyfill(&rects, cells, resolution, mode);
+        }
+
+        TileCoverage::new(cells, resolution)
+    }
+
+    /// Converts a point of the tile, in tile extent units (from 0 to
+    /// [`TileID::extent`], Y pointing down), into EPSG:4326 coordinates.
+    ///
+    /// This is the inverse of the projection applied when rendering.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are not finite.
+    #[must_use]
+    pub fn unproject(self, coord: Coord) -> LatLng {
+        let coord = TileCoord::from_xy(coord, self).to_ll();
+        LatLng::new(coord.y, coord.x).expect("finite coordinates")
+    }
+
+    /// Returns the cell, at the given resolution, under a point of the tile
+    /// (in tile extent units).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the coordinates are not finite.
+    #[must_use]
+    pub fn cell_at(self, coord: Coord, resolution: Resolution) -> CellIndex {
+        self.unproject(coord).to_cell(resolution)
+    }
+
+    /// Returns the cell, at the given resolution, under the center of a pixel
+    /// of the tile rendered as a `size`×`size` image.
+    ///
+    /// Returns `None` if the pixel is outside of the image (which is always
+    /// the case if `size` is 0).
+    #[must_use]
+    pub fn cell_at_pixel(
+        self,
+        (x, y): (u32, u32),
+        size: u32,
+        resolution: Resolution,
+    ) -> Option<CellIndex> {
+        if x >= size || y >= size {
+            return None;
+        }
+        let scale = f64::from(TILE_SIZE) / f64::from(size);
+        let coord = coord! {
+            x: (f64::from(x) + 0.5) * scale,
+            y: (f64::from(y) + 0.5) * scale,
+        };
+        Some(self.cell_at(coord, resolution))
+    }
+
+    /// Initialize a new tile identifier from TMS coordinates, where `y`
+    /// points north.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tile coordinates are invalid.
+    pub const fn from_tms(
+        x: u32,
+        y: u32,
+        z: u8,
+    ) -> Result<Self, InvalidTileID> {
+        if z > MAX_ZOOM {
+            return Err(InvalidTileID::InvalidZ(z as u32));
+        }
+        let bound = (1 << z) - 1;
+        if y > bound {
+            return Err(InvalidTileID::InvalidY(y));
+        }
+        Self::new(x, bound - y, z)
+    }
+
+    /// Returns the TMS coordinate of the tile, where `y` points north.
+    #[must_use]
+    pub const fn tms_xy(self) -> (u32, u32) {
+        let bound = (1 << self.z) - 1;
+        (self.x, bound - self.y)
+    }
+
+    /// Initialize a new tile identifier from a Bing Maps quadkey.
+    ///
+    /// Each digit selects a quadrant of the parent tile, the empty quadkey
+    /// being the single tile at zoom 0.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the quadkey contains something else than `0`-`3`
+    /// digits, or is too long.
+    pub fn from_quadkey(quadkey: &str) -> Result<Self, InvalidTileID> {
+        let z = u8::try_from(quadkey.len())
+            .ok()
+            .filter(|&z| z <= MAX_ZOOM)
+            .ok_or_else(|| {
+                InvalidTileID::InvalidZ(
+                    u32::try_from(quadkey.len()).unwrap_or(u32::MAX),
+                )
+            })?;
+        let (mut x, mut y) = (0, 0);
+        for digit in quadkey.bytes() {
+            let quadrant = match digit {
+                b'0'..=b'3' => u32::from(digit - b'0'),
+                _ => {
+                    return Err(InvalidTileID::InvalidFormat(
+                        quadkey.to_owned(),
+                    ));
+                }
+            };
+            x = (x << 1) | (quadrant & 1);
+            y = (y << 1) | (quadrant >> 1);
+        }
+        Ok(Self::new_unchecked(x, y, z))
+    }
+
+    /// Returns the Bing Maps quadkey of the tile.
+    #[must_use]
+    pub fn quadkey(self) -> String {
+        (0..self.z)
+            .rev()
+            .map(|shift| {
+                let quadrant =
+                    ((self.x >> shift) & 1) | (((self.y >> shift) & 1) << 1);
+                char::from(b'0' + u8::try_from(quadrant).expect("quadrant"))
+            })
             .collect()
     }
 
@@ -134,6 +327,15 @@ impl TileID {
         })
     }
 
+    /// Returns the 4 children of the tile.
+    pub(crate) fn children(self) -> impl DoubleEndedIterator<Item = Self> {
+        let (x, y, z) = (self.x << 1, self.y << 1, self.zoom() + 1);
+
+        [(x, y), (x + 1, y), (x, y + 1), (x + 1, y + 1)]
+            .into_iter()
+            .map(move |(x, y)| Self::new_unchecked(x, y, z))
+    }
+
     /// Returns the 8 neighbors of the tile.
     pub(crate) fn neighbors(self) -> impl Iterator<Item = Self> {
         let (curr_x, curr_y, z) = (self.x, self.y, self.z);
@@ -156,19 +358,25 @@ impl TileID {
         .into_iter()
     }
 
-    /// Returns the extent size.
+    /// Returns the extent size, in tile coordinates.
     #[must_use]
-    pub(crate) const fn extent() -> u32 {
+    pub const fn extent() -> u32 {
         TILE_SIZE
     }
 
+    /// Returns the buffer size.
+    #[must_use]
+    pub(crate) const fn buffer() -> u32 {
+        BUFFER
+    }
+
     /// Returns true if the tile is in the eastern hemisphere.
     #[must_use]
     pub(crate) const fn is_eastern(&self) -> bool {
-        self.x > ((1 << self.z) / 2)
+        self.x >= ((1 << self.z) / 2)
     }
 
-    /// Computes the shape of the padded bounding box of this tile.
+    /// Computes the shape of the bounding box of this tile, padded by `buffer`.
     ///
     /// In most cases, it's just a rectangle.
     //
@@ -176,12 +384,9 @@ impl TileID {
     // around the world (e.g. crossing the antimeridian), the bounding box is
     // split into smaller components that can be polyfilled independanly and
     // then merged back to obtain the final H3 coverage.
-    fn compute_bbox(self) -> MultiPolygon {
+    fn compute_bbox(self, buffer: u32) -> MultiPolygon {
         // Compute the padded bounding box of the tile.
-        let (x, y, z) = (self.x, self.y, self.z);
-        let nw = TileCoord::with_padding(x, y, z, -PADDING);
-        let se = TileCoord::with_padding(x + 1, y + 1, z, PADDING);
-        let bbox = Rect::new(nw, se);
+        let bbox = self.buffered_bbox(buffer);
 
         // Common case: a trivial bounding box.
         if bbox_is_trivial(&bbox) {
@@ -232,12 +437,33 @@ impl TileID {
 
     /// Returns the bounding box of a tile, in EPSG:4326 coordinate.
     pub(crate) fn bbox(self) -> Rect {
+        self.buffered_bbox(0)
+    }
+
+    /// Returns the bounding box of a tile, padded by `buffer`, in EPSG:4326
+    /// coordinate.
+    ///
+    /// Note that the longitudes may extend beyond [-180, 180].
+    pub(crate) fn buffered_bbox(self, buffer: u32) -> Rect {
         let (x, y, z) = (self.x, self.y, self.z);
-        let nw = TileCoord::with_padding(x, y, z, 0.);
-        let se = TileCoord::with_padding(x + 1, y + 1, z, 0.);
+        let padding = f64::from(buffer) * (1. / f64::from(TILE_SIZE));
+        let nw = TileCoord::with_padding(x, y, z, -padding);
+        let se = TileCoord::with_padding(x + 1, y + 1, z, padding);
         Rect::new(nw, se)
     }
 
+    /// Returns the bounding box of a tile, padded by `buffer`, in world
+    /// coordinates (see [`TileCoord::world`]).
+    pub(crate) fn world_bbox(self, buffer: u32) -> Rect {
+        let n = f64::from(1 << self.z);
+        let padding = f64::from(buffer) * (1. / f64::from(TILE_SIZE));
+        let (x, y) = (f64::from(self.x), f64::from(self.y));
+        Rect::new(
+            ((x - padding) / n, (y - padding) / n),
+            ((x + 1. + padding) / n, (y + 1. + padding) / n),
+        )
+    }
+
     /// Returns the buffered shape of a tile, in relative tile coordinate.
     pub(crate) fn buffered_shape() -> Rect {
         let min = -f64::from(BUFFER);
@@ -246,6 +472,51 @@ impl TileID {
     }
 }
 
+impl fmt::Display for TileID {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.z, self.x, self.y)
+    }
+}
+
+impl FromStr for TileID {
+    type Err = InvalidTileID;
+
+    /// Parses a tile identifier in the `z/x/y` form.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || InvalidTileID::InvalidFormat(s.to_owned());
+        let mut parts = s.split('/').map(str::parse::<u32>);
+        let (Some(Ok(z)), Some(Ok(x)), Some(Ok(y)), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        let z = u8::try_from(z).map_err(|_| InvalidTileID::InvalidZ(z))?;
+        Self::new(x, y, z)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for TileID {
+    /// Serializes the tile identifier in the `z/x/y` form.
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for TileID {
+    /// Deserializes a tile identifier from the `z/x/y` form.
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        let value = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+        value.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 /// Check if the bounding box is trivial (i.e. can be processed as-is).
 ///
 /// A bounding box is trivial if it's not overly wide (won't trigger H3
@@ -260,6 +531,81 @@ fn bbox_is_trivial(bbox: &Rect) -> bool {
 
 // -----------------------------------------------------------------------------
 
+/// A lazy iterator over the cells covering a tile.
+///
+/// Returned by [`TileID::coverage`].
+#[derive(Debug, Clone)]
+pub struct TileCoverage {
+    /// Compacted coverage, sorted.
+    compacted: Vec<CellIndex>,
+    /// Target resolution.
+    resolution: Resolution,
+    /// Index of the compacted cell being expanded.
+    cursor: usize,
+    /// Position of the next child to yield, in the current compacted cell.
+    position: u64,
+}
+
+impl TileCoverage {
+    const fn new(compacted: Vec<CellIndex>, resolution: Resolution) -> Self {
+        Self {
+            compacted,
+            resolution,
+            cursor: 0,
+            position: 0,
+        }
+    }
+
+    /// Returns the compacted coverage, sorted.
+    ///
+    /// Every cell is at the target resolution or coarser, the whole coverage
+    /// is obtained by expanding them to the target resolution.
+    #[must_use]
+    pub fn compacted(&self) -> &[CellIndex] {
+        &self.compacted
+    }
+
+    /// Consumes the iterator into the compacted coverage.
+    #[must_use]
+    pub fn into_compacted(self) -> Vec<CellIndex> {
+        self.compacted
+    }
+
+    /// Returns the target resolution.
+    #[must_use]
+    pub const fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+}
+
+impl Iterator for TileCoverage {
+    type Item = CellIndex;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let parent = *self.compacted.get(self.cursor)?;
+            if let Some(child) = parent.child_at(self.position, self.resolution)
+            {
+                self.position += 1;
+                return Some(child);
+            }
+            self.cursor += 1;
+            self.position = 0;
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = CellIndex::uncompact_size(
+            self.compacted.iter().skip(self.cursor).copied(),
+            self.resolution,
+        ) - self.position;
+        usize::try_from(remaining)
+            .map_or((usize::MAX, None), |size| (size, Some(size)))
+    }
+}
+
+// -----------------------------------------------------------------------------
+
 /// Coordinate in a `xy` grid at zoom level `z`.
 #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
 pub struct TileCoord {
@@ -281,8 +627,31 @@ impl TileCoord {
         Self { x, y, z }
     }
 
+    /// Converts the EPSG:4326 coordinates into world coordinates: the grid at
+    /// zoom 0, where the whole world spans from 0 to 1.
+    #[must_use]
+    pub fn world(coord: Coord) -> Coord {
+        let Self { x, y, .. } = Self::from_ll(coord, 0);
+        Coord { x, y }
+    }
+
+    /// Converts world coordinates into coordinates in grid at zoom `z`.
+    ///
+    /// Scaling by a power of two is exact: the result is the same as with
+    /// [`Self::from_ll`] on the EPSG:4326 coordinates.
+    #[must_use]
+    pub fn from_world(coord: Coord, z: u8) -> Self {
+        assert!(z <= MAX_ZOOM, "z out of range ({z} > {MAX_ZOOM})");
+        let z = u32::from(z);
+        let n = f64::from(1 << z);
+        Self {
+            x: coord.x * n,
+            y: coord.y * n,
+            z,
+        }
+    }
+
     /// Initializes a new tile coordinate from `xy` offset in the given tile.
-    #[cfg(test)]
     #[must_use]
     pub fn from_xy(coord: Coord, tile_id: TileID) -> Self {
         Self {
@@ -293,7 +662,6 @@ impl TileCoord {
     }
 
     /// Converts the grid coordinates into EPSG:4326 coordinates.
-    #[cfg(test)]
     #[must_use]
     pub fn to_ll(self) -> Coord {
         let n = f64::from(1 << self.z);
@@ -302,17 +670,16 @@ impl TileCoord {
         Coord { x: lng, y: lat }
     }
 
-    /// Reprojects this coordinate as centered on the specified tile.
+    /// Reprojects this coordinate as centered on the specified tile, in tile
+    /// extent units.
+    ///
+    /// The result isn't quantized: this is done when encoding, after clipping.
     #[must_use]
-    #[expect(
-        clippy::cast_possible_truncation,
-        reason = "MVT use integer coordinates"
-    )]
     pub fn project(self, tile: TileID) -> Coord {
         let center = Self::from(tile);
         Coord {
-            x: f64::from(((self.x - center.x) * f64::from(TILE_SIZE)) as i32),
-            y: f64::from(((self.y - center.y) * f64::from(TILE_SIZE)) as i32),
+            x: (self.x - center.x) * f64::from(TILE_SIZE),
+            y: (self.y - center.y) * f64::from(TILE_SIZE),
         }
     }
 
@@ -63,6 +63,21 @@ fn neighbors_antimeridian() {
     assert_eq!(results, expected);
 }
 
+#[test]
+fn eastern() {
+    for zoom in 1..=19 {
+        let half = (1 << zoom) / 2;
+
+        assert!(!TileID::new_unchecked(0, 0, zoom).is_eastern(), "{zoom}");
+        assert!(
+            !TileID::new_unchecked(half - 1, 0, zoom).is_eastern(),
+            "{zoom}"
+        );
+        // Starts at the prime meridian.
+        assert!(TileID::new_unchecked(half, 0, zoom).is_eastern(), "{zoom}");
+    }
+}
+
 #[test]
 fn bbox_z0() {
     let tile = TileID::new_unchecked(0, 0, 0);
@@ -137,6 +152,305 @@ fn bbox_transmeridian_left() {
     assert_eq!(result, expected);
 }
 
+#[test]
+fn world() {
+    let coords = [
+        coord! { x: 5.6, y: 60. },
+        coord! { x: -179.99, y: -84.9 },
+        coord! { x: 2.349_014, y: 48.864_716 },
+    ];
+
+    for coord in coords {
+        let world = TileCoord::world(coord);
+        for zoom in [0, 1, 12, 18, 30] {
+            // Bit for bit.
+            assert_eq!(
+                TileCoord::from_world(world, zoom),
+                TileCoord::from_ll(coord, zoom),
+                "{coord:?}/{zoom}"
+            );
+        }
+    }
+
+    let tile = TileID::new_unchecked(2112, 1197, 12);
+    let bbox = tile.world_bbox(TileID::buffer());
+    let (min, max) = (
+        TileCoord::from_world(bbox.min(), 12).project(tile),
+        TileCoord::from_world(bbox.max(), 12).project(tile),
+    );
+    assert_eq!(Rect::new(min, max), TileID::buffered_shape());
+}
+
+#[test]
+fn unproject() {
+    let tile = TileID::new_unchecked(16595, 11273, 15);
+    let coord = coord! { x: 1234.5, y: 3210.25 };
+
+    let projected =
+        TileCoord::from_ll(tile.unproject(coord).into(), 15).project(tile);
+
+    assert_float_eq!(projected.x, coord.x, abs <= 1e-6);
+    assert_float_eq!(projected.y, coord.y, abs <= 1e-6);
+}
+
+#[test]
+fn cell_at() {
+    let cell = CellIndex::try_from(0x8a1fb46622dffff).expect("cell");
+    let center = TileCoord::from_ll(LatLng::from(cell).into(), 18);
+    let tile = center.tile_id();
+
+    assert_eq!(tile.cell_at(center.project(tile), Resolution::Ten), cell);
+    assert_eq!(
+        tile.cell_at(center.project(tile), Resolution::Nine),
+        cell.parent(Resolution::Nine).expect("parent")
+    );
+    assert_eq!(
+        tile.cell_at_pixel((3, 250), 256, Resolution::Ten),
+        Some(tile.cell_at(coord! { x: 56., y: 4008. }, Resolution::Ten))
+    );
+    assert_eq!(tile.cell_at_pixel((0, 0), 0, Resolution::Ten), None);
+    // Outside of the image, thus of the tile.
+    assert!(
+        tile.cell_at_pixel((255, 255), 256, Resolution::Ten)
+            .is_some()
+    );
+    assert_eq!(tile.cell_at_pixel((256, 3), 256, Resolution::Ten), None);
+    assert_eq!(tile.cell_at_pixel((3, 256), 256, Resolution::Ten), None);
+    assert_eq!(
+        tile.cell_at_pixel((u32::MAX, u32::MAX), 256, Resolution::Ten),
+        None
+    );
+}
+
+#[test]
+fn new() {
+    assert!(matches!(
+        TileID::new(0, 0, 32),
+        Err(InvalidTileID::InvalidZ(32))
+    ));
+    assert!(matches!(
+        TileID::new(4, 0, 2),
+        Err(InvalidTileID::InvalidX(4))
+    ));
+    assert!(matches!(
+        TileID::new(0, 4, 2),
+        Err(InvalidTileID::InvalidY(4))
+    ));
+}
+
+#[test]
+fn display() {
+    let tile = TileID::new_unchecked(16595, 11273, 15);
+
+    assert_eq!(tile.to_string(), "15/16595/11273");
+    assert_eq!("15/16595/11273".parse::<TileID>().ok(), Some(tile));
+}
+
+#[test]
+fn from_str_invalid() {
+    for value in ["", "15/16595", "15/16595/11273/1", "a/b/c", "15/-1/2"] {
+        assert!(
+            matches!(
+                value.parse::<TileID>(),
+                Err(InvalidTileID::InvalidFormat(ref input)) if input == value
+            ),
+            "{value}"
+        );
+    }
+    assert!(matches!(
+        "300/0/0".parse::<TileID>(),
+        Err(InvalidTileID::InvalidZ(300))
+    ));
+    assert!(matches!(
+        "2/0/4".parse::<TileID>(),
+        Err(InvalidTileID::InvalidY(4))
+    ));
+}
+
+#[test]
+fn quadkey() {
+    // Example from the Bing Maps Tile System documentation.
+    let tile = TileID::new_unchecked(3, 5, 3);
+
+    assert_eq!(tile.quadkey(), "213");
+    assert_eq!(TileID::from_quadkey("213").ok(), Some(tile));
+    assert_eq!(TileID::new_unchecked(0, 0, 0).quadkey(), "");
+    assert_eq!(
+        TileID::from_quadkey("").ok(),
+        Some(TileID::new_unchecked(0, 0, 0))
+    );
+    assert!(matches!(
+        TileID::from_quadkey("214"),
+        Err(InvalidTileID::InvalidFormat(_))
+    ));
+    assert!(matches!(
+        TileID::from_quadkey(&"0".repeat(32)),
+        Err(InvalidTileID::InvalidZ(32))
+    ));
+
+    let tile = TileID::new_unchecked(0x7fff_ffff, 0x5555_5555, 31);
+    assert_eq!(TileID::from_quadkey(&tile.quadkey()).ok(), Some(tile));
+}
+
+#[test]
+fn tms() {
+    let tile = TileID::new_unchecked(16595, 11273, 15);
+
+    assert_eq!(tile.tms_xy(), (16595, 21494));
+    assert_eq!(TileID::from_tms(16595, 21494, 15).ok(), Some(tile));
+    assert_eq!(TileID::new_unchecked(0, 0, 0).tms_xy(), (0, 0));
+    assert!(matches!(
+        TileID::from_tms(0, 4, 2),
+        Err(InvalidTileID::InvalidY(4))
+    ));
+    assert!(matches!(
+        TileID::from_tms(0, 0, 32),
+        Err(InvalidTileID::InvalidZ(32))
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde() {
+    let tile = TileID::new_unchecked(16595, 11273, 15);
+
+    let json = serde_json::to_string(&tile).expect("serialized");
+    assert_eq!(json, r#""15/16595/11273""#);
+    assert_eq!(serde_json::from_str::<TileID>(&json).ok(), Some(tile));
+    assert!(serde_json::from_str::<TileID>(r#""15/65536/0""#).is_err());
+}
+
+#[test]
+fn coverage_z0_is_lazy() {
+    let tile = TileID::new_unchecked(0, 0, 0);
+    let coverage =
+        tile.coverage(Resolution::Fifteen, ContainmentMode::Covers, BUFFER);
+
+    assert_eq!(coverage.compacted().len(), 122);
+    assert_eq!(
+        coverage.take(3).collect::<Vec<_>>(),
+        CellIndex::base_cells()
+            .next()
+            .expect("base cell")
+            .children(Resolution::Fifteen)
+            .take(3)
+            .collect::<Vec<_>>()
+    );
+}
+
+/// Computes the coverage of a tile like before the lazy coverage: Covers
+/// polyfill at a coarse resolution then expanded, or a polyfill at the target
+/// resolution for the other modes.
+fn eager_coverage(
+    tile: TileID,
+    resolution: Resolution,
+    mode: ContainmentMode,
+) -> HashSet<CellIndex> {
+    let polyfill_res = if mode == ContainmentMode::Covers {
+        std::cmp::min(
+            resolution,
+            match tile.zoom() {
+                0 | 1 => Resolution::Zero,
+                2 => Resolution::One,
+                3 | 4 => Resolution::Two,
+                5 => Resolution::Three,
+                6 | 7 => Resolution::Four,
+                8 => Resolution::Five,
+                9 => Resolution::Six,
+                10 | 11 => Resolution::Seven,
+                12 => Resolution::Eight,
+                13 | 14 => Resolution::Nine,
+                _ => Resolution::Ten,
+            },
+        )
+    } else {
+        resolution
+    };
+    let mut tiler = TilerBuilder::new(polyfill_res)
+        .containment_mode(mode)
+        .build();
+    tiler
+        .add_batch(tile.compute_bbox(BUFFER))
+        .expect("valid bbox");
+    tiler
+        .into_coverage()
+        .flat_map(|cell| cell.children(resolution))
+        .collect()
+}
+
+#[test]
+fn coverage_matches_eager() {
+    let tiles = [
+        (TileID::new_unchecked(0, 71776, 17), Resolution::Ten),
+        (TileID::new_unchecked(3266, 1926, 12), Resolution::Nine),
+        (TileID::new_unchecked(131, 87, 8), Resolution::Seven),
+        (TileID::new_unchecked(4, 2, 3), Resolution::Four),
+        // Polar and transmeridian bounding boxes.
+        (TileID::new_unchecked(0, 0, 1), Resolution::Two),
+        (TileID::new_unchecked(1, 1, 1), Resolution::Two),
+        (TileID::new_unchecked(3, 0, 2), Resolution::Three),
+        (TileID::new_unchecked(0, 3, 2), Resolution::Three),
+    ];
+    let modes = [
+        ContainmentMode::Covers,
+        ContainmentMode::ContainsCentroid,
+        ContainmentMode::ContainsBoundary,
+        ContainmentMode::IntersectsBoundary,
+    ];
+
+    for (tile, resolution) in tiles {
+        for mode in modes {
+            let coverage = tile.coverage(resolution, mode, BUFFER);
+            let compacted = coverage.compacted().to_vec();
+            let expected = eager_coverage(tile, resolution, mode);
+
+            assert!(compacted.windows(2).all(|pair| pair[0] < pair[1]));
+            assert_eq!(coverage.size_hint().0, expected.len());
+            assert_eq!(
+                coverage.collect::<HashSet<_>>(),
+                expected,
+                "{tile} {mode:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn coverage_is_lazy() {
+    // Millions of cells, described by a few thousands.
+    let tile = TileID::new_unchecked(9, 5, 4);
+    for mode in [
+        ContainmentMode::ContainsCentroid,
+        ContainmentMode::ContainsBoundary,
+        ContainmentMode::IntersectsBoundary,
+    ] {
+        let coverage = tile.coverage(Resolution::Eight, mode, BUFFER);
+
+        assert!(coverage.size_hint().0 > 1_000_000, "{mode:?}");
+        assert!(coverage.compacted().len() < 15_000, "{mode:?}");
+    }
+}
+
+#[test]
+fn coverage_containment_modes() {
+    let tile = TileID::new_unchecked(3266, 1926, 12);
+    let covers = tile
+        .coverage(Resolution::Ten, ContainmentMode::Covers, BUFFER)
+        .collect::<HashSet<_>>();
+    let centroid = tile
+        .coverage(Resolution::Ten, ContainmentMode::ContainsCentroid, BUFFER)
+        .collect::<HashSet<_>>();
+    let unbuffered = tile
+        .coverage(Resolution::Ten, ContainmentMode::ContainsCentroid, 0)
+        .collect::<HashSet<_>>();
+
+    assert!(!unbuffered.is_empty());
+    assert!(unbuffered.len() < centroid.len());
+    assert!(unbuffered.is_subset(&centroid));
+    assert!(centroid.len() < covers.len());
+    assert!(centroid.is_subset(&covers));
+}
+
 // Zoom level 1 is tricky because it features:
 // - Wide bounding boxes
 // - Bounding boxex crossing the antimeridian
@@ -145,7 +459,7 @@ fn bbox_transmeridian_left() {
 #[test]
 fn bbox_z1_nw() {
     let tile = TileID::new_unchecked(0, 0, 1);
-    let result = tile.compute_bbox();
+    let result = tile.compute_bbox(BUFFER);
     let expected = MultiPolygon(vec![
         // Main bbox, left part.
         polygon![
@@ -175,7 +489,7 @@ fn bbox_z1_nw() {
 #[test]
 fn bbox_z1_ne() {
     let tile = TileID::new_unchecked(1, 0, 1);
-    let result = tile.compute_bbox();
+    let result = tile.compute_bbox(BUFFER);
     let expected = MultiPolygon(vec![
         // Main bbox, left part.
         polygon![
@@ -205,7 +519,7 @@ fn bbox_z1_ne() {
 #[test]
 fn bbox_z1_sw() {
     let tile = TileID::new_unchecked(0, 1, 1);
-    let result = tile.compute_bbox();
+    let result = tile.compute_bbox(BUFFER);
     let expected = MultiPolygon(vec![
         // Main bbox, left part.
         polygon![
@@ -235,7 +549,7 @@ fn bbox_z1_sw() {
 #[test]
 fn bbox_z1_se() {
     let tile = TileID::new_unchecked(1, 1, 1);
-    let result = tile.compute_bbox();
+    let result = tile.compute_bbox(BUFFER);
     let expected = MultiPolygon(vec![
         // Main bbox, left part.
         polygon![
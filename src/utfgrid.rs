@@ -0,0 +1,286 @@
+use crate::{
+    PropertyValue, TileEncoder, TileFeature, TileID,
+    encoder::{write_properties, write_string},
+    render::fix_transmeridian,
+    tile::TileCoord,
+};
+use ahash::HashMap;
+use geo::{BoundingRect, Coord, LineString, MultiPolygon, Polygon};
+use h3o::CellIndex;
+
+/// UTFGrid encoder, mapping each pixel of a grid covering the tile to the
+/// feature covering its center.
+///
+/// The output is a UTFGrid 1.3 JSON document: the grid rows, the keys of the
+/// features and, for each key, the properties of the feature. Pixels outside
+/// of every feature map to the empty key.
+///
+/// As a [`TileEncoder`], each feature is keyed by its identifier (or its
+/// position, if it has none), and later features cover earlier ones. The
+/// cells of a dataset can also be keyed individually, by their index, with
+/// [`UtfGridEncoder::encode_cells`].
+///
+/// # Example
+///
+/// ```
+/// use h3o::CellIndex;
+/// use h3o_mvt::{UtfGridEncoder, tiles_for_cell};
+///
+/// let cell = CellIndex::try_from(0x891fb46622fffff)?;
+/// let tile_id = tiles_for_cell(cell, 16..=16).into_iter().next().expect("tile");
+///
+/// let json = UtfGridEncoder::new(64).encode_cells(tile_id, [(cell, &[][..])]);
+/// assert!(json.contains(r#""891fb46622fffff":{}"#));
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct UtfGridEncoder {
+    /// Width (and height) of the grid.
+    size: u32,
+    /// Key index of each pixel (0 if uncovered).
+    grid: Vec<u32>,
+    /// Keys and their data, as JSON (the empty key first).
+    keys: Vec<(String, String)>,
+    /// Index of the keys.
+    index: HashMap<String, u32>,
+}
+
+impl UtfGridEncoder {
+    /// Largest grid size.
+    ///
+    /// A grid shows at most one key per pixel, and every key must map to a
+    /// Unicode character: larger grids could run out of characters.
+    pub const MAX_SIZE: u32 = 1024;
+
+    /// Initializes a new encoder, for a grid of `size`×`size` pixels.
+    ///
+    /// The usual UTFGrid resolution of 4 gives a 64×64 grid for a 256×256
+    /// pixels tile. Sizes above [`Self::MAX_SIZE`] are clamped.
+    #[must_use]
+    pub fn new(size: u32) -> Self {
+        Self {
+            size: size.min(Self::MAX_SIZE),
+            grid: Vec::new(),
+            keys: Vec::new(),
+            index: HashMap::default(),
+        }
+    }
+
+    /// Returns the width (and height) of the grid.
+    #[must_use]
+    pub const fn size(&self) -> u32 {
+        self.size
+    }
+
+    /// Encodes the UTFGrid of the given cells, each one keyed by its index
+    /// and holding its properties.
+    ///
+    /// The cells are projected like when rendering the tile, so the grid
+    /// lines up with the rendered tiles.
+    pub fn encode_cells<'a>(
+        &mut self,
+        tile_id: TileID,
+        cells: impl IntoIterator<
+            Item = (CellIndex, &'a [(&'a str, PropertyValue<'a>)]),
+        >,
+    ) -> String {
+        self.clear();
+        let zoom = tile_id.zoom();
+        for (cell, properties) in cells {
+            let mut ring = LineString::from(cell.boundary());
+            fix_transmeridian(tile_id, &mut ring);
+            for coord in ring.coords_mut() {
+                *coord = TileCoord::from_ll(*coord, zoom).project(tile_id);
+            }
+            let geometry = MultiPolygon::new(vec![Polygon::new(ring, vec![])]);
+            self.paint(&cell.to_string(), &geometry, properties);
+        }
+        self.finish()
+    }
+
+    /// Resets the grid.
+    fn clear(&mut self) {
+        self.grid.clear();
+        self.grid
+            .resize(pixel_count(self.size) * pixel_count(self.size), 0);
+        self.keys.clear();
+        self.keys.push((String::new(), String::new()));
+        self.index.clear();
+    }
+
+    /// Maps the pixels covered by the geometry (in tile coordinates) to the
+    /// given key.
+    fn paint(
+        &mut self,
+        key: &str,
+        geometry: &MultiPolygon,
+        properties: &[(&str, PropertyValue<'_>)],
+    ) {
+        let Some(bbox) = geometry.bounding_rect() else {
+            return;
+        };
+        let scale = f64::from(TileID::extent()) / f64::from(self.size);
+        let (columns, rows) = (
+            pixel_range(bbox.min().x / scale, bbox.max().x / scale, self.size),
+            pixel_range(bbox.min().y / scale, bbox.max().y / scale, self.size),
+        );
+
+        let mut code = None;
+        for row in rows {
+            for column in columns.clone() {
+                let center = Coord {
+                    x: (f64::from(column) + 0.5) * scale,
+                    y: (f64::from(row) + 0.5) * scale,
+                };
+                if !covers(geometry, center) {
+                    continue;
+                }
+                let code = *code.get_or_insert_with(|| {
+                    *self.index.entry(key.to_owned()).or_insert_with(|| {
+                        let mut data = String::new();
+                        write_properties(&mut data, properties);
+                        self.keys.push((key.to_owned(), data));
+                        u32::try_from(self.keys.len() - 1).expect("key count")
+                    })
+                });
+                let offset = pixel_count(row) * pixel_count(self.size);
+                self.grid[offset + pixel_count(column)] = code;
+            }
+        }
+    }
+
+    /// Serializes the grid, keeping only the keys still visible.
+    fn finish(&self) -> String {
+        let mut codes = vec![None; self.keys.len()];
+        codes[0] = Some(0);
+        for &key in &self.grid {
+            codes[index(key)].get_or_insert(0);
+        }
+        for (count, code) in codes.iter_mut().flatten().enumerate() {
+            *code = u32::try_from(count).expect("key count");
+        }
+
+        let mut json = String::from(r#"{"grid":["#);
+        for (index, row) in
+            self.grid.chunks(pixel_count(self.size).max(1)).enumerate()
+        {
+            if index != 0 {
+                json.push(',');
+            }
+            json.push('"');
+            json.extend(
+                row.iter().map(|&key| {
+                    encode_id(codes[self::index(key)].unwrap_or(0))
+                }),
+            );
+            json.push('"');
+        }
+        json.push_str(r#"],"keys":["#);
+        let visible = || {
+            self.keys
+                .iter()
+                .zip(&codes)
+                .filter_map(|(key, code)| code.map(|_| key))
+        };
+        for (index, (key, _)) in visible().enumerate() {
+            if index != 0 {
+                json.push(',');
+            }
+            write_string(&mut json, key);
+        }
+        json.push_str(r#"],"data":{"#);
+        for (index, (key, data)) in visible().skip(1).enumerate() {
+            if index != 0 {
+                json.push(',');
+            }
+            write_string(&mut json, key);
+            json.push(':');
+            json.push_str(data);
+        }
+        json.push_str("}}");
+        json
+    }
+}
+
+impl TileEncoder for UtfGridEncoder {
+    type Output = String;
+
+    fn encode(&mut self, _name: &str, features: &[TileFeature<'_>]) -> String {
+        self.clear();
+        for (index, feature) in features.iter().enumerate() {
+            let key = feature
+                .id()
+                .map_or_else(|| index.to_string(), |id| id.to_string());
+            self.paint(&key, feature.geometry(), feature.properties());
+        }
+        self.finish()
+    }
+}
+
+/// Tests if a point is inside a shape (even-odd rule).
+///
+/// Points on an edge are attributed to a single side, so that adjacent shapes
+/// leave no gap between them.
+fn covers(geometry: &MultiPolygon, point: Coord) -> bool {
+    geometry
+        .iter()
+        .flat_map(|polygon| {
+            std::iter::once(polygon.exterior()).chain(polygon.interiors())
+        })
+        .flat_map(LineString::lines)
+        .filter(|line| {
+            (line.start.y > point.y) != (line.end.y > point.y)
+                && point.x
+                    < (line.end.x - line.start.x) * (point.y - line.start.y)
+                        / (line.end.y - line.start.y)
+                        + line.start.x
+        })
+        .count()
+        % 2
+        == 1
+}
+
+/// Returns the pixels whose center is in the given range.
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_sign_loss,
+    reason = "clamped in the grid"
+)]
+fn pixel_range(min: f64, max: f64, size: u32) -> std::ops::Range<u32> {
+    let size = f64::from(size);
+    let start = (min - 0.5).ceil().clamp(0., size) as u32;
+    let end = ((max - 0.5).floor() + 1.).clamp(0., size) as u32;
+    start..end.max(start)
+}
+
+/// Returns the UTFGrid character of a key index.
+///
+/// Quotes, backslashes and surrogates are skipped. Every index of a grid of
+/// at most [`UtfGridEncoder::MAX_SIZE`] pixels wide has a character.
+const fn encode_id(id: u32) -> char {
+    let mut code = id + 32;
+    if code >= 34 {
+        code += 1;
+    }
+    if code >= 92 {
+        code += 1;
+    }
+    if code >= 0xd800 {
+        code += 0x800;
+    }
+    char::from_u32(code).expect("bounded by the grid size")
+}
+
+/// Converts a key index into an index.
+fn index(key: u32) -> usize {
+    usize::try_from(key).expect("key index")
+}
+
+/// Converts a number of pixels into a size.
+fn pixel_count(value: u32) -> usize {
+    usize::try_from(value).expect("grid size")
+}
+
+#[cfg(test)]
+#[path = "./utfgrid_tests.rs"]
+mod tests;
@@ -0,0 +1,142 @@
+use super::*;
+use crate::tiles_for_cell;
+use geo::polygon;
+use geojson::JsonValue;
+use h3o::{LatLng, Resolution};
+
+fn parse(json: &str) -> (Vec<Vec<char>>, Vec<String>, JsonValue) {
+    let mut value = json.parse::<JsonValue>().expect("JSON");
+    let grid = value["grid"]
+        .as_array()
+        .expect("grid")
+        .iter()
+        .map(|row| row.as_str().expect("row").chars().collect())
+        .collect();
+    let keys = value["keys"]
+        .as_array()
+        .expect("keys")
+        .iter()
+        .map(|key| key.as_str().expect("key").to_owned())
+        .collect();
+    (grid, keys, value["data"].take())
+}
+
+/// Returns the key of a grid pixel.
+fn key_at(grid: &[Vec<char>], keys: &[String], x: usize, y: usize) -> String {
+    let id = (0..u32::try_from(keys.len()).expect("keys"))
+        .find(|&id| encode_id(id) == grid[y][x])
+        .expect("known character");
+    keys[index(id)].clone()
+}
+
+#[test]
+fn cells() {
+    let center = LatLng::new(48.85, 2.35)
+        .expect("coordinate")
+        .to_cell(Resolution::Ten);
+    let cells = center.grid_disk::<Vec<_>>(3);
+    let tile_id = tiles_for_cell(center, 17..=17)
+        .into_iter()
+        .find(|tile| tile.zoom() == 17)
+        .expect("tile");
+    let properties = [("value", PropertyValue::UInt(7))];
+
+    let json = UtfGridEncoder::new(64).encode_cells(
+        tile_id,
+        cells.iter().map(|&cell| (cell, &properties[..])),
+    );
+
+    let (grid, keys, data) = parse(&json);
+    assert_eq!(grid.len(), 64);
+    assert!(grid.iter().all(|row| row.len() == 64));
+    assert_eq!(keys[0], "");
+    assert!(keys.len() > 1);
+    for key in &keys[1..] {
+        assert_eq!(data[key]["value"], 7, "{key}");
+    }
+
+    // Pixels map to the cell under their center, except along the edges
+    // (straight in the tile, but not on the sphere).
+    let mut mismatches = 0;
+    for y in 0..64 {
+        for x in 0..64 {
+            let center = Coord {
+                x: (f64::from(x) + 0.5) * 64.,
+                y: (f64::from(y) + 0.5) * 64.,
+            };
+            let cell = tile_id.cell_at(center, Resolution::Ten);
+            let expected = if cells.contains(&cell) {
+                cell.to_string()
+            } else {
+                String::new()
+            };
+            let key = key_at(&grid, &keys, index(x), index(y));
+            mismatches += usize::from(key != expected);
+        }
+    }
+    assert!(mismatches < 64 * 64 / 100, "{mismatches} mismatches");
+}
+
+#[test]
+fn features() {
+    let left = MultiPolygon::new(vec![polygon![
+        (x: 0., y: 0.),
+        (x: 2048., y: 0.),
+        (x: 2048., y: 4096.),
+        (x: 0., y: 4096.),
+    ]]);
+    let right = MultiPolygon::new(vec![polygon![
+        (x: 2048., y: 0.),
+        (x: 4096., y: 0.),
+        (x: 4096., y: 4096.),
+        (x: 2048., y: 4096.),
+    ]]);
+    let hidden = MultiPolygon::new(vec![polygon![
+        (x: 3000., y: 0.),
+        (x: 4096., y: 0.),
+        (x: 4096., y: 1024.),
+    ]]);
+    let properties = [("name", PropertyValue::String("left \"side\""))];
+    let features = [
+        TileFeature::new(&left).with_properties(&properties),
+        TileFeature::new(&hidden).with_id(42),
+        TileFeature::new(&right).with_id(7),
+    ];
+
+    let json = UtfGridEncoder::new(4).encode("h3", &features);
+
+    // The feature 42 is entirely covered by the feature 7.
+    assert_eq!(
+        json,
+        r#"{"grid":["!!##","!!##","!!##","!!##"],"keys":["","0","7"],"data":{"0":{"name":"left \"side\""},"7":{}}}"#
+    );
+}
+
+#[test]
+fn empty() {
+    let json = UtfGridEncoder::new(2).encode("h3", &[]);
+
+    assert_eq!(json, r#"{"grid":["  ","  "],"keys":[""],"data":{}}"#);
+}
+
+#[test]
+fn ids() {
+    assert_eq!(encode_id(0), ' ');
+    assert_eq!(encode_id(1), '!');
+    assert_eq!(encode_id(2), '#');
+    assert_eq!(encode_id(58), '[');
+    assert_eq!(encode_id(59), ']');
+    assert_eq!(encode_id(0xd800 - 34), '\u{e000}');
+}
+
+#[test]
+fn size_is_bounded() {
+    assert_eq!(UtfGridEncoder::new(64).size(), 64);
+    assert_eq!(
+        UtfGridEncoder::new(u32::MAX).size(),
+        UtfGridEncoder::MAX_SIZE
+    );
+    // Every pixel of the largest grid can show its own key.
+    let max = UtfGridEncoder::MAX_SIZE * UtfGridEncoder::MAX_SIZE;
+    assert_eq!(encode_id(max), '\u{100822}');
+}
@@ -0,0 +1,519 @@
+use geozero::mvt::tile::{Feature, GeomType, Layer, Value};
+use std::fmt;
+
+/// Command to start a new part.
+const MOVE_TO: u32 = 1;
+/// Command to extend the current part.
+const LINE_TO: u32 = 2;
+/// Command to close the current ring.
+const CLOSE_PATH: u32 = 7;
+
+/// Default extent, when the layer doesn't specify one.
+const DEFAULT_EXTENT: u32 = 4096;
+
+/// Checks a MVT layer against the rules of the MVT 2.1 specification.
+///
+/// This checks the layer metadata, the tags and the geometry of every
+/// feature: well-formedness of the command stream, ring winding and area, and
+/// coordinates within the extent padded by `buffer` (expressed in extent
+/// units, layers rendered by this crate use a buffer of 80 for an extent of
+/// 4096).
+///
+/// Returns every finding, an empty list meaning the layer is valid.
+///
+/// See <https://github.com/mapbox/vector-tile-spec/tree/master/2.1>
+///
+/// # Example
+///
+/// ```
+/// use h3o::CellIndex;
+/// use h3o_mvt::{render, tiles_for_cell, validate_layer};
+///
+/// let cell = CellIndex::try_from(0x891fb46622fffff)?;
+/// for tile_id in tiles_for_cell(cell, 14..=14) {
+///     let layer = render(tile_id, [cell], "h3".to_owned(), false)?;
+///     assert!(validate_layer(&layer, 80).is_empty());
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[must_use]
+pub fn validate_layer(layer: &Layer, buffer: u32) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    if layer.version != 2 {
+        findings.push(Finding::layer(Issue::UnsupportedVersion(layer.version)));
+    }
+    if layer.name.is_empty() {
+        findings.push(Finding::layer(Issue::EmptyName));
+    }
+    let extent = layer.extent.unwrap_or(DEFAULT_EXTENT);
+    if extent == 0 {
+        findings.push(Finding::layer(Issue::InvalidExtent));
+    }
+    for (index, value) in layer.values.iter().enumerate() {
+        if field_count(value) != 1 {
+            findings.push(Finding::layer(Issue::InvalidValue(index)));
+        }
+    }
+
+    let bounds = (-i64::from(buffer), i64::from(extent) + i64::from(buffer));
+    for (index, feature) in layer.features.iter().enumerate() {
+        let mut report = |issue| findings.push(Finding::feature(index, issue));
+        check_tags(layer, feature, &mut report);
+        check_geometry(feature, bounds, &mut report);
+    }
+
+    findings
+}
+
+/// Checks that the tags are pairs of valid key and value indexes.
+fn check_tags(
+    layer: &Layer,
+    feature: &Feature,
+    report: &mut impl FnMut(Issue),
+) {
+    if !feature.tags.len().is_multiple_of(2) {
+        report(Issue::OddTagCount);
+    }
+    for pair in feature.tags.chunks_exact(2) {
+        if !usize::try_from(pair[0]).is_ok_and(|key| key < layer.keys.len()) {
+            report(Issue::InvalidKeyIndex(pair[0]));
+        }
+        if !usize::try_from(pair[1])
+            .is_ok_and(|value| value < layer.values.len())
+        {
+            report(Issue::InvalidValueIndex(pair[1]));
+        }
+    }
+}
+
+/// Checks the geometry of a feature, according to its type.
+fn check_geometry(
+    feature: &Feature,
+    bounds: (i64, i64),
+    report: &mut impl FnMut(Issue),
+) {
+    let kind = feature
+        .r#type
+        .and_then(GeomType::from_i32)
+        .unwrap_or(GeomType::Unknown);
+    if kind == GeomType::Unknown {
+        report(Issue::UnknownGeometryType);
+        return;
+    }
+    if feature.geometry.is_empty() {
+        report(Issue::EmptyGeometry);
+        return;
+    }
+
+    let mut reader = Reader {
+        geometry: &feature.geometry,
+        offset: 0,
+        cursor: (0, 0),
+        bounds,
+        out_of_bounds: false,
+        ring: Vec::new(),
+    };
+    let result = match kind {
+        GeomType::Point => reader.points(report),
+        GeomType::Linestring => reader.lines(report),
+        GeomType::Polygon | GeomType::Unknown => reader.polygons(report),
+    };
+    if let Err(issue) = result {
+        report(issue);
+    }
+}
+
+/// Returns the number of fields set on a value.
+fn field_count(value: &Value) -> usize {
+    [
+        value.string_value.is_some(),
+        value.float_value.is_some(),
+        value.double_value.is_some(),
+        value.int_value.is_some(),
+        value.uint_value.is_some(),
+        value.sint_value.is_some(),
+        value.bool_value.is_some(),
+    ]
+    .into_iter()
+    .filter(|&is_set| is_set)
+    .count()
+}
+
+// -----------------------------------------------------------------------------
+
+/// Command stream reader.
+///
+/// Malformed streams (unknown or misplaced commands, missing parameters) stop
+/// the reading, whereas other issues are reported as they are found.
+struct Reader<'a> {
+    /// Command stream.
+    geometry: &'a [u32],
+    /// Position in the command stream.
+    offset: usize,
+    /// Current position of the cursor.
+    cursor: (i64, i64),
+    /// Allowed range for the coordinates.
+    bounds: (i64, i64),
+    /// Whether an out-of-bounds coordinate has already been reported.
+    out_of_bounds: bool,
+    /// Vertices of the current ring.
+    ring: Vec<(i64, i64)>,
+}
+
+impl Reader<'_> {
+    /// Reads a point geometry: a single `MoveTo`.
+    fn points(&mut self, report: &mut impl FnMut(Issue)) -> Result<(), Issue> {
+        let count = self.command(MOVE_TO)?;
+        if count == 0 {
+            report(Issue::InvalidCommandCount {
+                offset: self.offset - 1,
+                count,
+            });
+        }
+        for _ in 0..count {
+            self.vertex(report)?;
+        }
+        self.end()
+    }
+
+    /// Reads a linestring geometry: a sequence of `MoveTo` and `LineTo`.
+    fn lines(&mut self, report: &mut impl FnMut(Issue)) -> Result<(), Issue> {
+        while self.offset < self.geometry.len() {
+            self.move_to(report)?;
+            let count = self.command(LINE_TO)?;
+            if count == 0 {
+                report(Issue::InvalidCommandCount {
+                    offset: self.offset - 1,
+                    count,
+                });
+            }
+            self.line_to(count, report)?;
+        }
+        Ok(())
+    }
+
+    /// Reads a polygon geometry: a sequence of `MoveTo`, `LineTo` and
+    /// `ClosePath`.
+    fn polygons(
+        &mut self,
+        report: &mut impl FnMut(Issue),
+    ) -> Result<(), Issue> {
+        let mut ring = 0;
+        while self.offset < self.geometry.len() {
+            self.move_to(report)?;
+            let count = self.command(LINE_TO)?;
+            self.line_to(count, report)?;
+            let count = self.command(CLOSE_PATH)?;
+            if count != 1 {
+                report(Issue::InvalidCommandCount {
+                    offset: self.offset - 1,
+                    count,
+                });
+            }
+
+            let area = double_signed_area(&self.ring);
+            if self.ring.len() < 3 {
+                report(Issue::RingTooShort { ring });
+            } else if area == 0 {
+                report(Issue::ZeroAreaRing { ring });
+            } else if ring == 0 && area < 0 {
+                report(Issue::InvalidWinding { ring });
+            }
+            ring += 1;
+        }
+        Ok(())
+    }
+
+    /// Reads a `MoveTo` with a single vertex.
+    fn move_to(&mut self, report: &mut impl FnMut(Issue)) -> Result<(), Issue> {
+        self.ring.clear();
+        let count = self.command(MOVE_TO)?;
+        if count != 1 {
+            report(Issue::InvalidCommandCount {
+                offset: self.offset - 1,
+                count,
+            });
+        }
+        for _ in 0..count {
+            self.vertex(report)?;
+        }
+        Ok(())
+    }
+
+    /// Reads the vertices of a `LineTo`.
+    fn line_to(
+        &mut self,
+        count: u32,
+        report: &mut impl FnMut(Issue),
+    ) -> Result<(), Issue> {
+        for _ in 0..count {
+            let offset = self.offset;
+            let previous = self.cursor;
+            self.vertex(report)?;
+            if self.cursor == previous {
+                report(Issue::RepeatedVertex { offset });
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a command header, checking its identifier.
+    ///
+    /// Returns the command count.
+    fn command(&mut self, expected: u32) -> Result<u32, Issue> {
+        let offset = self.offset;
+        let &header = self
+            .geometry
+            .get(offset)
+            .ok_or(Issue::Truncated { offset })?;
+        let id = header & 0x7;
+        if ![MOVE_TO, LINE_TO, CLOSE_PATH].contains(&id) {
+            return Err(Issue::InvalidCommand { offset, id });
+        }
+        if id != expected {
+            return Err(Issue::UnexpectedCommand { offset, id });
+        }
+        self.offset += 1;
+        Ok(header >> 3)
+    }
+
+    /// Reads a vertex, moving the cursor.
+    fn vertex(&mut self, report: &mut impl FnMut(Issue)) -> Result<(), Issue> {
+        let offset = self.offset;
+        let [dx, dy] = *self
+            .geometry
+            .get(offset..offset + 2)
+            .and_then(|params| <&[u32; 2]>::try_from(params).ok())
+            .ok_or(Issue::Truncated { offset })?;
+        self.offset += 2;
+        self.cursor.0 += i64::from(unzigzag(dx));
+        self.cursor.1 += i64::from(unzigzag(dy));
+        self.ring.push(self.cursor);
+
+        let (min, max) = self.bounds;
+        let (x, y) = self.cursor;
+        let inside = (min..=max).contains(&x) && (min..=max).contains(&y);
+        if !inside && !self.out_of_bounds {
+            self.out_of_bounds = true;
+            report(Issue::OutOfBounds { x, y });
+        }
+        Ok(())
+    }
+
+    /// Checks that the whole command stream has been read.
+    fn end(&self) -> Result<(), Issue> {
+        self.geometry.get(self.offset).map_or(Ok(()), |&header| {
+            Err(Issue::UnexpectedCommand {
+                offset: self.offset,
+                id: header & 0x7,
+            })
+        })
+    }
+}
+
+/// Computes twice the signed area of a ring (positive when the ring is
+/// clockwise in tile coordinates).
+fn double_signed_area(vertices: &[(i64, i64)]) -> i128 {
+    let next = vertices.iter().cycle().skip(1);
+    vertices
+        .iter()
+        .zip(next)
+        .map(|(&(x0, y0), &(x1, y1))| {
+            i128::from(x0) * i128::from(y1) - i128::from(x1) * i128::from(y0)
+        })
+        .sum()
+}
+
+/// Decodes a zigzag-encoded parameter.
+#[expect(clippy::cast_possible_wrap, reason = "zigzag decoding")]
+const fn unzigzag(value: u32) -> i32 {
+    ((value >> 1) as i32) ^ -((value & 1) as i32)
+}
+
+// -----------------------------------------------------------------------------
+
+/// A violation of the MVT specification, found in a layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Finding {
+    /// Index of the offending feature, if any.
+    feature: Option<usize>,
+    /// The violated rule.
+    issue: Issue,
+}
+
+impl Finding {
+    /// Initializes a finding about the layer itself.
+    const fn layer(issue: Issue) -> Self {
+        Self {
+            feature: None,
+            issue,
+        }
+    }
+
+    /// Initializes a finding about a feature.
+    const fn feature(index: usize, issue: Issue) -> Self {
+        Self {
+            feature: Some(index),
+            issue,
+        }
+    }
+
+    /// Returns the index of the offending feature, if the finding isn't about
+    /// the layer itself.
+    #[must_use]
+    pub const fn feature_index(&self) -> Option<usize> {
+        self.feature
+    }
+
+    /// Returns the violated rule.
+    #[must_use]
+    pub const fn issue(&self) -> Issue {
+        self.issue
+    }
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.feature {
+            Some(index) => write!(f, "feature {index}: {}", self.issue),
+            None => write!(f, "layer: {}", self.issue),
+        }
+    }
+}
+
+/// Rules of the MVT specification.
+///
+/// Offsets are positions in the geometry command stream, and rings are
+/// numbered from 0 within a feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Issue {
+    /// Unsupported layer version (only version 2 is supported).
+    UnsupportedVersion(u32),
+    /// The layer name is empty.
+    EmptyName,
+    /// The layer extent is zero.
+    InvalidExtent,
+    /// The value at this index doesn't have exactly one field set.
+    InvalidValue(usize),
+    /// The tags aren't a list of key/value pairs.
+    OddTagCount,
+    /// The tag references a key that doesn't exist.
+    InvalidKeyIndex(u32),
+    /// The tag references a value that doesn't exist.
+    InvalidValueIndex(u32),
+    /// The geometry type is missing or unknown.
+    UnknownGeometryType,
+    /// The geometry is empty.
+    EmptyGeometry,
+    /// Unknown command identifier.
+    InvalidCommand {
+        /// Position of the command.
+        offset: usize,
+        /// Command identifier.
+        id: u32,
+    },
+    /// Command not allowed at this position, for this geometry type.
+    UnexpectedCommand {
+        /// Position of the command.
+        offset: usize,
+        /// Command identifier.
+        id: u32,
+    },
+    /// Command count not allowed for this command.
+    InvalidCommandCount {
+        /// Position of the command.
+        offset: usize,
+        /// Command count.
+        count: u32,
+    },
+    /// The command stream ends before a command or its parameters.
+    Truncated {
+        /// Position of the missing data.
+        offset: usize,
+    },
+    /// A `LineTo` doesn't move the cursor.
+    RepeatedVertex {
+        /// Position of the parameters.
+        offset: usize,
+    },
+    /// A coordinate lies outside of the extent padded by the buffer (only the
+    /// first one of a feature is reported).
+    OutOfBounds {
+        /// X coordinate.
+        x: i64,
+        /// Y coordinate.
+        y: i64,
+    },
+    /// The ring has less than 3 vertices.
+    RingTooShort {
+        /// Index of the ring.
+        ring: usize,
+    },
+    /// The ring has no area.
+    ZeroAreaRing {
+        /// Index of the ring.
+        ring: usize,
+    },
+    /// The first ring of a polygon has a negative area (i.e. it's a hole).
+    InvalidWinding {
+        /// Index of the ring.
+        ring: usize,
+    },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::UnsupportedVersion(version) => {
+                write!(f, "unsupported version: {version}")
+            }
+            Self::EmptyName => write!(f, "empty layer name"),
+            Self::InvalidExtent => write!(f, "invalid extent: 0"),
+            Self::InvalidValue(index) => {
+                write!(f, "value {index} must have exactly one field set")
+            }
+            Self::OddTagCount => write!(f, "odd number of tags"),
+            Self::InvalidKeyIndex(index) => {
+                write!(f, "invalid key index: {index}")
+            }
+            Self::InvalidValueIndex(index) => {
+                write!(f, "invalid value index: {index}")
+            }
+            Self::UnknownGeometryType => write!(f, "unknown geometry type"),
+            Self::EmptyGeometry => write!(f, "empty geometry"),
+            Self::InvalidCommand { offset, id } => {
+                write!(f, "invalid command {id} at offset {offset}")
+            }
+            Self::UnexpectedCommand { offset, id } => {
+                write!(f, "unexpected command {id} at offset {offset}")
+            }
+            Self::InvalidCommandCount { offset, count } => {
+                write!(f, "invalid command count {count} at offset {offset}")
+            }
+            Self::Truncated { offset } => {
+                write!(f, "truncated geometry at offset {offset}")
+            }
+            Self::RepeatedVertex { offset } => {
+                write!(f, "repeated vertex at offset {offset}")
+            }
+            Self::OutOfBounds { x, y } => {
+                write!(f, "coordinate out of bounds: ({x}, {y})")
+            }
+            Self::RingTooShort { ring } => {
+                write!(f, "ring {ring} has less than 3 vertices")
+            }
+            Self::ZeroAreaRing { ring } => {
+                write!(f, "ring {ring} has no area")
+            }
+            Self::InvalidWinding { ring } => {
+                write!(f, "ring {ring} must be an exterior ring")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "./validate_tests.rs"]
+mod tests;
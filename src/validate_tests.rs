@@ -0,0 +1,276 @@
+use super::*;
+use crate::{TileID, render, tiles_for_cell};
+use h3o::CellIndex;
+
+fn command(id: u32, count: u32) -> u32 {
+    id | (count << 3)
+}
+
+fn zigzag(value: i32) -> u32 {
+    u32::try_from((i64::from(value) << 1) ^ (i64::from(value) >> 63))
+        .expect("zigzag")
+}
+
+/// Encodes rings as a polygon command stream.
+fn polygon(rings: &[&[(i32, i32)]]) -> Vec<u32> {
+    let mut geometry = Vec::new();
+    let mut cursor = (0, 0);
+    for ring in rings {
+        for (index, &(x, y)) in ring.iter().enumerate() {
+            match index {
+                0 => geometry.push(command(MOVE_TO, 1)),
+                1 => geometry.push(command(
+                    LINE_TO,
+                    u32::try_from(ring.len() - 1).expect("count"),
+                )),
+                _ => (),
+            }
+            geometry.extend([zigzag(x - cursor.0), zigzag(y - cursor.1)]);
+            cursor = (x, y);
+        }
+        geometry.push(command(CLOSE_PATH, 1));
+    }
+    geometry
+}
+
+fn layer(kind: GeomType, geometry: Vec<u32>) -> Layer {
+    Layer {
+        version: 2,
+        name: "test".to_owned(),
+        features: vec![Feature {
+            r#type: Some(kind.into()),
+            geometry,
+            ..Feature::default()
+        }],
+        extent: Some(4096),
+        ..Layer::default()
+    }
+}
+
+fn issues(layer: &Layer) -> Vec<Issue> {
+    validate_layer(layer, 80)
+        .iter()
+        .map(Finding::issue)
+        .collect()
+}
+
+const SQUARE: &[(i32, i32)] = &[(0, 0), (100, 0), (100, 100), (0, 100)];
+const HOLE: &[(i32, i32)] = &[(10, 10), (10, 90), (90, 90), (90, 10)];
+
+#[test]
+fn rendered() {
+    let center = CellIndex::try_from(0x891fb46622fffff).expect("cell");
+    let cells = center.grid_disk::<Vec<_>>(8);
+
+    for scratch in [false, true] {
+        for tile_id in tiles_for_cell(center, 5..=16)
+            .into_iter()
+            .chain([TileID::new(0, 0, 0).expect("tile")])
+        {
+            let layer = render(
+                tile_id,
+                cells.iter().copied(),
+                "h3".to_owned(),
+                scratch,
+            )
+            .expect("render");
+
+            assert_eq!(validate_layer(&layer, 80), [], "{tile_id:?}");
+        }
+    }
+}
+
+#[test]
+fn valid() {
+    let layer = layer(GeomType::Polygon, polygon(&[SQUARE, HOLE, SQUARE]));
+
+    assert_eq!(issues(&layer), []);
+}
+
+#[test]
+fn metadata() {
+    let mut layer = layer(GeomType::Polygon, polygon(&[SQUARE]));
+    layer.version = 1;
+    layer.name.clear();
+    layer.extent = Some(0);
+    layer.values = vec![Value::default()];
+    layer.features.clear();
+
+    let findings = validate_layer(&layer, 80);
+
+    assert!(
+        findings
+            .iter()
+            .all(|finding| finding.feature_index().is_none())
+    );
+    assert_eq!(
+        findings.iter().map(Finding::issue).collect::<Vec<_>>(),
+        [
+            Issue::UnsupportedVersion(1),
+            Issue::EmptyName,
+            Issue::InvalidExtent,
+            Issue::InvalidValue(0),
+        ]
+    );
+}
+
+#[test]
+fn tags() {
+    let mut layer = layer(GeomType::Polygon, polygon(&[SQUARE]));
+    layer.keys = vec!["key".to_owned()];
+    layer.values = vec![Value {
+        bool_value: Some(true),
+        ..Value::default()
+    }];
+    layer.features[0].tags = vec![0, 0, 1, 2, 0];
+
+    let findings = validate_layer(&layer, 80);
+
+    assert!(
+        findings
+            .iter()
+            .all(|finding| finding.feature_index() == Some(0))
+    );
+    assert_eq!(
+        findings.iter().map(Finding::issue).collect::<Vec<_>>(),
+        [
+            Issue::OddTagCount,
+            Issue::InvalidKeyIndex(1),
+            Issue::InvalidValueIndex(2),
+        ]
+    );
+}
+
+#[test]
+fn geometry_type() {
+    let mut unknown = layer(GeomType::Unknown, polygon(&[SQUARE]));
+    assert_eq!(issues(&unknown), [Issue::UnknownGeometryType]);
+
+    unknown.features[0].r#type = Some(42);
+    assert_eq!(issues(&unknown), [Issue::UnknownGeometryType]);
+
+    let empty = layer(GeomType::Polygon, Vec::new());
+    assert_eq!(issues(&empty), [Issue::EmptyGeometry]);
+}
+
+#[test]
+fn malformed() {
+    let mut geometry = polygon(&[SQUARE]);
+    geometry[0] = command(3, 1);
+    assert_eq!(
+        issues(&layer(GeomType::Polygon, geometry)),
+        [Issue::InvalidCommand { offset: 0, id: 3 }]
+    );
+
+    // LineTo without a MoveTo.
+    let geometry = polygon(&[SQUARE])[3..].to_vec();
+    assert_eq!(
+        issues(&layer(GeomType::Polygon, geometry)),
+        [Issue::UnexpectedCommand { offset: 0, id: 2 }]
+    );
+
+    // Missing ClosePath.
+    let mut geometry = polygon(&[SQUARE]);
+    geometry.pop();
+    assert_eq!(
+        issues(&layer(GeomType::Polygon, geometry)),
+        [Issue::Truncated { offset: 10 }]
+    );
+
+    // Missing parameter.
+    let mut geometry = polygon(&[SQUARE]);
+    geometry.truncate(9);
+    assert_eq!(
+        issues(&layer(GeomType::Polygon, geometry)),
+        [Issue::Truncated { offset: 8 }]
+    );
+
+    // ClosePath in a linestring.
+    let geometry = polygon(&[SQUARE]);
+    assert_eq!(
+        issues(&layer(GeomType::Linestring, geometry)),
+        [Issue::UnexpectedCommand { offset: 10, id: 7 }]
+    );
+
+    // Points don't have LineTo.
+    let geometry = polygon(&[SQUARE]);
+    assert_eq!(
+        issues(&layer(GeomType::Point, geometry)),
+        [Issue::UnexpectedCommand { offset: 3, id: 2 }]
+    );
+}
+
+#[test]
+fn command_count() {
+    let mut geometry = polygon(&[SQUARE]);
+    geometry[10] = command(CLOSE_PATH, 2);
+    assert_eq!(
+        issues(&layer(GeomType::Polygon, geometry)),
+        [Issue::InvalidCommandCount {
+            offset: 10,
+            count: 2
+        }]
+    );
+
+    let geometry = vec![command(MOVE_TO, 0)];
+    assert_eq!(
+        issues(&layer(GeomType::Point, geometry)),
+        [Issue::InvalidCommandCount {
+            offset: 0,
+            count: 0
+        }]
+    );
+}
+
+#[test]
+fn rings() {
+    let geometry = polygon(&[&[(0, 0), (100, 0)]]);
+    assert_eq!(
+        issues(&layer(GeomType::Polygon, geometry)),
+        [Issue::RingTooShort { ring: 0 }]
+    );
+
+    let geometry = polygon(&[SQUARE, &[(0, 0), (100, 0), (50, 0)]]);
+    assert_eq!(
+        issues(&layer(GeomType::Polygon, geometry)),
+        [Issue::ZeroAreaRing { ring: 1 }]
+    );
+
+    let geometry = polygon(&[&[(0, 0), (100, 0), (100, 0), (0, 100)]]);
+    assert_eq!(
+        issues(&layer(GeomType::Polygon, geometry)),
+        [Issue::RepeatedVertex { offset: 6 }]
+    );
+}
+
+#[test]
+fn winding() {
+    let geometry = polygon(&[HOLE, SQUARE]);
+
+    assert_eq!(
+        issues(&layer(GeomType::Polygon, geometry)),
+        [Issue::InvalidWinding { ring: 0 }]
+    );
+}
+
+#[test]
+fn out_of_bounds() {
+    let geometry = polygon(&[&[(-80, -80), (4176, -80), (4176, 4176)]]);
+    assert_eq!(issues(&layer(GeomType::Polygon, geometry)), []);
+
+    let geometry = polygon(&[&[(-81, 0), (4190, -90), (4176, 4176)]]);
+    let layer = layer(GeomType::Polygon, geometry);
+    assert_eq!(issues(&layer), [Issue::OutOfBounds { x: -81, y: 0 }]);
+    assert_eq!(validate_layer(&layer, 100), []);
+}
+
+#[test]
+fn display() {
+    let findings = [
+        Finding::layer(Issue::EmptyName),
+        Finding::feature(3, Issue::ZeroAreaRing { ring: 1 }),
+    ];
+
+    assert_eq!(findings[0].to_string(), "layer: empty layer name");
+    assert_eq!(findings[1].to_string(), "feature 3: ring 1 has no area");
+}
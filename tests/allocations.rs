@@ -0,0 +1,105 @@
+//! Counts the allocations made when rendering tiles.
+//!
+//! This lives in its own test binary, as it replaces the global allocator.
+
+use h3o::{CellIndex, geom::SolventBuilder};
+use h3o_mvt::{Renderer, TileID, render, tiles_for_cell};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+/// Allocator counting the allocations made by the current thread.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+// SAFETY: every call is forwarded to the system allocator.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        // SAFETY: same contract as the caller.
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: same contract as the caller.
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        // SAFETY: same contract as the caller.
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Returns the number of allocations made by `f`.
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+fn dataset() -> (Vec<CellIndex>, Vec<TileID>) {
+    let center = CellIndex::try_from(0x891fb46622fffff).expect("cell");
+    let cells = center.grid_disk::<Vec<_>>(4);
+    let mut tiles = cells
+        .iter()
+        .flat_map(|&cell| tiles_for_cell(cell, 15..=15))
+        .collect::<Vec<_>>();
+    tiles.sort_unstable();
+    tiles.dedup();
+
+    (cells, tiles)
+}
+
+#[test]
+fn renderer_saves_allocations() {
+    let (cells, tiles) = dataset();
+    let mut renderer = Renderer::new("h3".to_owned(), false);
+    let mut buffer = Vec::new();
+    // Warm up the buffers.
+    for &tile_id in &tiles {
+        buffer.clear();
+        renderer
+            .render_to_bytes(tile_id, cells.iter().copied(), &mut buffer)
+            .expect("render");
+    }
+
+    // The solvent allocates its vertex graph on every call, whatever the
+    // renderer does: only the other allocations are compared.
+    let solvent = SolventBuilder::new().build();
+    let dissolve = allocations(|| {
+        for _ in &tiles {
+            solvent.dissolve(cells.iter().copied()).expect("dissolve");
+        }
+    });
+    let fresh = allocations(|| {
+        for &tile_id in &tiles {
+            render(tile_id, cells.iter().copied(), "h3".to_owned(), false)
+                .expect("render");
+        }
+    });
+    let reused = allocations(|| {
+        for &tile_id in &tiles {
+            buffer.clear();
+            renderer
+                .render_to_bytes(tile_id, cells.iter().copied(), &mut buffer)
+                .expect("render");
+        }
+    });
+
+    let (fresh, reused) = (fresh - dissolve, reused - dissolve);
+    assert!(reused * 2 < fresh, "{fresh} vs {reused} allocations");
+}